@@ -35,8 +35,8 @@ async fn main() -> Result<()> {
         input_mint: USDC_MINT,
         output_mint: NATIVE_MINT,
         // Restrict the route search to specific DEXes for potential latency reduction.
-        dexes: Some("Whirlpool,Meteora DLMM,Raydium CLMM".into()),
-        slippage_bps: 50, // 0.5% slippage tolerance
+        dexes: Some(vec!["Whirlpool".into(), "Meteora DLMM".into(), "Raydium CLMM".into()]),
+        slippage_bps: Some(50), // 0.5% slippage tolerance
         ..QuoteRequest::default()
     };
 
@@ -0,0 +1,87 @@
+//! Serializes swaps touching the same wallet or token accounts while unrelated swaps run
+//! concurrently, avoiding transactions of the same process failing on each other's account locks.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{Arc, Mutex, PoisonError, Weak},
+};
+
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+
+use crate::{solana_types::Pubkey, swap::SwapRequest};
+
+/// Queue per account, shared by every task executing swaps
+///
+/// Locks are acquired in a consistent order, so tasks locking overlapping sets of accounts cannot
+/// deadlock, and each queue is released once no task waits on it.
+#[derive(Debug, Default)]
+pub struct AccountLocks {
+    locks: Mutex<HashMap<Pubkey, Weak<AsyncMutex<()>>>>,
+}
+
+/// Holds the accounts until dropped
+#[derive(Debug)]
+pub struct AccountLocksGuard {
+    _guards: Vec<OwnedMutexGuard<()>>,
+}
+
+impl AccountLocks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Waits until no other task holds any of `accounts`, then holds them until the guard is dropped
+    pub async fn lock(&self, accounts: impl IntoIterator<Item = Pubkey>) -> AccountLocksGuard {
+        let accounts = accounts.into_iter().collect::<BTreeSet<_>>();
+        let account_locks = {
+            let mut locks = self.locks.lock().unwrap_or_else(PoisonError::into_inner);
+            locks.retain(|_, lock| lock.strong_count() > 0);
+            accounts
+                .iter()
+                .map(|account| {
+                    let lock = locks.entry(*account).or_default();
+                    lock.upgrade().unwrap_or_else(|| {
+                        let account_lock = Arc::new(AsyncMutex::new(()));
+                        *lock = Arc::downgrade(&account_lock);
+                        account_lock
+                    })
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut guards = Vec::with_capacity(account_locks.len());
+        for account_lock in account_locks {
+            guards.push(account_lock.lock_owned().await);
+        }
+        AccountLocksGuard { _guards: guards }
+    }
+
+    /// Locks the accounts of `swap_request` written by the swap, see [`SwapRequest::locked_accounts`]
+    pub async fn lock_swap(&self, swap_request: &SwapRequest) -> AccountLocksGuard {
+        self.lock(swap_request.locked_accounts()).await
+    }
+
+    /// Number of accounts currently held or waited on
+    pub fn len(&self) -> usize {
+        self.locks
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .filter(|lock| lock.strong_count() > 0)
+            .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl SwapRequest {
+    /// Accounts of the user the swap writes to: the wallet, which stands for all of its token
+    /// accounts, and the destination token account which may belong to someone else
+    pub fn locked_accounts(&self) -> Vec<Pubkey> {
+        let mut accounts = vec![self.user_public_key];
+        accounts.extend(self.config.destination_token_account);
+        accounts
+    }
+}
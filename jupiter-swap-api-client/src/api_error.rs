@@ -0,0 +1,86 @@
+//! Typed error payloads returned by the API with non-success responses, see [`JupiterApiError`].
+
+use std::{convert::Infallible, fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::serde_helpers::option_field_as_string;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ApiErrorCode {
+    CouldNotFindAnyRoute,
+    NoRoutesFound,
+    TokenNotTradable,
+    MarketNotFound,
+    CircularArbitrageIsDisabled,
+    RoutePlanDoesNotConsumeAllTheAmount,
+    CannotComputeOtherAmountThreshold,
+    InvalidInputMint,
+    InvalidOutputMint,
+    NotSupported,
+    /// A code unknown to this version of the client
+    Other(String),
+}
+
+impl ApiErrorCode {
+    /// No route can be found for the pair and amount, retrying the same request will not help
+    pub fn is_no_route(&self) -> bool {
+        matches!(
+            self,
+            Self::CouldNotFindAnyRoute | Self::NoRoutesFound | Self::TokenNotTradable
+        )
+    }
+}
+
+impl fmt::Display for ApiErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::CouldNotFindAnyRoute => "COULD_NOT_FIND_ANY_ROUTE",
+            Self::NoRoutesFound => "NO_ROUTES_FOUND",
+            Self::TokenNotTradable => "TOKEN_NOT_TRADABLE",
+            Self::MarketNotFound => "MARKET_NOT_FOUND",
+            Self::CircularArbitrageIsDisabled => "CIRCULAR_ARBITRAGE_IS_DISABLED",
+            Self::RoutePlanDoesNotConsumeAllTheAmount => {
+                "ROUTE_PLAN_DOES_NOT_CONSUME_ALL_THE_AMOUNT"
+            }
+            Self::CannotComputeOtherAmountThreshold => "CANNOT_COMPUTE_OTHER_AMOUNT_THRESHOLD",
+            Self::InvalidInputMint => "INVALID_INPUT_MINT",
+            Self::InvalidOutputMint => "INVALID_OUTPUT_MINT",
+            Self::NotSupported => "NOT_SUPPORTED",
+            Self::Other(code) => code,
+        })
+    }
+}
+
+impl FromStr for ApiErrorCode {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_ascii_uppercase().as_str() {
+            "COULD_NOT_FIND_ANY_ROUTE" => Self::CouldNotFindAnyRoute,
+            "NO_ROUTES_FOUND" => Self::NoRoutesFound,
+            "TOKEN_NOT_TRADABLE" => Self::TokenNotTradable,
+            "MARKET_NOT_FOUND" => Self::MarketNotFound,
+            "CIRCULAR_ARBITRAGE_IS_DISABLED" => Self::CircularArbitrageIsDisabled,
+            "ROUTE_PLAN_DOES_NOT_CONSUME_ALL_THE_AMOUNT" => {
+                Self::RoutePlanDoesNotConsumeAllTheAmount
+            }
+            "CANNOT_COMPUTE_OTHER_AMOUNT_THRESHOLD" => Self::CannotComputeOtherAmountThreshold,
+            "INVALID_INPUT_MINT" => Self::InvalidInputMint,
+            "INVALID_OUTPUT_MINT" => Self::InvalidOutputMint,
+            "NOT_SUPPORTED" => Self::NotSupported,
+            _ => Self::Other(s.to_string()),
+        })
+    }
+}
+
+/// JSON body of a non-success response, e.g.
+/// `{"error": "Could not find any route", "errorCode": "COULD_NOT_FIND_ANY_ROUTE"}`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct JupiterApiError {
+    #[serde(alias = "message")]
+    pub error: String,
+    #[serde(with = "option_field_as_string", default)]
+    pub error_code: Option<ApiErrorCode>,
+}
@@ -0,0 +1,34 @@
+//! Loading the API key from the OS keychain rather than plain environment variables, for desktop
+//! apps embedding the client.
+
+use keyring::Entry;
+
+/// Reads the API key stored in the OS keychain under `service` and `user`
+pub fn api_key_from_keyring(service: &str, user: &str) -> Result<String, keyring::Error> {
+    Entry::new(service, user)?.get_password()
+}
+
+/// Stores `api_key` in the OS keychain under `service` and `user`, replacing any previous key
+pub fn store_api_key_in_keyring(
+    service: &str,
+    user: &str,
+    api_key: &str,
+) -> Result<(), keyring::Error> {
+    Entry::new(service, user)?.set_password(api_key)
+}
+
+/// Removes the API key stored under `service` and `user`
+pub fn delete_api_key_from_keyring(service: &str, user: &str) -> Result<(), keyring::Error> {
+    Entry::new(service, user)?.delete_credential()
+}
+
+impl crate::JupiterSwapApiClient {
+    /// Sets the API key stored in the OS keychain under `service` and `user`
+    pub fn with_api_key_from_keyring(
+        self,
+        service: &str,
+        user: &str,
+    ) -> Result<Self, keyring::Error> {
+        Ok(self.with_api_key(api_key_from_keyring(service, user)?))
+    }
+}
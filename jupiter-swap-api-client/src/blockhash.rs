@@ -0,0 +1,109 @@
+//! Blockhash acquisition for the transaction building helpers, see [`BlockhashProvider`].
+
+use async_trait::async_trait;
+
+use crate::{solana_types::Hash, ClientError};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlockhashInfo {
+    pub blockhash: Hash,
+    /// Last block height at which a transaction using the blockhash can land
+    pub last_valid_block_height: u64,
+}
+
+/// Source of the recent blockhash of built transactions
+///
+/// Implementations can cache blockhashes, feed them from a websocket subscription or return a
+/// durable nonce, so building a transaction does not have to cost an RPC call.
+#[async_trait]
+pub trait BlockhashProvider: Send + Sync {
+    async fn blockhash(&self) -> Result<BlockhashInfo, ClientError>;
+}
+
+/// Always provides the same blockhash, typically the nonce of a durable nonce account
+#[derive(Clone, Copy, Debug)]
+pub struct FixedBlockhashProvider {
+    pub blockhash_info: BlockhashInfo,
+}
+
+impl FixedBlockhashProvider {
+    /// Durable nonces never expire
+    pub fn durable_nonce(nonce: Hash) -> Self {
+        Self {
+            blockhash_info: BlockhashInfo {
+                blockhash: nonce,
+                last_valid_block_height: u64::MAX,
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl BlockhashProvider for FixedBlockhashProvider {
+    async fn blockhash(&self) -> Result<BlockhashInfo, ClientError> {
+        Ok(self.blockhash_info)
+    }
+}
+
+#[cfg(feature = "rpc")]
+mod rpc {
+    use std::{
+        sync::Arc,
+        time::{Duration, Instant},
+    };
+
+    use solana_client::nonblocking::rpc_client::RpcClient;
+    use tokio::sync::Mutex;
+
+    use super::*;
+
+    /// Fetches the latest blockhash at the RPC client's commitment, reusing it for `max_age`
+    pub struct RpcBlockhashProvider {
+        rpc_client: Arc<RpcClient>,
+        max_age: Duration,
+        cached: Mutex<Option<(BlockhashInfo, Instant)>>,
+    }
+
+    impl RpcBlockhashProvider {
+        /// Blockhashes stay valid for about a minute, reusing them for a few seconds is safe
+        pub const DEFAULT_MAX_AGE: Duration = Duration::from_secs(5);
+
+        pub fn new(rpc_client: Arc<RpcClient>) -> Self {
+            Self::with_max_age(rpc_client, Self::DEFAULT_MAX_AGE)
+        }
+
+        pub fn with_max_age(rpc_client: Arc<RpcClient>, max_age: Duration) -> Self {
+            Self {
+                rpc_client,
+                max_age,
+                cached: Mutex::new(None),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl BlockhashProvider for RpcBlockhashProvider {
+        async fn blockhash(&self) -> Result<BlockhashInfo, ClientError> {
+            // Holding the lock while fetching lets concurrent callers share a single request
+            let mut cached = self.cached.lock().await;
+            if let Some((blockhash_info, fetched_at)) = *cached {
+                if fetched_at.elapsed() < self.max_age {
+                    return Ok(blockhash_info);
+                }
+            }
+            let (blockhash, last_valid_block_height) = self
+                .rpc_client
+                .get_latest_blockhash_with_commitment(self.rpc_client.commitment())
+                .await?;
+            let blockhash_info = BlockhashInfo {
+                blockhash,
+                last_valid_block_height,
+            };
+            *cached = Some((blockhash_info, Instant::now()));
+            Ok(blockhash_info)
+        }
+    }
+}
+
+#[cfg(feature = "rpc")]
+pub use rpc::RpcBlockhashProvider;
@@ -0,0 +1,112 @@
+//! Construction of a [`JupiterSwapApiClient`] with the HTTP settings production deployments need,
+//! see [`JupiterSwapApiClientBuilder`].
+
+use std::time::Duration;
+
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Client,
+};
+
+use crate::{ClientError, JupiterSwapApiClient};
+
+#[derive(Clone, Debug)]
+pub struct JupiterSwapApiClientBuilder {
+    base_path: String,
+    api_key: Option<String>,
+    api_key_header: Option<HeaderName>,
+    default_headers: HeaderMap,
+    connect_timeout: Option<Duration>,
+    /// Maximum time between two reads of a response
+    read_timeout: Option<Duration>,
+    pool_idle_timeout: Option<Duration>,
+    user_agent: Option<String>,
+}
+
+impl JupiterSwapApiClientBuilder {
+    pub fn new(base_path: String) -> Self {
+        Self {
+            base_path,
+            api_key: None,
+            api_key_header: None,
+            default_headers: HeaderMap::new(),
+            connect_timeout: None,
+            read_timeout: None,
+            pool_idle_timeout: None,
+            user_agent: None,
+        }
+    }
+
+    pub fn api_key(mut self, api_key: String) -> Self {
+        self.api_key = Some(api_key);
+        self
+    }
+
+    /// Header the API key is sent in, [`crate::API_KEY_HEADER`] by default
+    pub fn api_key_header(mut self, api_key_header: HeaderName) -> Self {
+        self.api_key_header = Some(api_key_header);
+        self
+    }
+
+    /// Sent with every request, see [`crate::ClientConfig::default_headers`]
+    pub fn default_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.default_headers.insert(name, value);
+        self
+    }
+
+    pub fn default_headers(mut self, default_headers: HeaderMap) -> Self {
+        self.default_headers.extend(default_headers);
+        self
+    }
+
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    pub fn read_timeout(mut self, read_timeout: Duration) -> Self {
+        self.read_timeout = Some(read_timeout);
+        self
+    }
+
+    pub fn pool_idle_timeout(mut self, pool_idle_timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(pool_idle_timeout);
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: String) -> Self {
+        self.user_agent = Some(user_agent);
+        self
+    }
+
+    pub fn build(self) -> Result<JupiterSwapApiClient, ClientError> {
+        let mut http_client = Client::builder();
+        if let Some(connect_timeout) = self.connect_timeout {
+            http_client = http_client.connect_timeout(connect_timeout);
+        }
+        if let Some(read_timeout) = self.read_timeout {
+            http_client = http_client.read_timeout(read_timeout);
+        }
+        if let Some(pool_idle_timeout) = self.pool_idle_timeout {
+            http_client = http_client.pool_idle_timeout(pool_idle_timeout);
+        }
+        if let Some(user_agent) = self.user_agent {
+            http_client = http_client.user_agent(user_agent);
+        }
+        let mut client = JupiterSwapApiClient::with_client(self.base_path, http_client.build()?)
+            .with_default_headers(self.default_headers);
+        if let Some(api_key_header) = self.api_key_header {
+            client = client.with_api_key_header(api_key_header);
+        }
+        if let Some(api_key) = self.api_key {
+            client = client.with_api_key(api_key);
+        }
+        Ok(client)
+    }
+}
+
+impl JupiterSwapApiClient {
+    pub fn builder(base_path: String) -> JupiterSwapApiClientBuilder {
+        JupiterSwapApiClientBuilder::new(base_path)
+    }
+}
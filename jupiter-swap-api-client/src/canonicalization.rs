@@ -0,0 +1,65 @@
+//! Deterministic serialization of quote requests, usable as cache key, for deduplication or to
+//! sign requests in proxy deployments.
+
+use std::collections::BTreeMap;
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    quote::{InternalQuoteRequest, QuoteRequest},
+    solana_types::Hash,
+};
+
+impl QuoteRequest {
+    /// Query parameters sent for the request, `quote_args` included, sorted by name then value
+    ///
+    /// DEX lists are sorted and deduplicated, empty lists and unset parameters are omitted, so
+    /// requests routing the same way give the same parameters.
+    pub fn canonical_params(&self) -> Vec<(String, String)> {
+        let mut quote_request = self.clone();
+        for dexes in [&mut quote_request.dexes, &mut quote_request.excluded_dexes]
+            .into_iter()
+            .flatten()
+        {
+            dexes.sort_by_cached_key(ToString::to_string);
+            dexes.dedup();
+        }
+        let quote_args = quote_request
+            .quote_args
+            .take()
+            .map(BTreeMap::from_iter)
+            .unwrap_or_default();
+
+        let mut params = query_params(&InternalQuoteRequest::from(quote_request));
+        params.extend(query_params(&quote_args));
+        params.sort();
+        params
+    }
+
+    /// URL-encoded query string of [`Self::canonical_params`]
+    pub fn canonical_query(&self) -> String {
+        self.canonical_params()
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+
+    /// SHA-256 of [`Self::canonical_query`], equal for requests routing the same way
+    pub fn canonical_hash(&self) -> Hash {
+        Hash::new_from_array(Sha256::digest(self.canonical_query()).into())
+    }
+}
+
+/// URL-encoded `(name, value)` pairs of `value` serialized as a query string
+fn query_params(value: &impl serde::Serialize) -> Vec<(String, String)> {
+    serde_qs::to_string(value)
+        .expect("quote parameters serialize to a query string")
+        .split('&')
+        .filter(|param| !param.is_empty())
+        .map(|param| match param.split_once('=') {
+            Some((name, value)) => (name.to_string(), value.to_string()),
+            None => (param.to_string(), String::new()),
+        })
+        .collect()
+}
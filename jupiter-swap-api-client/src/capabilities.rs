@@ -0,0 +1,136 @@
+//! Report of what this build of the crate supports, to log at startup and attach to bug reports,
+//! see [`capabilities`].
+
+use serde::Serialize;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Endpoint {
+    /// `swap`, `ultra`, `tokens` or `recurring`
+    pub api: &'static str,
+    pub method: &'static str,
+    pub path: &'static str,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Capabilities {
+    pub crate_version: &'static str,
+    /// Version of the Swap API schema the request and response types follow
+    pub swap_api_version: &'static str,
+    pub ultra_api_version: &'static str,
+    pub tokens_api_version: &'static str,
+    pub recurring_api_version: &'static str,
+    pub endpoints: Vec<Endpoint>,
+    /// Cargo features enabled in this build
+    pub features: Vec<&'static str>,
+}
+
+const ENDPOINTS: [Endpoint; 17] = [
+    Endpoint {
+        api: "swap",
+        method: "GET",
+        path: "/quote",
+    },
+    Endpoint {
+        api: "swap",
+        method: "POST",
+        path: "/swap",
+    },
+    Endpoint {
+        api: "swap",
+        method: "POST",
+        path: "/swap-instructions",
+    },
+    Endpoint {
+        api: "swap",
+        method: "GET",
+        path: "/program-id-to-label",
+    },
+    Endpoint {
+        api: "ultra",
+        method: "GET",
+        path: "/order",
+    },
+    Endpoint {
+        api: "ultra",
+        method: "POST",
+        path: "/execute",
+    },
+    Endpoint {
+        api: "ultra",
+        method: "GET",
+        path: "/balances/{wallet}",
+    },
+    Endpoint {
+        api: "ultra",
+        method: "GET",
+        path: "/shield",
+    },
+    Endpoint {
+        api: "tokens",
+        method: "GET",
+        path: "/token/{mint}",
+    },
+    Endpoint {
+        api: "tokens",
+        method: "GET",
+        path: "/tagged/{tags}",
+    },
+    Endpoint {
+        api: "tokens",
+        method: "GET",
+        path: "/mints/tradable",
+    },
+    Endpoint {
+        api: "recurring",
+        method: "POST",
+        path: "/createOrder",
+    },
+    Endpoint {
+        api: "recurring",
+        method: "POST",
+        path: "/cancelOrder",
+    },
+    Endpoint {
+        api: "recurring",
+        method: "POST",
+        path: "/priceDeposit",
+    },
+    Endpoint {
+        api: "recurring",
+        method: "POST",
+        path: "/priceWithdraw",
+    },
+    Endpoint {
+        api: "recurring",
+        method: "POST",
+        path: "/execute",
+    },
+    Endpoint {
+        api: "recurring",
+        method: "GET",
+        path: "/getRecurringOrders",
+    },
+];
+
+pub fn capabilities() -> Capabilities {
+    let features = [
+        ("rpc", cfg!(feature = "rpc")),
+        ("keyring", cfg!(feature = "keyring")),
+        ("chaos", cfg!(feature = "chaos")),
+        ("test-util", cfg!(feature = "test-util")),
+    ];
+    Capabilities {
+        crate_version: env!("CARGO_PKG_VERSION"),
+        swap_api_version: "v6",
+        ultra_api_version: "v1",
+        tokens_api_version: "v1",
+        recurring_api_version: "v1",
+        endpoints: ENDPOINTS.to_vec(),
+        features: features
+            .into_iter()
+            .filter_map(|(feature, enabled)| enabled.then_some(feature))
+            .collect(),
+    }
+}
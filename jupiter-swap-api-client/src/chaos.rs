@@ -0,0 +1,162 @@
+//! Failure injection around the HTTP transport, to exercise the resilience paths of execution
+//! engines built on the client against realistic failure modes.
+
+use std::{
+    ops::Range,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+
+use crate::{check_is_success, read_body_with_limit, ClientError};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InjectedFault {
+    /// Answered with a 429 without reaching the server
+    RateLimited,
+    /// Answered with a 503 without reaching the server
+    ServerError,
+    /// The response body is cut in half
+    TruncatedBody,
+    /// The response body is replaced with invalid JSON
+    MalformedJson,
+}
+
+impl InjectedFault {
+    const ALL: [Self; 4] = [
+        Self::RateLimited,
+        Self::ServerError,
+        Self::TruncatedBody,
+        Self::MalformedJson,
+    ];
+}
+
+/// Probability of every fault, in `[0, 1]`, faults are mutually exclusive per request
+#[derive(Clone, Debug, Default)]
+pub struct ChaosConfig {
+    /// Extra latency drawn uniformly from the range before every request
+    pub latency: Option<Range<Duration>>,
+    pub rate_limited_probability: f64,
+    pub server_error_probability: f64,
+    pub truncated_body_probability: f64,
+    pub malformed_json_probability: f64,
+}
+
+impl ChaosConfig {
+    fn probability(&self, fault: InjectedFault) -> f64 {
+        match fault {
+            InjectedFault::RateLimited => self.rate_limited_probability,
+            InjectedFault::ServerError => self.server_error_probability,
+            InjectedFault::TruncatedBody => self.truncated_body_probability,
+            InjectedFault::MalformedJson => self.malformed_json_probability,
+        }
+    }
+}
+
+/// Wraps the transport of a client, see
+/// [`JupiterSwapApiClient::with_chaos`](crate::JupiterSwapApiClient::with_chaos)
+#[derive(Debug)]
+pub struct ChaosTransport {
+    config: ChaosConfig,
+    /// xorshift64 state, deterministic for a given seed
+    rng_state: AtomicU64,
+    injected: [AtomicU64; 4],
+}
+
+impl ChaosTransport {
+    pub fn new(config: ChaosConfig) -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+        Self::with_seed(config, seed)
+    }
+
+    /// Same seed, same sequence of faults
+    pub fn with_seed(config: ChaosConfig, seed: u64) -> Self {
+        Self {
+            config,
+            // xorshift gets stuck on 0
+            rng_state: AtomicU64::new(seed.max(1)),
+            injected: Default::default(),
+        }
+    }
+
+    pub fn config(&self) -> &ChaosConfig {
+        &self.config
+    }
+
+    /// Number of times `fault` was injected
+    pub fn injected(&self, fault: InjectedFault) -> u64 {
+        self.injected[fault as usize].load(Ordering::Relaxed)
+    }
+
+    /// Uniform draw in `[0, 1)`
+    fn next_f64(&self) -> f64 {
+        let mut next = 0;
+        let _ = self
+            .rng_state
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |mut state| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                next = state;
+                Some(state)
+            });
+        (next >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn draw_fault(&self) -> Option<InjectedFault> {
+        let draw = self.next_f64();
+        let mut cumulative_probability = 0.;
+        let fault = InjectedFault::ALL.into_iter().find(|fault| {
+            cumulative_probability += self.config.probability(*fault);
+            draw < cumulative_probability
+        })?;
+        self.injected[fault as usize].fetch_add(1, Ordering::Relaxed);
+        Some(fault)
+    }
+
+    /// Sends `request` with the faults drawn for it
+    pub(crate) async fn execute<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+        limit: usize,
+        observe_response: impl FnOnce(&Response),
+    ) -> Result<(T, StatusCode), ClientError> {
+        if let Some(latency) = &self.config.latency {
+            let extra = latency
+                .end
+                .saturating_sub(latency.start)
+                .mul_f64(self.next_f64());
+            tokio::time::sleep(latency.start + extra).await;
+        }
+
+        let fault = self.draw_fault();
+        match fault {
+            Some(InjectedFault::RateLimited) => {
+                return Err(ClientError::RateLimited { retry_after: None })
+            }
+            Some(InjectedFault::ServerError) => {
+                return Err(ClientError::RequestFailed {
+                    status: StatusCode::SERVICE_UNAVAILABLE,
+                    body: "Injected failure".to_string(),
+                })
+            }
+            _ => (),
+        }
+
+        let response = request.send().await?;
+        observe_response(&response);
+        let response = check_is_success(response, limit).await?;
+        let status = response.status();
+        let mut body = read_body_with_limit(response, limit).await?;
+        match fault {
+            Some(InjectedFault::TruncatedBody) => body.truncate(body.len() / 2),
+            Some(InjectedFault::MalformedJson) => body = b"{\"injected\": malformed".to_vec(),
+            _ => (),
+        }
+        Ok((crate::error::deserialize_body(&body)?, status))
+    }
+}
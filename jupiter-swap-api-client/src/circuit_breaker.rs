@@ -0,0 +1,89 @@
+//! Volatility halt: blocks swaps of a pair whose quoted price moved too much within a short window.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use rust_decimal::Decimal;
+
+use crate::{
+    quote::QuoteResponse, route_plan_with_metadata::price, solana_types::Pubkey, ClientError,
+};
+
+/// Rates of the recent quotes of a pair, raw output amount per raw input amount
+type PairRates = VecDeque<(Instant, Decimal)>;
+
+/// Tracks the rate of successive quotes per pair, a pair is halted while its rate moved by more
+/// than `max_move_bps` within `window`
+#[derive(Debug)]
+pub struct PriceCircuitBreaker {
+    pub max_move_bps: u16,
+    pub window: Duration,
+    rates: Mutex<HashMap<(Pubkey, Pubkey), PairRates>>,
+}
+
+impl PriceCircuitBreaker {
+    pub fn new(max_move_bps: u16, window: Duration) -> Self {
+        Self {
+            max_move_bps,
+            window,
+            rates: Mutex::default(),
+        }
+    }
+
+    /// Records the rate of `quote_response`
+    pub fn observe(&self, quote_response: &QuoteResponse) {
+        let Some(rate) = price(quote_response.in_amount, 0, quote_response.out_amount, 0) else {
+            return;
+        };
+        let now = Instant::now();
+        let mut rates = self.rates.lock().unwrap_or_else(PoisonError::into_inner);
+        let pair_rates = rates
+            .entry((quote_response.input_mint, quote_response.output_mint))
+            .or_default();
+        pair_rates.push_back((now, rate));
+        while pair_rates
+            .front()
+            .is_some_and(|(observed_at, _)| now.duration_since(*observed_at) > self.window)
+        {
+            pair_rates.pop_front();
+        }
+    }
+
+    /// Largest rate move in bps observed within the window for the pair
+    pub fn move_bps(&self, input_mint: &Pubkey, output_mint: &Pubkey) -> Decimal {
+        let now = Instant::now();
+        let rates = self.rates.lock().unwrap_or_else(PoisonError::into_inner);
+        let recent_rates = rates
+            .get(&(*input_mint, *output_mint))
+            .into_iter()
+            .flatten()
+            .filter(|(observed_at, _)| now.duration_since(*observed_at) <= self.window)
+            .map(|(_, rate)| *rate);
+        let (min, max) = recent_rates.fold((None, None), |(min, max), rate| {
+            (
+                Some(min.map_or(rate, |min: Decimal| min.min(rate))),
+                Some(max.map_or(rate, |max: Decimal| max.max(rate))),
+            )
+        });
+        match (min, max) {
+            (Some(min), Some(max)) if !min.is_zero() => (max - min) / min * Decimal::from(10_000),
+            _ => Decimal::ZERO,
+        }
+    }
+
+    /// Fails with [`ClientError::VolatilityHalt`] when the pair of `quote_response` is halted
+    pub fn check(&self, quote_response: &QuoteResponse) -> Result<(), ClientError> {
+        let move_bps = self.move_bps(&quote_response.input_mint, &quote_response.output_mint);
+        if move_bps > Decimal::from(self.max_move_bps) {
+            return Err(ClientError::VolatilityHalt {
+                input_mint: quote_response.input_mint,
+                output_mint: quote_response.output_mint,
+                move_bps,
+            });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+//! Compute unit usage observed per route shape, used to predict the compute units needed by
+//! similar routes without simulating every swap.
+
+use std::{
+    collections::{BTreeSet, HashMap, VecDeque},
+    sync::{Mutex, PoisonError},
+};
+
+use crate::{quote::QuoteResponse, route_plan_with_metadata::RoutePlanStep, swap::SwapResponse};
+
+/// Routes sharing the same DEXes and number of hops tend to consume a similar amount of compute units
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RouteShape {
+    pub dexes: BTreeSet<String>,
+    pub hop_count: usize,
+}
+
+impl RouteShape {
+    pub fn from_route_plan(route_plan: &[RoutePlanStep]) -> Self {
+        Self {
+            dexes: route_plan
+                .iter()
+                .map(|step| step.swap_info.label.clone())
+                .collect(),
+            hop_count: route_plan.len(),
+        }
+    }
+}
+
+/// Summary of the compute units observed for a route shape
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ComputeUnitStats {
+    pub samples: usize,
+    pub min: u32,
+    pub max: u32,
+    pub mean: u32,
+}
+
+/// Records compute unit usage per [`RouteShape`], keeping the most recent `max_samples_per_shape`
+/// samples of every shape
+#[derive(Debug)]
+pub struct ComputeUnitEstimator {
+    max_samples_per_shape: usize,
+    samples: Mutex<HashMap<RouteShape, VecDeque<u32>>>,
+}
+
+impl Default for ComputeUnitEstimator {
+    fn default() -> Self {
+        Self::new(256)
+    }
+}
+
+impl ComputeUnitEstimator {
+    pub fn new(max_samples_per_shape: usize) -> Self {
+        Self {
+            max_samples_per_shape: max_samples_per_shape.max(1),
+            samples: Mutex::default(),
+        }
+    }
+
+    /// Records compute units consumed by (or simulated for) a swap following `route_plan`
+    pub fn record(&self, route_plan: &[RoutePlanStep], compute_units: u32) {
+        let mut samples = self.samples.lock().unwrap_or_else(PoisonError::into_inner);
+        let shape_samples = samples
+            .entry(RouteShape::from_route_plan(route_plan))
+            .or_default();
+        if shape_samples.len() == self.max_samples_per_shape {
+            shape_samples.pop_front();
+        }
+        shape_samples.push_back(compute_units);
+    }
+
+    /// Records the compute unit limit set by the API, which is the simulated usage when
+    /// `dynamic_compute_unit_limit` is enabled
+    pub fn record_swap_response(
+        &self,
+        quote_response: &QuoteResponse,
+        swap_response: &SwapResponse,
+    ) {
+        self.record(&quote_response.route_plan, swap_response.compute_unit_limit);
+    }
+
+    pub fn stats(&self, route_plan: &[RoutePlanStep]) -> Option<ComputeUnitStats> {
+        let samples = self.samples.lock().unwrap_or_else(PoisonError::into_inner);
+        let shape_samples = samples.get(&RouteShape::from_route_plan(route_plan))?;
+        let sum = shape_samples
+            .iter()
+            .map(|&units| u64::from(units))
+            .sum::<u64>();
+        Some(ComputeUnitStats {
+            samples: shape_samples.len(),
+            min: *shape_samples.iter().min()?,
+            max: *shape_samples.iter().max()?,
+            mean: (sum / shape_samples.len() as u64) as u32,
+        })
+    }
+
+    /// Compute units covering `percentile` (0-100) of the swaps previously observed for routes of
+    /// the same shape, `None` if no such route was observed
+    pub fn estimate(&self, route_plan: &[RoutePlanStep], percentile: u8) -> Option<u32> {
+        let samples = self.samples.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut shape_samples = samples
+            .get(&RouteShape::from_route_plan(route_plan))?
+            .iter()
+            .copied()
+            .collect::<Vec<_>>();
+        drop(samples);
+
+        shape_samples.sort_unstable();
+        let percentile = usize::from(percentile.min(100));
+        let index = (shape_samples.len() * percentile).div_ceil(100).max(1) - 1;
+        shape_samples.get(index).copied()
+    }
+}
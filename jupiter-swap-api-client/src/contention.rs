@@ -0,0 +1,153 @@
+//! Estimates write-lock contention of candidate routes, for latency-sensitive users who prefer the
+//! route most likely to land over the best priced one.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use crate::{quote::QuoteResponse, route_plan_with_metadata::RoutePlanStep, solana_types::Pubkey};
+
+/// Contention estimate of a route, lower is more likely to land
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContentionScore {
+    /// Every hop write-locks its pool, each recent use of a pool adds to its weight
+    pub score: f64,
+    /// Most used pool of the route within the window and its number of uses
+    pub hottest_amm_key: Option<(Pubkey, usize)>,
+}
+
+/// Tracks recent usage of pools, fed with the routes of swaps seen landing or failing on lock
+/// contention
+#[derive(Debug)]
+pub struct ContentionEstimator {
+    pub window: Duration,
+    /// Weight of a hop through a pool with no recent use
+    pub hop_weight: f64,
+    /// Weight added by every recent use of a pool
+    pub usage_weight: f64,
+    usage: Mutex<HashMap<Pubkey, VecDeque<Instant>>>,
+}
+
+impl Default for ContentionEstimator {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(60))
+    }
+}
+
+impl ContentionEstimator {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            hop_weight: 1.,
+            usage_weight: 0.25,
+            usage: Mutex::default(),
+        }
+    }
+
+    /// Records a use of the pool `amm_key`, pools with no use left within the window are
+    /// forgotten
+    pub fn record_usage(&self, amm_key: Pubkey) {
+        let now = Instant::now();
+        let mut usage = self.usage.lock().unwrap_or_else(PoisonError::into_inner);
+        usage.entry(amm_key).or_default().push_back(now);
+        usage.retain(|_, uses| {
+            while uses
+                .front()
+                .is_some_and(|used_at| now.duration_since(*used_at) > self.window)
+            {
+                uses.pop_front();
+            }
+            !uses.is_empty()
+        });
+    }
+
+    /// Records a use of every pool of `route_plan`
+    pub fn record_route(&self, route_plan: &[RoutePlanStep]) {
+        for step in route_plan {
+            self.record_usage(step.swap_info.amm_key);
+        }
+    }
+
+    /// Uses of the pool `amm_key` within the window
+    pub fn usage(&self, amm_key: &Pubkey) -> usize {
+        let now = Instant::now();
+        self.usage
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(amm_key)
+            .map_or(0, |uses| {
+                uses.iter()
+                    .filter(|used_at| now.duration_since(**used_at) <= self.window)
+                    .count()
+            })
+    }
+
+    pub fn score(&self, quote_response: &QuoteResponse) -> ContentionScore {
+        let mut score = 0.;
+        let mut hottest_amm_key: Option<(Pubkey, usize)> = None;
+        for step in &quote_response.route_plan {
+            let amm_key = step.swap_info.amm_key;
+            let usage = self.usage(&amm_key);
+            score += self.hop_weight + self.usage_weight * usage as f64;
+            if hottest_amm_key.is_none_or(|(_, hottest_usage)| usage > hottest_usage) {
+                hottest_amm_key = Some((amm_key, usage));
+            }
+        }
+        ContentionScore {
+            score,
+            hottest_amm_key,
+        }
+    }
+
+    /// Indices of `quote_responses` ordered by increasing contention, ties keep the input order
+    pub fn rank(&self, quote_responses: &[QuoteResponse]) -> Vec<(usize, ContentionScore)> {
+        let mut ranked = quote_responses
+            .iter()
+            .map(|quote_response| self.score(quote_response))
+            .enumerate()
+            .collect::<Vec<_>>();
+        ranked.sort_by(|(_, a), (_, b)| a.score.total_cmp(&b.score));
+        ranked
+    }
+
+    /// Least contended quote among those whose output is at most `max_out_amount_loss_bps` below
+    /// the best output
+    pub fn least_contended<'a>(
+        &self,
+        quote_responses: &'a [QuoteResponse],
+        max_out_amount_loss_bps: u16,
+    ) -> Option<&'a QuoteResponse> {
+        let best_out_amount = quote_responses
+            .iter()
+            .map(|quote_response| quote_response.out_amount)
+            .max()?;
+        let min_out_amount = u128::from(best_out_amount)
+            * u128::from(10_000 - max_out_amount_loss_bps.min(10_000))
+            / 10_000;
+        self.rank(quote_responses)
+            .into_iter()
+            .map(|(index, _)| &quote_responses[index])
+            .find(|quote_response| u128::from(quote_response.out_amount) >= min_out_amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pools_without_recent_use_are_forgotten() {
+        let estimator = ContentionEstimator::new(Duration::from_millis(10));
+        let stale_amm_key = Pubkey::new_from_array([1; 32]);
+        let amm_key = Pubkey::new_from_array([2; 32]);
+        estimator.record_usage(stale_amm_key);
+        std::thread::sleep(Duration::from_millis(20));
+        estimator.record_usage(amm_key);
+
+        let usage = estimator.usage.lock().unwrap();
+        assert!(!usage.contains_key(&stale_amm_key));
+        assert_eq!(usage[&amm_key].len(), 1);
+    }
+}
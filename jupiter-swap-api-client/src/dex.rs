@@ -0,0 +1,218 @@
+//! DEX labels accepted by the `dexes` and `excluded_dexes` quote parameters, see [`Dex`].
+
+use std::{collections::HashSet, convert::Infallible, fmt, str::FromStr};
+
+use crate::{quote::QuoteRequest, ClientError, JupiterSwapApiClient};
+
+/// A DEX as labelled by the router, serialized as the exact label the API expects
+///
+/// Labels can be listed with
+/// [`JupiterSwapApiClient::program_id_to_label`](crate::JupiterSwapApiClient::program_id_to_label).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Dex {
+    OneDex,
+    Aldrin,
+    Crema,
+    FluxBeam,
+    GooseFxGamma,
+    Invariant,
+    LifinityV2,
+    Meteora,
+    MeteoraDlmm,
+    Moonshot,
+    ObricV2,
+    OpenBookV2,
+    OrcaV2,
+    Phoenix,
+    PumpFun,
+    Raydium,
+    RaydiumClmm,
+    RaydiumCp,
+    Saber,
+    Sanctum,
+    SanctumInfinity,
+    SolFi,
+    StabbleStableSwap,
+    StabbleWeightedSwap,
+    Whirlpool,
+    /// A label unknown to this version of the client, sent as is
+    Other(String),
+}
+
+impl Dex {
+    const KNOWN: [Self; 25] = [
+        Self::OneDex,
+        Self::Aldrin,
+        Self::Crema,
+        Self::FluxBeam,
+        Self::GooseFxGamma,
+        Self::Invariant,
+        Self::LifinityV2,
+        Self::Meteora,
+        Self::MeteoraDlmm,
+        Self::Moonshot,
+        Self::ObricV2,
+        Self::OpenBookV2,
+        Self::OrcaV2,
+        Self::Phoenix,
+        Self::PumpFun,
+        Self::Raydium,
+        Self::RaydiumClmm,
+        Self::RaydiumCp,
+        Self::Saber,
+        Self::Sanctum,
+        Self::SanctumInfinity,
+        Self::SolFi,
+        Self::StabbleStableSwap,
+        Self::StabbleWeightedSwap,
+        Self::Whirlpool,
+    ];
+
+    pub fn label(&self) -> &str {
+        match self {
+            Self::OneDex => "1DEX",
+            Self::Aldrin => "Aldrin",
+            Self::Crema => "Crema",
+            Self::FluxBeam => "FluxBeam",
+            Self::GooseFxGamma => "GooseFX GAMMA",
+            Self::Invariant => "Invariant",
+            Self::LifinityV2 => "Lifinity V2",
+            Self::Meteora => "Meteora",
+            Self::MeteoraDlmm => "Meteora DLMM",
+            Self::Moonshot => "Moonshot",
+            Self::ObricV2 => "Obric V2",
+            Self::OpenBookV2 => "OpenBook V2",
+            Self::OrcaV2 => "Orca V2",
+            Self::Phoenix => "Phoenix",
+            Self::PumpFun => "Pump.fun",
+            Self::Raydium => "Raydium",
+            Self::RaydiumClmm => "Raydium CLMM",
+            Self::RaydiumCp => "Raydium CP",
+            Self::Saber => "Saber",
+            Self::Sanctum => "Sanctum",
+            Self::SanctumInfinity => "Sanctum Infinity",
+            Self::SolFi => "SolFi",
+            Self::StabbleStableSwap => "Stabble Stable Swap",
+            Self::StabbleWeightedSwap => "Stabble Weighted Swap",
+            Self::Whirlpool => "Whirlpool",
+            Self::Other(label) => label,
+        }
+    }
+}
+
+impl fmt::Display for Dex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+impl FromStr for Dex {
+    type Err = Infallible;
+
+    /// Known labels match regardless of case and whitespace, e.g. `" obric  v2"` reads as
+    /// [`Dex::ObricV2`], unknown labels are trimmed
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let label = s.split_whitespace().collect::<Vec<_>>().join(" ");
+        Ok(Self::KNOWN
+            .into_iter()
+            .find(|dex| dex.label().eq_ignore_ascii_case(&label))
+            .unwrap_or(Self::Other(label)))
+    }
+}
+
+impl From<&str> for Dex {
+    fn from(label: &str) -> Self {
+        let Ok(dex) = label.parse();
+        dex
+    }
+}
+
+impl From<String> for Dex {
+    fn from(label: String) -> Self {
+        Self::from(label.as_str())
+    }
+}
+
+/// A label of a quote request the router does not know
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownDexLabel {
+    pub label: String,
+    /// Closest live label, likely what was meant
+    pub suggestion: Option<String>,
+}
+
+impl fmt::Display for UnknownDexLabel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.suggestion {
+            Some(suggestion) => write!(f, "\"{}\" (did you mean \"{suggestion}\"?)", self.label),
+            None => write!(f, "\"{}\"", self.label),
+        }
+    }
+}
+
+/// Number of single character edits between `a` and `b`, ignoring case
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.to_ascii_lowercase().chars().collect::<Vec<_>>();
+    let b = b.to_ascii_lowercase().chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a_char) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Closest of `live_labels` to `label`, when close enough to be a typo
+fn suggest<'a>(label: &str, live_labels: impl Iterator<Item = &'a str>) -> Option<String> {
+    let max_distance = (label.len() / 3).max(1);
+    live_labels
+        .map(|live_label| (edit_distance(label, live_label), live_label))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min()
+        .map(|(_, live_label)| live_label.to_string())
+}
+
+impl JupiterSwapApiClient {
+    /// Checks the `dexes` and `excluded_dexes` of `quote_request` against the labels served by
+    /// `/program-id-to-label`, the router silently ignores unknown labels
+    pub async fn validate_dex_labels(
+        &self,
+        quote_request: &QuoteRequest,
+    ) -> Result<(), ClientError> {
+        self.in_flight(async {
+            let live_labels = self
+                .program_id_to_label()
+                .await?
+                .into_values()
+                .collect::<HashSet<_>>();
+            let mut labels = Vec::new();
+            for dex in [&quote_request.dexes, &quote_request.excluded_dexes]
+                .into_iter()
+                .flatten()
+                .flatten()
+            {
+                let label = dex.label();
+                if !live_labels.contains(label)
+                    && labels
+                        .iter()
+                        .all(|unknown: &UnknownDexLabel| unknown.label != label)
+                {
+                    labels.push(UnknownDexLabel {
+                        label: label.to_string(),
+                        suggestion: suggest(label, live_labels.iter().map(String::as_str)),
+                    });
+                }
+            }
+            if labels.is_empty() {
+                Ok(())
+            } else {
+                Err(ClientError::UnknownDexLabels { labels })
+            }
+        })
+        .await
+    }
+}
@@ -0,0 +1,161 @@
+//! Temporarily excludes DEXes whose routes keep failing, re-including them after a cool-down.
+
+use std::{
+    collections::{BTreeSet, HashMap, VecDeque},
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    dex::Dex,
+    quote::{QuoteRequest, QuoteResponse},
+    route_plan_with_metadata::RoutePlanStep,
+    swap::SwapResponse,
+};
+
+#[derive(Clone, Debug)]
+pub struct DexExclusionPolicy {
+    /// Failures within `failure_window` after which a DEX is excluded
+    pub failure_threshold: usize,
+    pub failure_window: Duration,
+    /// How long a DEX stays excluded
+    pub cooldown: Duration,
+}
+
+impl Default for DexExclusionPolicy {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 3,
+            failure_window: Duration::from_secs(5 * 60),
+            cooldown: Duration::from_secs(10 * 60),
+        }
+    }
+}
+
+/// Distinct DEX labels of `route_plan`
+fn route_labels(route_plan: &[RoutePlanStep]) -> BTreeSet<&str> {
+    route_plan
+        .iter()
+        .map(|step| step.swap_info.label.as_str())
+        .collect()
+}
+
+#[derive(Debug, Default)]
+struct DexState {
+    failures: VecDeque<Instant>,
+    excluded_until: Option<Instant>,
+}
+
+/// Tracks simulation and execution failures per DEX label and appends the labels failing
+/// repeatedly to the `excluded_dexes` of quote requests
+///
+/// A failed route counts as a failure of every DEX it goes through.
+#[derive(Debug, Default)]
+pub struct AdaptiveDexExclusion {
+    policy: DexExclusionPolicy,
+    states: Mutex<HashMap<String, DexState>>,
+}
+
+impl AdaptiveDexExclusion {
+    pub fn new(policy: DexExclusionPolicy) -> Self {
+        Self {
+            policy,
+            states: Mutex::default(),
+        }
+    }
+
+    pub fn policy(&self) -> &DexExclusionPolicy {
+        &self.policy
+    }
+
+    pub fn record_label_failure(&self, label: &str) {
+        let now = Instant::now();
+        let mut states = self.states.lock().unwrap_or_else(PoisonError::into_inner);
+        let state = states.entry(label.to_string()).or_default();
+        state.failures.push_back(now);
+        while state
+            .failures
+            .front()
+            .is_some_and(|failure| now.duration_since(*failure) > self.policy.failure_window)
+        {
+            state.failures.pop_front();
+        }
+        if state.failures.len() >= self.policy.failure_threshold {
+            state.failures.clear();
+            state.excluded_until = Some(now + self.policy.cooldown);
+        }
+    }
+
+    /// Records a failed swap following `route_plan`, once per DEX however many hops go through it
+    pub fn record_failure(&self, route_plan: &[RoutePlanStep]) {
+        for label in route_labels(route_plan) {
+            self.record_label_failure(label);
+        }
+    }
+
+    /// Records a successful swap following `route_plan`, forgetting past failures of its DEXes
+    pub fn record_success(&self, route_plan: &[RoutePlanStep]) {
+        let mut states = self.states.lock().unwrap_or_else(PoisonError::into_inner);
+        for label in route_labels(route_plan) {
+            if let Some(state) = states.get_mut(label) {
+                state.failures.clear();
+            }
+        }
+    }
+
+    /// Records a failure when the API reported a simulation error for the swap built from
+    /// `quote_response`, a success otherwise
+    pub fn record_swap_response(
+        &self,
+        quote_response: &QuoteResponse,
+        swap_response: &SwapResponse,
+    ) {
+        if swap_response.simulation_error.is_some() {
+            self.record_failure(&quote_response.route_plan);
+        } else {
+            self.record_success(&quote_response.route_plan);
+        }
+    }
+
+    /// Labels currently excluded
+    pub fn excluded_labels(&self) -> Vec<String> {
+        let now = Instant::now();
+        let mut states = self.states.lock().unwrap_or_else(PoisonError::into_inner);
+        states.retain(|_, state| {
+            if state.excluded_until.is_some_and(|until| until <= now) {
+                state.excluded_until = None;
+            }
+            state.excluded_until.is_some() || !state.failures.is_empty()
+        });
+        let mut labels = states
+            .iter()
+            .filter(|(_, state)| state.excluded_until.is_some())
+            .map(|(label, _)| label.clone())
+            .collect::<Vec<_>>();
+        labels.sort();
+        labels
+    }
+
+    /// Re-includes `label` before its cool-down ends
+    pub fn reinclude(&self, label: &str) {
+        self.states
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(label);
+    }
+
+    /// Appends the excluded labels to the request `excluded_dexes`
+    pub fn apply(&self, quote_request: &mut QuoteRequest) {
+        let excluded_labels = self.excluded_labels();
+        if excluded_labels.is_empty() {
+            return;
+        }
+        let excluded_dexes = quote_request.excluded_dexes.get_or_insert_with(Vec::new);
+        for label in excluded_labels {
+            let dex = Dex::from(label);
+            if !excluded_dexes.contains(&dex) {
+                excluded_dexes.push(dex);
+            }
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Latency probing of regional endpoints, routing requests to the fastest healthy one.
+
+use std::{
+    sync::{Arc, PoisonError, RwLock},
+    time::{Duration, Instant},
+};
+
+use futures::future::join_all;
+use reqwest::Client;
+use tokio::task::JoinHandle;
+
+use crate::JupiterSwapApiClient;
+
+/// Result of the last probe of an endpoint
+#[derive(Clone, Debug, PartialEq)]
+pub struct EndpointStatus {
+    pub base_path: String,
+    /// Round trip time of the probe, `None` if the endpoint is unhealthy
+    pub rtt: Option<Duration>,
+}
+
+impl EndpointStatus {
+    pub fn is_healthy(&self) -> bool {
+        self.rtt.is_some()
+    }
+}
+
+/// Measures the round trip time to a set of regional base paths
+///
+/// An endpoint is healthy when the probe gets a response without a server error within
+/// `timeout`.
+#[derive(Debug)]
+pub struct EndpointProber {
+    pub base_paths: Vec<String>,
+    /// Path requested on every base path
+    pub probe_path: String,
+    pub timeout: Duration,
+    statuses: RwLock<Vec<EndpointStatus>>,
+}
+
+impl EndpointProber {
+    pub fn new(base_paths: Vec<String>) -> Self {
+        Self {
+            base_paths,
+            probe_path: "/program-id-to-label".to_string(),
+            timeout: Duration::from_secs(2),
+            statuses: RwLock::default(),
+        }
+    }
+
+    pub fn with_probe_path(mut self, probe_path: String) -> Self {
+        self.probe_path = probe_path;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Probes every endpoint concurrently
+    pub async fn probe(&self, http_client: &Client) -> Vec<EndpointStatus> {
+        let statuses = join_all(self.base_paths.iter().map(|base_path| async move {
+            let started_at = Instant::now();
+            let response = http_client
+                .get(format!("{base_path}{}", self.probe_path))
+                .timeout(self.timeout)
+                .send()
+                .await;
+            let healthy = response.is_ok_and(|response| !response.status().is_server_error());
+            EndpointStatus {
+                base_path: base_path.clone(),
+                rtt: healthy.then(|| started_at.elapsed()),
+            }
+        }))
+        .await;
+        *self
+            .statuses
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = statuses.clone();
+        statuses
+    }
+
+    /// Results of the last probe
+    pub fn statuses(&self) -> Vec<EndpointStatus> {
+        self.statuses
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Fastest healthy endpoint of the last probe
+    pub fn fastest(&self) -> Option<String> {
+        self.statuses
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .filter_map(|status| Some((status.rtt?, &status.base_path)))
+            .min_by_key(|(rtt, _)| *rtt)
+            .map(|(_, base_path)| base_path.clone())
+    }
+}
+
+impl JupiterSwapApiClient {
+    /// Probes the endpoints of `prober` and switches every clone to the fastest healthy one,
+    /// returning it, the base path is kept when no endpoint is healthy
+    pub async fn select_fastest_endpoint(&self, prober: &EndpointProber) -> Option<String> {
+        prober.probe(&self.inner.http_client).await;
+        let fastest = prober.fastest()?;
+        if fastest != self.base_path() {
+            self.set_base_path(fastest.clone());
+        }
+        Some(fastest)
+    }
+
+    /// Re-evaluates the fastest endpoint every `interval` until the returned task is aborted or
+    /// the client shuts down
+    pub fn spawn_endpoint_selection(
+        &self,
+        prober: Arc<EndpointProber>,
+        interval: Duration,
+    ) -> JoinHandle<()> {
+        let client = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            while !client.is_shutting_down() {
+                interval.tick().await;
+                client.select_fastest_endpoint(&prober).await;
+            }
+        })
+    }
+}
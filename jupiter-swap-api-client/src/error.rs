@@ -0,0 +1,286 @@
+use std::{error::Error as StdError, sync::Arc, time::Duration};
+
+use thiserror::Error;
+
+/// Errors returned by [`crate::JupiterSwapApiClient`]
+///
+/// Underlying errors are reference counted so that a single failure can be cloned and handed to
+/// every task awaiting the same call, while still being reachable through [`StdError::source`].
+#[derive(Clone, Debug, Error)]
+#[non_exhaustive]
+pub enum ClientError {
+    #[error("Request failed with status {status}: {body}")]
+    RequestFailed {
+        status: reqwest::StatusCode,
+        body: String,
+    },
+    /// 429 response, `retry_after` is read from the `Retry-After` or rate limit reset headers
+    #[error("Rate limited, retry after {retry_after:?}")]
+    RateLimited { retry_after: Option<Duration> },
+    /// Non-success response with a JSON error payload
+    #[error("Request failed with status {status}: {}", error.error)]
+    Api {
+        status: reqwest::StatusCode,
+        error: crate::api_error::JupiterApiError,
+    },
+    /// The request or the response body read did not complete in time
+    #[error("Request timed out")]
+    Timeout(#[source] Arc<reqwest::Error>),
+    /// No connection could be established with the server
+    #[error("Failed to connect")]
+    Connect(#[source] Arc<reqwest::Error>),
+    /// The TLS handshake with the server failed
+    #[error("TLS error")]
+    Tls(#[source] Arc<reqwest::Error>),
+    /// The request could not be built or sent for another reason (invalid url, redirect loop, ...)
+    #[error("Failed to send request")]
+    Request(#[source] Arc<reqwest::Error>),
+    /// The connection broke while reading the response body
+    #[error("Failed to read response body")]
+    Body(#[source] Arc<reqwest::Error>),
+    /// The response body is not valid JSON or does not match the expected schema
+    #[error("Failed to deserialize response at {path}, body: {body_snippet}")]
+    DeserializationError {
+        /// Path of the offending field, e.g. `routePlan[0].swapInfo.inAmount`, `.` for the root
+        path: String,
+        /// Start of the body, truncated to [`DESERIALIZATION_BODY_SNIPPET_LEN`] bytes
+        body_snippet: String,
+        #[source]
+        source: Arc<serde_json::Error>,
+    },
+    #[error("Response body exceeded the maximum size of {limit} bytes")]
+    ResponseTooLarge { limit: usize },
+    /// The client is shutting down and no longer accepts new calls
+    #[error("Client is shutting down")]
+    ShuttingDown,
+    /// A [`crate::blockhash::BlockhashProvider`] could not provide a blockhash
+    #[error("Blockhash unavailable: {reason}")]
+    BlockhashUnavailable { reason: String },
+    /// The instructions do not fit in a transaction message
+    #[cfg(feature = "solana")]
+    #[error("Failed to compile transaction message")]
+    MessageCompile(#[source] solana_message::CompileError),
+    /// The quoted price of the pair moved too much recently, see
+    /// [`crate::circuit_breaker::PriceCircuitBreaker`]
+    #[error("Swaps from {input_mint} to {output_mint} halted after a {move_bps} bps price move")]
+    VolatilityHalt {
+        input_mint: crate::solana_types::Pubkey,
+        output_mint: crate::solana_types::Pubkey,
+        move_bps: rust_decimal::Decimal,
+    },
+    /// The swap was rejected by a [`crate::swap_policy::SwapPolicy`] of the client
+    #[error("Quote rejected by policy: {0}")]
+    PolicyViolation(crate::quote_policy::PolicyViolation),
+    /// A [`crate::replay::ReplayClient`] has no recording to serve the call
+    #[error("Replay unavailable: {reason}")]
+    Replay { reason: String },
+    /// A [`crate::quote_builder::QuoteRequestBuilder`] was given parameters that cannot be routed
+    #[error("Invalid quote request: {reason}")]
+    InvalidQuoteRequest { reason: String },
+    /// The request names DEX labels unknown to the router, see
+    /// [`crate::JupiterSwapApiClient::validate_dex_labels`]
+    #[error(
+        "Unknown DEX labels: {}",
+        crate::serde_helpers::comma_separated::join(labels)
+    )]
+    UnknownDexLabels {
+        labels: Vec<crate::dex::UnknownDexLabel>,
+    },
+    /// An Ultra order was returned without a transaction to sign, e.g. when the taker lacks funds
+    #[error("Ultra order {request_id} has no transaction")]
+    UltraOrderWithoutTransaction { request_id: String },
+    /// The swap transaction has no Jupiter route instruction this crate can decode
+    #[error("Swap transaction has no recognized route instruction")]
+    UnrecognizedSwapTransaction,
+    /// The serialized transaction exceeds the packet size by `overflow` bytes
+    #[error("Transaction of {size} bytes exceeds the packet size by {overflow} bytes")]
+    TransactionTooLarge { size: usize, overflow: usize },
+    /// The transaction landed but failed
+    #[cfg(feature = "solana")]
+    #[error("Transaction {signature} failed: {error}")]
+    TransactionFailed {
+        signature: crate::solana_types::Signature,
+        error: solana_transaction_error::TransactionError,
+    },
+    /// The transaction did not land before its blockhash expired, the swap must be quoted again
+    #[error("Transaction {signature} expired after block height {last_valid_block_height}")]
+    TransactionExpired {
+        signature: crate::solana_types::Signature,
+        last_valid_block_height: u64,
+    },
+    /// A signer failed or was missing to sign a transaction
+    #[cfg(feature = "solana")]
+    #[error("Failed to sign transaction")]
+    Signer(#[source] Arc<solana_signer::SignerError>),
+    /// The signer given to sign a swap is not the user the swap was requested for
+    #[error("Signer {signer} does not match the swap user {user_public_key}")]
+    SignerMismatch {
+        user_public_key: crate::solana_types::Pubkey,
+        signer: crate::solana_types::Pubkey,
+    },
+    /// A transaction returned by the API could not be decoded
+    #[error("Failed to decode transaction")]
+    TransactionDecode(#[source] Arc<bincode::Error>),
+    /// A Solana RPC request made by one of the helpers failed
+    #[cfg(feature = "rpc")]
+    #[error("RPC request failed")]
+    Rpc(#[source] Arc<solana_client::client_error::ClientError>),
+}
+
+impl ClientError {
+    /// Status of the non-success response, if any
+    pub fn status(&self) -> Option<reqwest::StatusCode> {
+        match self {
+            Self::RequestFailed { status, .. } | Self::Api { status, .. } => Some(*status),
+            Self::RateLimited { .. } => Some(reqwest::StatusCode::TOO_MANY_REQUESTS),
+            _ => None,
+        }
+    }
+
+    /// Code of the JSON error payload, if any
+    pub fn api_error_code(&self) -> Option<&crate::api_error::ApiErrorCode> {
+        match self {
+            Self::Api { error, .. } => error.error_code.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Whether the failure is transient and the same request may succeed if sent again
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::RequestFailed { status, .. } | Self::Api { status, .. } => {
+                status.is_server_error() || *status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            }
+            Self::RateLimited { .. } | Self::Timeout(_) | Self::Connect(_) | Self::Body(_) => true,
+            Self::Tls(_)
+            | Self::Request(_)
+            | Self::DeserializationError { .. }
+            | Self::ResponseTooLarge { .. }
+            | Self::ShuttingDown
+            | Self::VolatilityHalt { .. }
+            | Self::PolicyViolation(_)
+            | Self::Replay { .. }
+            | Self::UnknownDexLabels { .. }
+            | Self::InvalidQuoteRequest { .. }
+            | Self::UltraOrderWithoutTransaction { .. }
+            | Self::UnrecognizedSwapTransaction
+            | Self::TransactionTooLarge { .. }
+            | Self::TransactionExpired { .. }
+            | Self::SignerMismatch { .. }
+            | Self::TransactionDecode(_) => false,
+            Self::BlockhashUnavailable { .. } => true,
+            #[cfg(feature = "solana")]
+            Self::MessageCompile(_) | Self::TransactionFailed { .. } | Self::Signer(_) => false,
+            #[cfg(feature = "rpc")]
+            Self::Rpc(error) => matches!(
+                error.kind(),
+                solana_client::client_error::ClientErrorKind::Io(_)
+                    | solana_client::client_error::ClientErrorKind::Reqwest(_)
+            ),
+        }
+    }
+}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(error: reqwest::Error) -> Self {
+        let tls = is_tls_error(&error);
+        let error = Arc::new(error);
+        if error.is_timeout() {
+            Self::Timeout(error)
+        } else if error.is_connect() {
+            if tls {
+                Self::Tls(error)
+            } else {
+                Self::Connect(error)
+            }
+        } else if error.is_body() || error.is_decode() {
+            Self::Body(error)
+        } else {
+            Self::Request(error)
+        }
+    }
+}
+
+#[cfg(feature = "solana")]
+impl From<solana_message::CompileError> for ClientError {
+    fn from(error: solana_message::CompileError) -> Self {
+        Self::MessageCompile(error)
+    }
+}
+
+#[cfg(feature = "solana")]
+impl From<solana_signer::SignerError> for ClientError {
+    fn from(error: solana_signer::SignerError) -> Self {
+        Self::Signer(Arc::new(error))
+    }
+}
+
+impl From<bincode::Error> for ClientError {
+    fn from(error: bincode::Error) -> Self {
+        Self::TransactionDecode(Arc::new(error))
+    }
+}
+
+impl From<serde_json::Error> for ClientError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::DeserializationError {
+            path: ".".to_string(),
+            body_snippet: String::new(),
+            source: Arc::new(error),
+        }
+    }
+}
+
+/// Maximum length of [`ClientError::DeserializationError::body_snippet`]
+pub const DESERIALIZATION_BODY_SNIPPET_LEN: usize = 256;
+
+/// Deserializes a response body, reporting the path of the offending field and the start of the
+/// body on failure
+pub(crate) fn deserialize_body<T: serde::de::DeserializeOwned>(
+    body: &[u8],
+) -> Result<T, ClientError> {
+    serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_slice(body)).map_err(
+        |error| {
+            let snippet = &body[..body.len().min(DESERIALIZATION_BODY_SNIPPET_LEN)];
+            let mut body_snippet = String::from_utf8_lossy(snippet).into_owned();
+            if snippet.len() < body.len() {
+                body_snippet.push_str("...");
+            }
+            ClientError::DeserializationError {
+                path: error.path().to_string(),
+                body_snippet,
+                source: Arc::new(error.into_inner()),
+            }
+        },
+    )
+}
+
+#[cfg(feature = "rpc")]
+impl From<solana_client::client_error::ClientError> for ClientError {
+    fn from(error: solana_client::client_error::ClientError) -> Self {
+        Self::Rpc(Arc::new(error))
+    }
+}
+
+/// Whether a connect error failed during the TLS handshake
+///
+/// reqwest does not expose its TLS backend errors. rustls failures are recognized as the invalid
+/// data I/O errors tokio-rustls wraps them in rather than by type, as the rustls version reqwest
+/// depends on changes across its patch releases.
+fn is_tls_error(error: &reqwest::Error) -> bool {
+    let mut source = error.source();
+    while let Some(error) = source {
+        #[cfg(feature = "native-tls")]
+        if error.is::<native_tls::Error>() {
+            return true;
+        }
+        #[cfg(feature = "rustls-tls")]
+        if error.downcast_ref::<std::io::Error>().is_some_and(|error| {
+            error.kind() == std::io::ErrorKind::InvalidData && error.get_ref().is_some()
+        }) {
+            return true;
+        }
+        source = error.source();
+    }
+    false
+}
@@ -0,0 +1,67 @@
+//! Structured events of the swap lifecycle, emitted by the client and the execution helpers to a
+//! single [`SwapEventListener`], see
+//! [`JupiterSwapApiClient::with_event_listener`](crate::JupiterSwapApiClient::with_event_listener).
+
+use std::time::Duration;
+
+use crate::{
+    solana_types::{Pubkey, Signature},
+    ClientError,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwapStage {
+    Quote,
+    /// `/swap` or `/swap-instructions`
+    Build,
+    Submit,
+}
+
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum SwapEvent {
+    QuoteRequested {
+        input_mint: Pubkey,
+        output_mint: Pubkey,
+        amount: u64,
+    },
+    QuoteReceived {
+        input_mint: Pubkey,
+        output_mint: Pubkey,
+        in_amount: u64,
+        out_amount: u64,
+        elapsed: Duration,
+    },
+    TransactionBuilt {
+        input_mint: Pubkey,
+        output_mint: Pubkey,
+        user: Pubkey,
+        elapsed: Duration,
+    },
+    Submitted {
+        signature: Signature,
+    },
+    Confirmed {
+        signature: Signature,
+    },
+    Failed {
+        stage: SwapStage,
+        input_mint: Pubkey,
+        output_mint: Pubkey,
+        /// Set once the transaction was submitted
+        signature: Option<Signature>,
+        error: ClientError,
+    },
+}
+
+/// Called synchronously on the task emitting the event, implementations should forward events to
+/// a channel rather than block
+pub trait SwapEventListener: Send + Sync {
+    fn on_event(&self, event: &SwapEvent);
+}
+
+impl<F: Fn(&SwapEvent) + Send + Sync> SwapEventListener for F {
+    fn on_event(&self, event: &SwapEvent) {
+        self(event)
+    }
+}
@@ -0,0 +1,220 @@
+//! End to end execution of a swap: build the transaction through `/swap`, sign, send and confirm
+//! it, see [`JupiterSwapApiClient::execute_swap`].
+
+use std::time::{Duration, Instant};
+
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_config::RpcSendTransactionConfig};
+use solana_commitment_config::CommitmentConfig;
+use solana_signature::Signature;
+use solana_transaction::versioned::VersionedTransaction;
+
+use crate::{
+    events::{SwapEvent, SwapStage},
+    receipt::SwapReceipt,
+    signature_tracker::SignatureTracker,
+    signer::AsyncSigner,
+    swap::SwapRequest,
+    ClientError, JupiterSwapApiClient,
+};
+
+#[derive(Clone, Debug)]
+pub struct SendOptions {
+    /// Forwarded to `sendTransaction`
+    pub rpc_send_config: RpcSendTransactionConfig,
+    /// Commitment the transaction must reach to be confirmed
+    pub commitment: CommitmentConfig,
+    /// Interval between two signature status polls
+    pub poll_interval: Duration,
+    /// Interval between two submissions of the transaction until it lands, `None` to send it once
+    /// and leave rebroadcasting to the RPC node
+    pub rebroadcast_interval: Option<Duration>,
+    /// Registers every sent transaction, its status transitions are then published up to a
+    /// terminal status, including after it is confirmed with [`commitment`](Self::commitment)
+    pub signature_tracker: Option<SignatureTracker>,
+}
+
+impl Default for SendOptions {
+    fn default() -> Self {
+        Self {
+            rpc_send_config: RpcSendTransactionConfig::default(),
+            commitment: CommitmentConfig::confirmed(),
+            poll_interval: Duration::from_millis(500),
+            rebroadcast_interval: Some(Duration::from_secs(2)),
+            signature_tracker: None,
+        }
+    }
+}
+
+/// A swap confirmed with the requested commitment
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapExecution {
+    pub signature: Signature,
+    pub slot: u64,
+}
+
+impl JupiterSwapApiClient {
+    /// Builds the swap transaction of `swap_request`, signs it with `signer`, sends it and waits
+    /// for its confirmation until its `last_valid_block_height` is exceeded, then fetches the
+    /// receipt of the confirmed swap
+    ///
+    /// `signer` must be the `user_public_key` of the request, otherwise
+    /// [`ClientError::SignerMismatch`] is returned before calling `/swap`.
+    /// A transaction that landed but failed is reported as [`ClientError::TransactionFailed`], one
+    /// that never landed as [`ClientError::TransactionExpired`]. The receipt has no executed
+    /// amounts when the confirmed transaction cannot be fetched, see [`SwapReceipt::from_quote`].
+    pub async fn execute_swap(
+        &self,
+        swap_request: &SwapRequest,
+        signer: &dyn AsyncSigner,
+        rpc_client: &RpcClient,
+        send_options: &SendOptions,
+    ) -> Result<SwapReceipt, ClientError> {
+        self.in_flight(async {
+            if signer.pubkey() != swap_request.user_public_key {
+                return Err(ClientError::SignerMismatch {
+                    user_public_key: swap_request.user_public_key,
+                    signer: signer.pubkey(),
+                });
+            }
+            let swap_response = self.swap(swap_request, None).await?;
+            let transaction = swap_response.sign(&[signer]).await?;
+            if let Some(signature) = transaction.signatures.first() {
+                self.emit_event(SwapEvent::Submitted {
+                    signature: *signature,
+                });
+            }
+            let result = send_and_confirm_transaction(
+                rpc_client,
+                &transaction,
+                swap_response.last_valid_block_height,
+                send_options,
+            )
+            .await;
+            match &result {
+                Ok(execution) => self.emit_event(SwapEvent::Confirmed {
+                    signature: execution.signature,
+                }),
+                Err(error) => self.emit_event(SwapEvent::Failed {
+                    stage: SwapStage::Submit,
+                    input_mint: swap_request.quote_response.input_mint,
+                    output_mint: swap_request.quote_response.output_mint,
+                    signature: transaction.signatures.first().copied(),
+                    error: error.clone(),
+                }),
+            }
+            let execution = result?;
+
+            // The swap landed, failing to fetch its receipt must not be reported as a failed swap
+            let quote_response = &swap_request.quote_response;
+            let receipt = SwapReceipt::fetch(
+                rpc_client,
+                &swap_request.user_public_key,
+                quote_response,
+                &execution.signature,
+            )
+            .await
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| {
+                SwapReceipt::from_quote(quote_response, execution.signature, execution.slot)
+            });
+            Ok(receipt)
+        })
+        .await
+    }
+}
+
+/// Sends the signed `transaction`, submitting it again every
+/// [`rebroadcast_interval`](SendOptions::rebroadcast_interval) until it is confirmed or
+/// `last_valid_block_height` is exceeded, in which case [`ClientError::TransactionExpired`] is
+/// returned and the swap can be quoted again
+pub async fn send_and_confirm_transaction(
+    rpc_client: &RpcClient,
+    transaction: &VersionedTransaction,
+    last_valid_block_height: u64,
+    send_options: &SendOptions,
+) -> Result<SwapExecution, ClientError> {
+    let signature = rpc_client
+        .send_transaction_with_config(transaction, send_options.rpc_send_config)
+        .await?;
+    if let Some(signature_tracker) = &send_options.signature_tracker {
+        signature_tracker.track(signature, Some(last_valid_block_height));
+    }
+    let mut last_sent_at = Instant::now();
+    loop {
+        tokio::time::sleep(send_options.poll_interval).await;
+        let status = rpc_client
+            .get_signature_statuses(&[signature])
+            .await?
+            .value
+            .into_iter()
+            .next()
+            .flatten();
+        if let Some(status) = status {
+            if let Some(error) = status.err {
+                return Err(ClientError::TransactionFailed { signature, error });
+            }
+            if status.satisfies_commitment(send_options.commitment) {
+                return Ok(SwapExecution {
+                    signature,
+                    slot: status.slot,
+                });
+            }
+        } else if rpc_client
+            .get_block_height_with_commitment(send_options.commitment)
+            .await?
+            > last_valid_block_height
+        {
+            return Err(ClientError::TransactionExpired {
+                signature,
+                last_valid_block_height,
+            });
+        } else if send_options
+            .rebroadcast_interval
+            .is_some_and(|rebroadcast_interval| last_sent_at.elapsed() >= rebroadcast_interval)
+        {
+            // The first submission went through preflight, a failed rebroadcast is superseded by
+            // the next one
+            let _ = rpc_client
+                .send_transaction_with_config(
+                    transaction,
+                    RpcSendTransactionConfig {
+                        skip_preflight: true,
+                        ..send_options.rpc_send_config
+                    },
+                )
+                .await;
+            last_sent_at = Instant::now();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn execute_swap_can_be_spawned() {
+        // Only compiles when the future of `execute_swap` is `Send`
+        fn spawn(
+            client: JupiterSwapApiClient,
+            swap_request: SwapRequest,
+            signer: Arc<dyn AsyncSigner>,
+            rpc_client: Arc<RpcClient>,
+        ) -> tokio::task::JoinHandle<Result<SwapReceipt, ClientError>> {
+            tokio::spawn(async move {
+                client
+                    .execute_swap(
+                        &swap_request,
+                        signer.as_ref(),
+                        &rpc_client,
+                        &SendOptions::default(),
+                    )
+                    .await
+            })
+        }
+        let _ = spawn;
+    }
+}
@@ -0,0 +1,185 @@
+//! Checks that a swap fits a legacy transaction, as required by most hardware wallets, and
+//! suggests quote parameters making it fit when it does not.
+
+use solana_message::{Message, VersionedMessage};
+use solana_packet::PACKET_DATA_SIZE;
+use solana_pubkey::Pubkey;
+use solana_transaction::{versioned::VersionedTransaction, Transaction};
+
+use crate::{
+    quote::QuoteRequest,
+    swap::{SwapInstructionsResponse, SwapResponse},
+    ClientError,
+};
+
+/// Maximum number of accounts a transaction can lock
+pub const MAX_TRANSACTION_ACCOUNT_LOCKS: usize = 64;
+
+/// Size of an account key in a legacy message
+const ACCOUNT_KEY_SIZE: usize = 32;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LegacyIssue {
+    /// The quote was not requested with `as_legacy_transaction`
+    NotRequestedAsLegacy,
+    /// The swap relies on address lookup tables, which legacy transactions cannot use
+    RequiresLookupTables {
+        count: usize,
+    },
+    /// The serialized transaction exceeds the packet size
+    TooLarge {
+        size: usize,
+        limit: usize,
+    },
+    TooManyAccounts {
+        count: usize,
+        limit: usize,
+    },
+}
+
+/// Quote parameter change making the swap more likely to fit a legacy transaction
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LegacySuggestion {
+    SetAsLegacyTransaction,
+    SetMaxAccounts(usize),
+    SetOnlyDirectRoutes,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LegacyCompatibility {
+    /// Serialized size of the transaction, signatures included
+    pub size: usize,
+    pub account_count: usize,
+    pub issues: Vec<LegacyIssue>,
+    pub suggestions: Vec<LegacySuggestion>,
+}
+
+impl LegacyCompatibility {
+    fn new(
+        size: usize,
+        account_count: usize,
+        lookup_table_count: usize,
+        quote_request: &QuoteRequest,
+    ) -> Self {
+        let mut issues = Vec::new();
+        if quote_request.as_legacy_transaction != Some(true) {
+            issues.push(LegacyIssue::NotRequestedAsLegacy);
+        }
+        if lookup_table_count > 0 {
+            issues.push(LegacyIssue::RequiresLookupTables {
+                count: lookup_table_count,
+            });
+        }
+        if size > PACKET_DATA_SIZE {
+            issues.push(LegacyIssue::TooLarge {
+                size,
+                limit: PACKET_DATA_SIZE,
+            });
+        }
+        if account_count > MAX_TRANSACTION_ACCOUNT_LOCKS {
+            issues.push(LegacyIssue::TooManyAccounts {
+                count: account_count,
+                limit: MAX_TRANSACTION_ACCOUNT_LOCKS,
+            });
+        }
+
+        let mut suggestions = Vec::new();
+        if issues.contains(&LegacyIssue::NotRequestedAsLegacy) {
+            suggestions.push(LegacySuggestion::SetAsLegacyTransaction);
+        }
+        // Accounts to drop for the transaction to fit, every account costs its key in the message
+        let excess_accounts = size
+            .saturating_sub(PACKET_DATA_SIZE)
+            .div_ceil(ACCOUNT_KEY_SIZE)
+            .max(account_count.saturating_sub(MAX_TRANSACTION_ACCOUNT_LOCKS));
+        if excess_accounts > 0 {
+            let max_accounts = quote_request
+                .max_accounts
+                .unwrap_or(account_count)
+                .min(account_count)
+                .saturating_sub(excess_accounts);
+            suggestions.push(LegacySuggestion::SetMaxAccounts(max_accounts));
+            if quote_request.only_direct_routes != Some(true) {
+                suggestions.push(LegacySuggestion::SetOnlyDirectRoutes);
+            }
+        }
+
+        Self {
+            size,
+            account_count,
+            issues,
+            suggestions,
+        }
+    }
+
+    pub fn is_compatible(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Applies the suggestions to `quote_request`, to quote again
+    pub fn apply_suggestions(&self, quote_request: &mut QuoteRequest) {
+        for suggestion in &self.suggestions {
+            match suggestion {
+                LegacySuggestion::SetAsLegacyTransaction => {
+                    quote_request.as_legacy_transaction = Some(true)
+                }
+                LegacySuggestion::SetMaxAccounts(max_accounts) => {
+                    quote_request.max_accounts = Some(*max_accounts)
+                }
+                LegacySuggestion::SetOnlyDirectRoutes => {
+                    quote_request.only_direct_routes = Some(true)
+                }
+            }
+        }
+    }
+}
+
+impl SwapInstructionsResponse {
+    /// Checks that the instructions fit a legacy transaction paid by `payer`, `quote_request` being
+    /// the request of the quote they were built from
+    pub fn check_legacy(
+        &self,
+        payer: &Pubkey,
+        quote_request: &QuoteRequest,
+    ) -> LegacyCompatibility {
+        let transaction =
+            Transaction::new_unsigned(Message::new(&self.instructions(), Some(payer)));
+        let size = bincode::serialized_size(&transaction).map_or(usize::MAX, |size| size as usize);
+        LegacyCompatibility::new(
+            size,
+            transaction.message.account_keys.len(),
+            self.address_lookup_table_addresses.len(),
+            quote_request,
+        )
+    }
+}
+
+impl SwapResponse {
+    /// Checks that the swap transaction is a legacy transaction within limits, `quote_request`
+    /// being the request of the quote it was built from
+    pub fn check_legacy(
+        &self,
+        quote_request: &QuoteRequest,
+    ) -> Result<LegacyCompatibility, ClientError> {
+        let transaction: VersionedTransaction = bincode::deserialize(&self.swap_transaction)?;
+        let (account_count, lookup_table_count) = match &transaction.message {
+            VersionedMessage::Legacy(message) => (message.account_keys.len(), 0),
+            VersionedMessage::V0(message) => (
+                message.account_keys.len()
+                    + message
+                        .address_table_lookups
+                        .iter()
+                        .map(|lookup| lookup.writable_indexes.len() + lookup.readonly_indexes.len())
+                        .sum::<usize>(),
+                message.address_table_lookups.len(),
+            ),
+        };
+        Ok(LegacyCompatibility::new(
+            self.swap_transaction.len(),
+            account_count,
+            lookup_table_count,
+            quote_request,
+        ))
+    }
+}
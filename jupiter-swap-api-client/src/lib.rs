@@ -1,68 +1,947 @@
-use std::collections::HashMap;
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    future::Future,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc, PoisonError, RwLock,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
-use quote::{InternalQuoteRequest, QuoteRequest, QuoteResponse};
-use reqwest::{Client, Response};
+use api_error::JupiterApiError;
+use circuit_breaker::PriceCircuitBreaker;
+use dex_exclusion::AdaptiveDexExclusion;
+use events::{SwapEvent, SwapEventListener, SwapStage};
+use futures::StreamExt;
+use metrics::{MetricsObserver, RequestInfo};
+use middleware::Middleware;
+use priority_fees::FeeProfiles;
+use program_denylist::ProgramDenylist;
+use quote::{InternalQuoteRequest, QuoteDefaults, QuoteRequest, QuoteResponse};
+use quote_policy::QuotePolicy;
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Client, RequestBuilder, Response, StatusCode,
+};
+use retry::{Attempt, AttemptLog, AttemptLogCallback, AttemptOutcome, RetryPolicy};
 use serde::de::DeserializeOwned;
+use serde_json::Value;
+use solana_types::Pubkey;
 use swap::{SwapInstructionsResponse, SwapInstructionsResponseInternal, SwapRequest, SwapResponse};
-use thiserror::Error;
+use swap_policy::SwapPolicy;
+use tokio::sync::Notify;
 
+pub mod account_locks;
+pub mod api_error;
+#[cfg(feature = "keyring")]
+pub mod api_key_storage;
+pub mod blockhash;
+pub mod builder;
+pub mod canonicalization;
+pub mod capabilities;
+#[cfg(feature = "chaos")]
+pub mod chaos;
+pub mod circuit_breaker;
+pub mod compute_units;
+pub mod contention;
+pub mod dex;
+pub mod dex_exclusion;
+pub mod endpoint_selection;
+pub mod error;
+pub mod events;
+#[cfg(feature = "rpc")]
+pub mod execute;
+#[cfg(feature = "solana")]
+pub mod legacy_transaction;
+#[cfg(feature = "rpc")]
+pub mod max_swap;
+pub mod metrics;
+pub mod middleware;
+#[cfg(feature = "solana")]
+pub mod offline;
+#[cfg(feature = "solana")]
+pub mod payment;
+pub mod portfolio;
+pub mod priority_fees;
+pub mod program_denylist;
 pub mod quote;
+pub mod quote_builder;
+pub mod quote_handle;
+pub mod quote_ladder;
+pub mod quote_policy;
+pub mod quote_watcher;
+pub mod receipt;
+pub mod recurring;
+pub mod replay;
+pub mod request_stats;
+#[cfg(feature = "rpc")]
+pub mod requote;
+pub mod retry;
+pub mod route_constraints;
 pub mod route_plan_with_metadata;
 pub mod serde_helpers;
+pub mod shield;
+#[cfg(feature = "rpc")]
+pub mod signature_tracker;
+#[cfg(feature = "solana")]
+pub mod signer;
+pub mod simulation_error;
+pub mod solana_types;
 pub mod swap;
+pub mod swap_api;
+pub mod swap_instructions_cache;
+pub mod swap_policy;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+pub mod token_extensions;
+pub mod tokens;
+pub mod tracking;
+#[cfg(feature = "solana")]
+pub mod transaction_builder;
+#[cfg(feature = "solana")]
+pub mod transaction_composer;
 pub mod transaction_config;
+pub mod transaction_config_builder;
+#[cfg(feature = "solana")]
+pub mod transaction_verification;
+pub mod ultra;
+
+pub use builder::JupiterSwapApiClientBuilder;
+pub use capabilities::capabilities;
+pub use error::ClientError;
+
+/// Default upper bound on the size of a response body, in bytes.
+pub const DEFAULT_MAX_RESPONSE_BODY_SIZE: usize = 16 * 1024 * 1024;
+
+/// Id of the system program
+pub(crate) const SYSTEM_PROGRAM_ID: Pubkey =
+    Pubkey::from_str_const("11111111111111111111111111111111");
+
+/// Mint of wrapped SOL
+pub const NATIVE_MINT: Pubkey =
+    Pubkey::from_str_const("So11111111111111111111111111111111111111112");
+
+/// Header carrying the API key
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Header carrying the idempotency key of requests building or executing transactions, so that
+/// retrying them after an ambiguous failure cannot be handled twice by the server
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
 
+/// Settings that can be changed on a live client, see [`JupiterSwapApiClient::update_config`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClientConfig {
+    pub base_path: String,
+    /// Base path of the Ultra API, see [`ultra`]
+    pub ultra_base_path: String,
+    /// Base path of the Token API, see [`tokens`]
+    pub tokens_base_path: String,
+    /// Base path of the Recurring API, see [`recurring`]
+    pub recurring_base_path: String,
+    /// Sent in the `api_key_header` header when set
+    pub api_key: Option<String>,
+    /// [`API_KEY_HEADER`] unless the deployment expects the key under another name
+    pub api_key_header: HeaderName,
+    /// Sent with every request, e.g. `x-client-id` or partner tokens
+    pub default_headers: HeaderMap,
+    pub retry_policy: RetryPolicy,
+    /// Merged into every quote request
+    pub quote_defaults: QuoteDefaults,
+    /// Endpoint receiving a share of the quotes, see [`CanaryConfig`]
+    pub canary: Option<CanaryConfig>,
+}
+
+/// Sends `percent` % of the quotes to another base path, e.g. to validate a new endpoint on a
+/// small share of the traffic
+///
+/// Swaps built from those quotes are sent to the same base path, see [`QuoteResponse::origin`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CanaryConfig {
+    pub base_path: String,
+    pub percent: u8,
+}
+
+/// Cloning is cheap: clones share the connection pool, caches and [`ClientConfig`], so updating the
+/// configuration through one clone affects every clone
 #[derive(Clone)]
 pub struct JupiterSwapApiClient {
-    pub base_path: String,
+    inner: Arc<ClientInner>,
 }
 
-#[derive(Debug, Error)]
-pub enum ClientError {
-    #[error("Request failed with status {status}: {body}")]
-    RequestFailed {
-        status: reqwest::StatusCode,
-        body: String,
-    },
-    #[error("Failed to deserialize response: {0}")]
-    DeserializationError(#[from] reqwest::Error),
+struct ClientInner {
+    http_client: Client,
+    config: RwLock<ClientConfig>,
+    /// Maximum number of bytes read from a response body before aborting with
+    /// [`ClientError::ResponseTooLarge`]
+    max_response_body_size: usize,
+    attempt_log_callback: Option<AttemptLogCallback>,
+    program_denylist: Option<Arc<ProgramDenylist>>,
+    dex_exclusion: Option<Arc<AdaptiveDexExclusion>>,
+    circuit_breaker: Option<Arc<PriceCircuitBreaker>>,
+    swap_policies: Vec<Arc<dyn SwapPolicy>>,
+    fee_profiles: Option<Arc<FeeProfiles>>,
+    event_listener: Option<Arc<dyn SwapEventListener>>,
+    metrics_observer: Option<Arc<dyn MetricsObserver>>,
+    middlewares: Vec<Arc<dyn Middleware>>,
+    #[cfg(feature = "chaos")]
+    chaos: Option<Arc<chaos::ChaosTransport>>,
+    request_stats: request_stats::RequestStats,
+    /// Number of quotes sent, every quote whose number modulo 100 is below the canary percent is
+    /// sent to the canary
+    quote_count: AtomicU64,
+    /// Shared with the detached copies, so that shutting down the client shuts down its `with_*`
+    /// clones too
+    shutdown: Arc<ShutdownState>,
+}
+
+#[derive(Default)]
+struct ShutdownState {
+    shutting_down: AtomicBool,
+    in_flight: AtomicUsize,
+    drained: Notify,
 }
 
-async fn check_is_success(response: Response) -> Result<Response, ClientError> {
+tokio::task_local! {
+    /// Shutdown state of the call in flight on the current task, the calls it makes on the same
+    /// client are part of it rather than new calls
+    static CALL_IN_FLIGHT: Arc<ShutdownState>;
+}
+
+/// Tracks a call in flight for the duration of its lifetime
+struct InFlightGuard(Arc<ShutdownState>);
+
+impl InFlightGuard {
+    fn acquire(shutdown: &Arc<ShutdownState>) -> Result<Self, ClientError> {
+        shutdown.in_flight.fetch_add(1, Ordering::SeqCst);
+        let guard = Self(shutdown.clone());
+        if shutdown.shutting_down.load(Ordering::SeqCst) {
+            return Err(ClientError::ShuttingDown);
+        }
+        Ok(guard)
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        if self.0.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.drained.notify_waiters();
+        }
+    }
+}
+
+impl ClientInner {
+    fn config_mut(&mut self) -> &mut ClientConfig {
+        self.config
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Copy that no longer shares its configuration with `self`, the connection pool is still shared
+    fn detached(&self) -> Self {
+        Self {
+            http_client: self.http_client.clone(),
+            config: RwLock::new(
+                self.config
+                    .read()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .clone(),
+            ),
+            max_response_body_size: self.max_response_body_size,
+            attempt_log_callback: self.attempt_log_callback.clone(),
+            program_denylist: self.program_denylist.clone(),
+            dex_exclusion: self.dex_exclusion.clone(),
+            circuit_breaker: self.circuit_breaker.clone(),
+            swap_policies: self.swap_policies.clone(),
+            fee_profiles: self.fee_profiles.clone(),
+            event_listener: self.event_listener.clone(),
+            metrics_observer: self.metrics_observer.clone(),
+            middlewares: self.middlewares.clone(),
+            #[cfg(feature = "chaos")]
+            chaos: self.chaos.clone(),
+            request_stats: Default::default(),
+            quote_count: AtomicU64::new(0),
+            shutdown: self.shutdown.clone(),
+        }
+    }
+}
+
+/// Reads the response body chunk by chunk, aborting as soon as `limit` would be exceeded
+async fn read_body_with_limit(
+    mut response: Response,
+    limit: usize,
+) -> Result<Vec<u8>, ClientError> {
+    if response
+        .content_length()
+        .is_some_and(|content_length| content_length > limit as u64)
+    {
+        return Err(ClientError::ResponseTooLarge { limit });
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if body.len() + chunk.len() > limit {
+            return Err(ClientError::ResponseTooLarge { limit });
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+/// Delay requested by a rate limited response
+///
+/// `Retry-After` holds seconds or an HTTP date. The rate limit reset headers hold seconds, or the
+/// Unix time of the reset when larger than the current Unix time.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    retry_after_at(headers, SystemTime::now())
+}
+
+fn retry_after_at(headers: &HeaderMap, now: SystemTime) -> Option<Duration> {
+    let header = |name: &str| headers.get(name)?.to_str().ok().map(str::trim);
+    let seconds = |value: &str| Duration::try_from_secs_f64(value.parse::<f64>().ok()?).ok();
+
+    if let Some(value) = header("retry-after") {
+        if let Some(delay) = seconds(value) {
+            return Some(delay);
+        }
+        if let Ok(date) = httpdate::parse_http_date(value) {
+            return Some(date.duration_since(now).unwrap_or_default());
+        }
+    }
+    ["ratelimit-reset", "x-ratelimit-reset"]
+        .into_iter()
+        .find_map(|name| {
+            let reset = seconds(header(name)?)?;
+            let since_epoch = now.duration_since(UNIX_EPOCH).ok()?;
+            Some(if reset > since_epoch {
+                reset - since_epoch
+            } else {
+                reset
+            })
+        })
+}
+
+async fn check_is_success(response: Response, limit: usize) -> Result<Response, ClientError> {
+    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+        return Err(ClientError::RateLimited {
+            retry_after: retry_after(response.headers()),
+        });
+    }
     if !response.status().is_success() {
         let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        return Err(ClientError::RequestFailed { status, body });
+        let body = read_body_with_limit(response, limit)
+            .await
+            .unwrap_or_default();
+        return Err(match serde_json::from_slice::<JupiterApiError>(&body) {
+            Ok(error) => ClientError::Api { status, error },
+            Err(_) => ClientError::RequestFailed {
+                status,
+                body: String::from_utf8_lossy(&body).into_owned(),
+            },
+        });
     }
     Ok(response)
 }
 
 async fn check_status_code_and_deserialize<T: DeserializeOwned>(
     response: Response,
-) -> Result<T, ClientError> {
-    let response = check_is_success(response).await?;
-    response
-        .json::<T>()
-        .await
-        .map_err(ClientError::DeserializationError)
+    limit: usize,
+) -> Result<(T, StatusCode), ClientError> {
+    let response = check_is_success(response, limit).await?;
+    let status = response.status();
+    let body = read_body_with_limit(response, limit).await?;
+    Ok((error::deserialize_body(&body)?, status))
+}
+
+/// Swaps are sent to the API that returned their quote when it is not the configured base path,
+/// e.g. a per-call override or the canary, and to the current base path otherwise
+fn swap_base_path(config: &ClientConfig, swap_request: &SwapRequest) -> String {
+    swap_request
+        .quote_response
+        .origin
+        .clone()
+        .unwrap_or_else(|| config.base_path.clone())
 }
 
 impl JupiterSwapApiClient {
     pub fn new(base_path: String) -> Self {
-        Self { base_path }
+        Self::with_client(base_path, Client::new())
+    }
+
+    /// Sends requests through `http_client`, e.g. to configure proxies, TLS roots or pool sizes
+    pub fn with_client(base_path: String, http_client: Client) -> Self {
+        Self {
+            inner: Arc::new(ClientInner {
+                http_client,
+                config: RwLock::new(ClientConfig {
+                    base_path,
+                    ultra_base_path: ultra::DEFAULT_ULTRA_BASE_PATH.to_string(),
+                    tokens_base_path: tokens::DEFAULT_TOKENS_BASE_PATH.to_string(),
+                    recurring_base_path: recurring::DEFAULT_RECURRING_BASE_PATH.to_string(),
+                    api_key: None,
+                    api_key_header: HeaderName::from_static(API_KEY_HEADER),
+                    default_headers: HeaderMap::new(),
+                    retry_policy: RetryPolicy::default(),
+                    quote_defaults: QuoteDefaults::default(),
+                    canary: None,
+                }),
+                max_response_body_size: DEFAULT_MAX_RESPONSE_BODY_SIZE,
+                attempt_log_callback: None,
+                program_denylist: None,
+                dex_exclusion: None,
+                circuit_breaker: None,
+                swap_policies: Vec::new(),
+                fee_profiles: None,
+                event_listener: None,
+                metrics_observer: None,
+                middlewares: Vec::new(),
+                #[cfg(feature = "chaos")]
+                chaos: None,
+                request_stats: Default::default(),
+                quote_count: AtomicU64::new(0),
+                shutdown: Default::default(),
+            }),
+        }
+    }
+
+    /// Applies `update` to the inner state, detaching it first if it is shared with other clones so
+    /// that `with_*` methods never affect existing clones, unlike `set_*` methods
+    fn with_inner(mut self, update: impl FnOnce(&mut ClientInner)) -> Self {
+        if Arc::get_mut(&mut self.inner).is_none() {
+            self.inner = Arc::new(self.inner.detached());
+        }
+        update(Arc::get_mut(&mut self.inner).expect("inner is not shared"));
+        self
+    }
+
+    /// Overrides the maximum response body size, see [`DEFAULT_MAX_RESPONSE_BODY_SIZE`]
+    pub fn with_max_response_body_size(self, max_response_body_size: usize) -> Self {
+        self.with_inner(|inner| inner.max_response_body_size = max_response_body_size)
+    }
+
+    pub fn max_response_body_size(&self) -> usize {
+        self.inner.max_response_body_size
+    }
+
+    pub fn with_api_key(self, api_key: String) -> Self {
+        self.with_inner(|inner| inner.config_mut().api_key = Some(api_key))
+    }
+
+    pub fn with_api_key_header(self, api_key_header: HeaderName) -> Self {
+        self.with_inner(|inner| inner.config_mut().api_key_header = api_key_header)
+    }
+
+    /// Sends `value` in the `name` header of every request
+    pub fn with_default_header(self, name: HeaderName, value: HeaderValue) -> Self {
+        self.with_inner(|inner| {
+            inner.config_mut().default_headers.insert(name, value);
+        })
+    }
+
+    pub fn with_default_headers(self, default_headers: HeaderMap) -> Self {
+        self.with_inner(|inner| inner.config_mut().default_headers.extend(default_headers))
+    }
+
+    pub fn with_retry_policy(self, retry_policy: RetryPolicy) -> Self {
+        self.with_inner(|inner| inner.config_mut().retry_policy = retry_policy)
+    }
+
+    pub fn with_quote_defaults(self, quote_defaults: QuoteDefaults) -> Self {
+        self.with_inner(|inner| inner.config_mut().quote_defaults = quote_defaults)
+    }
+
+    pub fn with_canary(self, canary: CanaryConfig) -> Self {
+        self.with_inner(|inner| inner.config_mut().canary = Some(canary))
+    }
+
+    /// Snapshot of the current configuration
+    pub fn config(&self) -> ClientConfig {
+        self.inner
+            .config
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Atomically updates the configuration, calls already in flight keep the configuration they started with
+    pub fn update_config(&self, update: impl FnOnce(&mut ClientConfig)) {
+        update(
+            &mut self
+                .inner
+                .config
+                .write()
+                .unwrap_or_else(PoisonError::into_inner),
+        );
+    }
+
+    pub fn base_path(&self) -> String {
+        self.config().base_path
+    }
+
+    pub fn set_base_path(&self, base_path: String) {
+        self.update_config(|config| config.base_path = base_path);
+    }
+
+    pub fn set_api_key(&self, api_key: Option<String>) {
+        self.update_config(|config| config.api_key = api_key);
+    }
+
+    pub fn set_default_headers(&self, default_headers: HeaderMap) {
+        self.update_config(|config| config.default_headers = default_headers);
+    }
+
+    pub fn set_retry_policy(&self, retry_policy: RetryPolicy) {
+        self.update_config(|config| config.retry_policy = retry_policy);
+    }
+
+    pub fn set_quote_defaults(&self, quote_defaults: QuoteDefaults) {
+        self.update_config(|config| config.quote_defaults = quote_defaults);
+    }
+
+    pub fn set_canary(&self, canary: Option<CanaryConfig>) {
+        self.update_config(|config| config.canary = canary);
+    }
+
+    /// Registers a callback receiving the [`AttemptLog`] of every completed call
+    pub fn with_attempt_log_callback(
+        self,
+        callback: impl Fn(&AttemptLog) + Send + Sync + 'static,
+    ) -> Self {
+        self.with_inner(|inner| inner.attempt_log_callback = Some(Arc::new(callback)))
+    }
+
+    /// Excludes the DEXes of the given AMM programs from every quote
+    pub fn with_program_denylist(self, program_denylist: ProgramDenylist) -> Self {
+        self.with_inner(|inner| inner.program_denylist = Some(Arc::new(program_denylist)))
+    }
+
+    /// Excludes the DEXes failing repeatedly from every quote, failures and successes are recorded
+    /// by the caller through the shared [`AdaptiveDexExclusion`]
+    pub fn with_dex_exclusion(self, dex_exclusion: Arc<AdaptiveDexExclusion>) -> Self {
+        self.with_inner(|inner| inner.dex_exclusion = Some(dex_exclusion))
+    }
+
+    /// Observes every quote and refuses to build swaps of pairs halted by `circuit_breaker`
+    pub fn with_circuit_breaker(self, circuit_breaker: Arc<PriceCircuitBreaker>) -> Self {
+        self.with_inner(|inner| inner.circuit_breaker = Some(circuit_breaker))
+    }
+
+    /// Refuses to build swaps from quotes exceeding the limits of `quote_policy`
+    pub fn with_quote_policy(self, quote_policy: QuotePolicy) -> Self {
+        self.with_swap_policy(Arc::new(quote_policy))
+    }
+
+    /// Adds a policy checking every swap, policies run in the order they were added and the first
+    /// violation fails the call with [`ClientError::PolicyViolation`]
+    pub fn with_swap_policy(self, swap_policy: Arc<dyn SwapPolicy>) -> Self {
+        self.with_inner(|inner| inner.swap_policies.push(swap_policy))
+    }
+
+    /// Fee profiles used to resolve [`TransactionConfig::fee_profile`](transaction_config::TransactionConfig::fee_profile),
+    /// their default values are used otherwise
+    pub fn with_fee_profiles(self, fee_profiles: Arc<FeeProfiles>) -> Self {
+        self.with_inner(|inner| inner.fee_profiles = Some(fee_profiles))
+    }
+
+    /// Injects the faults of `chaos` into every request, for resilience testing
+    #[cfg(feature = "chaos")]
+    pub fn with_chaos(self, chaos: Arc<chaos::ChaosTransport>) -> Self {
+        self.with_inner(|inner| inner.chaos = Some(chaos))
+    }
+
+    /// Streams the lifecycle events of every quote and swap to `event_listener`
+    pub fn with_event_listener(self, event_listener: Arc<dyn SwapEventListener>) -> Self {
+        self.with_inner(|inner| inner.event_listener = Some(event_listener))
+    }
+
+    /// Reports the start, latency and outcome of every HTTP attempt to `metrics_observer`
+    pub fn with_metrics_observer(self, metrics_observer: Arc<dyn MetricsObserver>) -> Self {
+        self.with_inner(|inner| inner.metrics_observer = Some(metrics_observer))
+    }
+
+    /// Appends `middleware` to the middlewares seeing every HTTP attempt
+    pub fn with_middleware(self, middleware: Arc<dyn Middleware>) -> Self {
+        self.with_inner(|inner| inner.middlewares.push(middleware))
+    }
+
+    /// Forwards `event` to the event listener, for the stages handled outside the client such as
+    /// submitting the transaction
+    pub fn emit_event(&self, event: SwapEvent) {
+        if let Some(event_listener) = &self.inner.event_listener {
+            event_listener.on_event(&event);
+        }
+    }
+
+    fn record_quote(
+        &self,
+        quote_request: &QuoteRequest,
+        started_at: Instant,
+        result: &Result<QuoteResponse, ClientError>,
+    ) {
+        self.inner.request_stats.record_quote(
+            quote_request.input_mint,
+            quote_request.output_mint,
+            result,
+        );
+        self.emit_event(match result {
+            Ok(quote_response) => SwapEvent::QuoteReceived {
+                input_mint: quote_response.input_mint,
+                output_mint: quote_response.output_mint,
+                in_amount: quote_response.in_amount,
+                out_amount: quote_response.out_amount,
+                elapsed: started_at.elapsed(),
+            },
+            Err(error) => SwapEvent::Failed {
+                stage: SwapStage::Quote,
+                input_mint: quote_request.input_mint,
+                output_mint: quote_request.output_mint,
+                signature: None,
+                error: error.clone(),
+            },
+        });
+    }
+
+    fn record_swap<T>(
+        &self,
+        swap_request: &SwapRequest,
+        started_at: Instant,
+        result: &Result<T, ClientError>,
+    ) {
+        let quote_response = &swap_request.quote_response;
+        self.inner.request_stats.record_swap(
+            quote_response.input_mint,
+            quote_response.output_mint,
+            result,
+        );
+        self.emit_event(match result {
+            Ok(_) => SwapEvent::TransactionBuilt {
+                input_mint: quote_response.input_mint,
+                output_mint: quote_response.output_mint,
+                user: swap_request.user_public_key,
+                elapsed: started_at.elapsed(),
+            },
+            Err(error) => SwapEvent::Failed {
+                stage: SwapStage::Build,
+                input_mint: quote_response.input_mint,
+                output_mint: quote_response.output_mint,
+                signature: None,
+                error: error.clone(),
+            },
+        });
+    }
+
+    fn check_swap_guards(&self, swap_request: &SwapRequest) -> Result<(), ClientError> {
+        for swap_policy in &self.inner.swap_policies {
+            swap_policy
+                .validate_quote(&swap_request.quote_response)
+                .map_err(ClientError::PolicyViolation)?;
+        }
+        match &self.inner.circuit_breaker {
+            Some(circuit_breaker) => circuit_breaker.check(&swap_request.quote_response),
+            None => Ok(()),
+        }
+    }
+
+    /// Resolves the fee profile selected on the transaction config, if any
+    fn resolve_fee_profile<'a>(&self, swap_request: &'a SwapRequest) -> Cow<'a, SwapRequest> {
+        if swap_request.config.fee_profile.is_none() {
+            return Cow::Borrowed(swap_request);
+        }
+        let mut swap_request = swap_request.clone();
+        match &self.inner.fee_profiles {
+            Some(fee_profiles) => swap_request.config.apply_fee_profile(fee_profiles, None),
+            None => swap_request
+                .config
+                .apply_fee_profile(&FeeProfiles::default(), None),
+        }
+        Cow::Owned(swap_request)
+    }
+
+    /// Stops accepting new calls, which fail with [`ClientError::ShuttingDown`], then waits up to
+    /// `grace` for the calls in flight on this client and its clones, `with_*` ones included, to
+    /// complete
+    ///
+    /// A call is a whole public method, whatever the number of requests it sends. Returns whether
+    /// every call in flight completed within the grace period. Attempt log callbacks are invoked
+    /// before a call completes, so nothing is left buffered once drained.
+    pub async fn shutdown(&self, grace: Duration) -> bool {
+        let shutdown = &self.inner.shutdown;
+        shutdown.shutting_down.store(true, Ordering::SeqCst);
+
+        let deadline = tokio::time::Instant::now() + grace;
+        loop {
+            let drained = shutdown.drained.notified();
+            if shutdown.in_flight.load(Ordering::SeqCst) == 0 {
+                return true;
+            }
+            if tokio::time::timeout_at(deadline, drained).await.is_err() {
+                return shutdown.in_flight.load(Ordering::SeqCst) == 0;
+            }
+        }
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.inner.shutdown.shutting_down.load(Ordering::SeqCst)
+    }
+
+    /// Runs `call` as a single call in flight, for the whole of a public method
+    ///
+    /// Calls made by `call` on this client or its clones are part of it, so a shutdown starting
+    /// midway lets a multi-request call complete instead of failing its remaining requests.
+    pub(crate) async fn in_flight<T>(
+        &self,
+        call: impl Future<Output = Result<T, ClientError>>,
+    ) -> Result<T, ClientError> {
+        let shutdown = &self.inner.shutdown;
+        if CALL_IN_FLIGHT
+            .try_with(|in_flight| Arc::ptr_eq(in_flight, shutdown))
+            .unwrap_or(false)
+        {
+            return call.await;
+        }
+        let _in_flight = InFlightGuard::acquire(shutdown)?;
+        CALL_IN_FLIGHT.scope(shutdown.clone(), call).await
+    }
+
+    /// Sends the request built by `request`, retrying retryable failures according to the retry policy
+    async fn send<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        request: impl Fn(&Client, &str) -> RequestBuilder,
+    ) -> Result<T, ClientError> {
+        self.send_to(|config| config.base_path.clone(), path, request)
+            .await
+    }
+
+    /// Same as `send` for an API hosted under another base path of the configuration
+    pub(crate) async fn send_to<T: DeserializeOwned>(
+        &self,
+        base_path: impl FnOnce(&ClientConfig) -> String,
+        path: &str,
+        request: impl Fn(&Client, &str) -> RequestBuilder,
+    ) -> Result<T, ClientError> {
+        let config = self.config();
+        let url = format!("{}{path}", base_path(&config));
+        let mut attempt_log = AttemptLog::default();
+        let mut retry = 0;
+        let result = loop {
+            let request_info = RequestInfo {
+                endpoint: path,
+                url: &url,
+                retry,
+            };
+            if let Some(metrics_observer) = &self.inner.metrics_observer {
+                metrics_observer.on_request_start(&request_info);
+            }
+            let start = Instant::now();
+            let mut request =
+                request(&self.inner.http_client, &url).headers(config.default_headers.clone());
+            if let Some(api_key) = &config.api_key {
+                request = request.header(&config.api_key_header, api_key);
+            }
+            for middleware in &self.inner.middlewares {
+                request = middleware.on_request(&request_info, request);
+            }
+            let result = self.execute(&request_info, request).await;
+            let latency = start.elapsed();
+            if let Some(metrics_observer) = &self.inner.metrics_observer {
+                match &result {
+                    Ok((_, status)) => {
+                        metrics_observer.on_response(&request_info, *status, latency)
+                    }
+                    Err(error) => metrics_observer.on_error(&request_info, error, latency),
+                }
+            }
+            let result = result.map(|(response, _)| response);
+            attempt_log.attempts.push(Attempt {
+                endpoint: url.clone(),
+                latency,
+                outcome: match &result {
+                    Ok(_) => AttemptOutcome::Success,
+                    Err(error) => AttemptOutcome::Failed(error.clone()),
+                },
+            });
+            match result {
+                Err(error) if error.is_retryable() && retry < config.retry_policy.max_retries => {
+                    tokio::time::sleep(config.retry_policy.delay(retry, &error)).await;
+                    retry += 1;
+                }
+                result => break result,
+            }
+        };
+
+        if let Some(attempt_log_callback) = &self.inner.attempt_log_callback {
+            attempt_log_callback(&attempt_log);
+        }
+        result
+    }
+
+    async fn execute<T: DeserializeOwned>(
+        &self,
+        request_info: &RequestInfo<'_>,
+        request: RequestBuilder,
+    ) -> Result<(T, StatusCode), ClientError> {
+        let observe_response = |response: &Response| {
+            for middleware in &self.inner.middlewares {
+                middleware.on_response(request_info, response);
+            }
+        };
+        #[cfg(feature = "chaos")]
+        if let Some(chaos) = &self.inner.chaos {
+            return chaos
+                .execute(request, self.inner.max_response_body_size, observe_response)
+                .await;
+        }
+        match request.send().await {
+            Ok(response) => {
+                observe_response(&response);
+                check_status_code_and_deserialize(response, self.inner.max_response_body_size).await
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Same as `send_to` with an [`IDEMPOTENCY_KEY_HEADER`] shared by every attempt, `idempotency_key`
+    /// is generated when unset and retries are enabled
+    pub(crate) async fn send_idempotent<T: DeserializeOwned>(
+        &self,
+        base_path: impl FnOnce(&ClientConfig) -> String,
+        path: &str,
+        idempotency_key: Option<&str>,
+        request: impl Fn(&Client, &str) -> RequestBuilder,
+    ) -> Result<T, ClientError> {
+        let idempotency_key = match idempotency_key {
+            Some(idempotency_key) => Some(Cow::Borrowed(idempotency_key)),
+            None if self.config().retry_policy.max_retries > 0 => {
+                Some(Cow::Owned(uuid::Uuid::new_v4().to_string()))
+            }
+            None => None,
+        };
+        self.send_to(base_path, path, |client, url| match &idempotency_key {
+            Some(idempotency_key) => {
+                request(client, url).header(IDEMPOTENCY_KEY_HEADER, idempotency_key.as_ref())
+            }
+            None => request(client, url),
+        })
+        .await
     }
 
     pub async fn quote(&self, quote_request: &QuoteRequest) -> Result<QuoteResponse, ClientError> {
-        let url = format!("{}/quote", self.base_path);
+        self.quote_with_base_path(quote_request, None).await
+    }
+
+    /// Same as [`Self::quote`], sent to `base_path` instead of the configured base path or canary
+    /// when provided
+    pub async fn quote_with_base_path(
+        &self,
+        quote_request: &QuoteRequest,
+        base_path: Option<&str>,
+    ) -> Result<QuoteResponse, ClientError> {
+        self.in_flight(async {
+            self.emit_event(SwapEvent::QuoteRequested {
+                input_mint: quote_request.input_mint,
+                output_mint: quote_request.output_mint,
+                amount: quote_request.amount,
+            });
+            let started_at = Instant::now();
+            let result = self.send_quote(quote_request, base_path).await;
+            self.record_quote(quote_request, started_at, &result);
+            result
+        })
+        .await
+    }
+
+    async fn send_quote(
+        &self,
+        quote_request: &QuoteRequest,
+        base_path: Option<&str>,
+    ) -> Result<QuoteResponse, ClientError> {
+        let (mut quote_response, origin) = self
+            .send_quote_as::<QuoteResponse>(quote_request, base_path)
+            .await?;
+        quote_response.origin = origin;
+        if let Some(circuit_breaker) = &self.inner.circuit_breaker {
+            circuit_breaker.observe(&quote_response);
+        }
+        Ok(quote_response)
+    }
+
+    /// Applies the quote defaults and exclusions then sends the quote, along with the base path it
+    /// was sent to when it is not the configured base path
+    async fn send_quote_as<T: DeserializeOwned>(
+        &self,
+        quote_request: &QuoteRequest,
+        base_path: Option<&str>,
+    ) -> Result<(T, Option<String>), ClientError> {
         let extra_args = quote_request.quote_args.clone();
-        let internal_quote_request = InternalQuoteRequest::from(quote_request.clone());
-        let response = Client::new()
-            .get(url)
-            .query(&internal_quote_request)
-            .query(&extra_args)
-            .send()
+        let mut quote_request = quote_request.clone();
+        let config = self.config();
+        config.quote_defaults.apply(&mut quote_request);
+        if let Some(program_denylist) = &self.inner.program_denylist {
+            program_denylist.apply(self, &mut quote_request).await?;
+        }
+        if let Some(dex_exclusion) = &self.inner.dex_exclusion {
+            dex_exclusion.apply(&mut quote_request);
+        }
+        let configured_base_path = config.base_path.clone();
+        let base_path = match base_path {
+            Some(base_path) => base_path.to_string(),
+            None => self.select_quote_base_path(config),
+        };
+        let internal_quote_request = InternalQuoteRequest::from(quote_request);
+        let quote_response = self
+            .send_to(
+                |_| base_path.clone(),
+                "/quote",
+                |client, url| {
+                    client
+                        .get(url)
+                        .query(&internal_quote_request)
+                        .query(&extra_args)
+                },
+            )
             .await?;
-        check_status_code_and_deserialize(response).await
+        let origin = (base_path != configured_base_path).then_some(base_path);
+        Ok((quote_response, origin))
+    }
+
+    /// Same as [`Self::quote`] returning the response as received, for fields the typed response
+    /// does not have yet
+    pub async fn quote_raw(&self, quote_request: &QuoteRequest) -> Result<Value, ClientError> {
+        self.in_flight(async { Ok(self.send_quote_as(quote_request, None).await?.0) })
+            .await
+    }
+
+    /// Quotes every request of `quote_requests` with at most `concurrency` requests in flight,
+    /// results are in the order of the requests
+    ///
+    /// Every quote shares the connection pool of the client. A `concurrency` of 0 is treated as 1.
+    pub async fn quote_many(
+        &self,
+        quote_requests: &[QuoteRequest],
+        concurrency: usize,
+    ) -> Vec<Result<QuoteResponse, ClientError>> {
+        // Futures owning their request and a client handle, borrowing ones fail the `Send` check
+        // of `tokio::spawn` on callers
+        let quotes = futures::stream::iter(quote_requests.iter().cloned())
+            .map(|quote_request| {
+                let client = self.clone();
+                async move { client.quote(&quote_request).await }
+            })
+            .buffered(concurrency.max(1))
+            .collect();
+        self.in_flight(async { Ok(quotes.await) })
+            .await
+            .unwrap_or_else(|error| quote_requests.iter().map(|_| Err(error.clone())).collect())
+    }
+
+    fn select_quote_base_path(&self, config: ClientConfig) -> String {
+        let quote_count = self.inner.quote_count.fetch_add(1, Ordering::Relaxed);
+        match config.canary {
+            Some(canary) if quote_count % 100 < u64::from(canary.percent) => canary.base_path,
+            _ => config.base_path,
+        }
     }
 
     pub async fn swap(
@@ -70,26 +949,240 @@ impl JupiterSwapApiClient {
         swap_request: &SwapRequest,
         extra_args: Option<HashMap<String, String>>,
     ) -> Result<SwapResponse, ClientError> {
-        let response = Client::new()
-            .post(format!("{}/swap", self.base_path))
-            .query(&extra_args)
-            .json(swap_request)
-            .send()
-            .await?;
-        check_status_code_and_deserialize(response).await
+        self.in_flight(async {
+            let started_at = Instant::now();
+            let result = self
+                .send_swap(swap_request, extra_args)
+                .await
+                .and_then(|swap_response| {
+                    for swap_policy in &self.inner.swap_policies {
+                        swap_policy
+                            .validate_swap_response(swap_request, &swap_response)
+                            .map_err(ClientError::PolicyViolation)?;
+                    }
+                    Ok(swap_response)
+                });
+            self.record_swap(swap_request, started_at, &result);
+            result
+        })
+        .await
+    }
+
+    /// Same as [`Self::swap`] returning the response as received
+    pub async fn swap_raw(
+        &self,
+        swap_request: &SwapRequest,
+        extra_args: Option<HashMap<String, String>>,
+    ) -> Result<Value, ClientError> {
+        self.in_flight(self.send_swap(swap_request, extra_args))
+            .await
+    }
+
+    async fn send_swap<T: DeserializeOwned>(
+        &self,
+        swap_request: &SwapRequest,
+        extra_args: Option<HashMap<String, String>>,
+    ) -> Result<T, ClientError> {
+        self.check_swap_guards(swap_request)?;
+        let swap_request = self.resolve_fee_profile(swap_request);
+        let body = swap_request.body()?;
+        self.send_idempotent(
+            |config| swap_base_path(config, &swap_request),
+            "/swap",
+            swap_request.config.idempotency_key.as_deref(),
+            |client, url| client.post(url).query(&extra_args).json(&body),
+        )
+        .await
     }
 
     pub async fn swap_instructions(
         &self,
         swap_request: &SwapRequest,
     ) -> Result<SwapInstructionsResponse, ClientError> {
-        let response = Client::new()
-            .post(format!("{}/swap-instructions", self.base_path))
-            .json(swap_request)
-            .send()
-            .await?;
-        check_status_code_and_deserialize::<SwapInstructionsResponseInternal>(response)
+        self.in_flight(async {
+            let started_at = Instant::now();
+            let result = self
+                .send_swap_instructions::<SwapInstructionsResponseInternal>(swap_request)
+                .await
+                .map(Into::into);
+            self.record_swap(swap_request, started_at, &result);
+            result
+        })
+        .await
+    }
+
+    /// Same as [`Self::swap_instructions`] returning the response as received
+    pub async fn swap_instructions_raw(
+        &self,
+        swap_request: &SwapRequest,
+    ) -> Result<Value, ClientError> {
+        self.in_flight(self.send_swap_instructions(swap_request))
             .await
-            .map(Into::into)
+    }
+
+    async fn send_swap_instructions<T: DeserializeOwned>(
+        &self,
+        swap_request: &SwapRequest,
+    ) -> Result<T, ClientError> {
+        self.check_swap_guards(swap_request)?;
+        let swap_request = self.resolve_fee_profile(swap_request);
+        let body = swap_request.body()?;
+        self.send_idempotent(
+            |config| swap_base_path(config, &swap_request),
+            "/swap-instructions",
+            swap_request.config.idempotency_key.as_deref(),
+            |client, url| client.post(url).json(&body),
+        )
+        .await
+    }
+
+    /// Maps every AMM program id supported by the router to its DEX label
+    pub async fn program_id_to_label(&self) -> Result<HashMap<Pubkey, String>, ClientError> {
+        let program_id_to_label = self
+            .in_flight(
+                self.send::<HashMap<String, String>>("/program-id-to-label", |client, url| {
+                    client.get(url)
+                }),
+            )
+            .await?;
+        Ok(program_id_to_label
+            .into_iter()
+            .filter_map(|(program_id, label)| {
+                Pubkey::from_str(&program_id)
+                    .ok()
+                    .map(|program_id| (program_id, label))
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction_config::TransactionConfig;
+
+    fn swap_request(origin: Option<&str>) -> SwapRequest {
+        let mut quote_response: QuoteResponse = serde_json::from_value(serde_json::json!({
+            "inputMint": "So11111111111111111111111111111111111111112",
+            "inAmount": "1000000",
+            "outputMint": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+            "outAmount": "1000000",
+            "otherAmountThreshold": "995000",
+            "swapMode": "ExactIn",
+            "slippageBps": 50,
+            "platformFee": null,
+            "priceImpactPct": "0",
+            "routePlan": [],
+        }))
+        .unwrap();
+        quote_response.origin = origin.map(str::to_string);
+        SwapRequest {
+            user_public_key: Pubkey::new_from_array([1; 32]),
+            quote_response,
+            config: TransactionConfig::default(),
+        }
+    }
+
+    #[test]
+    fn swaps_follow_the_current_base_path_unless_pinned() {
+        let client = JupiterSwapApiClient::new("https://old.example".to_string());
+        let unpinned = swap_request(None);
+        let pinned = swap_request(Some("https://canary.example"));
+        client.set_base_path("https://new.example".to_string());
+
+        let config = client.config();
+        assert_eq!(swap_base_path(&config, &unpinned), "https://new.example");
+        assert_eq!(swap_base_path(&config, &pinned), "https://canary.example");
+    }
+
+    #[test]
+    fn quote_many_can_be_spawned() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        // Nothing listens on the discard port, every quote fails to connect
+        let client = JupiterSwapApiClient::new("http://127.0.0.1:9".to_string());
+        let requests = vec![QuoteRequest::default(), QuoteRequest::default()];
+        let results = runtime.block_on(async move {
+            tokio::spawn(async move { client.quote_many(&requests, 4).await })
+                .await
+                .unwrap()
+        });
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(Result::is_err));
+    }
+
+    #[test]
+    fn with_clones_share_the_shutdown_of_their_original() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let client = JupiterSwapApiClient::new("http://127.0.0.1:9".to_string());
+        let _shared = client.clone();
+        let detached = client.clone().with_api_key("key".to_string());
+        runtime.block_on(async {
+            assert!(client.shutdown(Duration::ZERO).await);
+            assert!(detached.is_shutting_down());
+            assert!(matches!(
+                detached.quote(&QuoteRequest::default()).await,
+                Err(ClientError::ShuttingDown)
+            ));
+        });
+    }
+
+    #[test]
+    fn calls_in_flight_complete_their_nested_calls_after_shutdown() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let client = JupiterSwapApiClient::new("http://127.0.0.1:9".to_string());
+        let result = runtime.block_on(client.in_flight(async {
+            let shutdown = client.clone().with_api_key("key".to_string());
+            assert!(!shutdown.shutdown(Duration::ZERO).await);
+            client.in_flight(async { Ok(()) }).await
+        }));
+        assert!(result.is_ok());
+        assert!(client.is_shutting_down());
+    }
+
+    fn headers(name: &'static str, value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(name, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn retry_after_reads_seconds_and_http_dates() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        assert_eq!(
+            retry_after_at(&headers("retry-after", "1.5"), now),
+            Some(Duration::from_millis(1500))
+        );
+        let date = httpdate::fmt_http_date(now + Duration::from_secs(120));
+        assert_eq!(
+            retry_after_at(&headers("retry-after", &date), now),
+            Some(Duration::from_secs(120))
+        );
+        let past_date = httpdate::fmt_http_date(now - Duration::from_secs(120));
+        assert_eq!(
+            retry_after_at(&headers("retry-after", &past_date), now),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn retry_after_reads_relative_and_absolute_resets() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        assert_eq!(
+            retry_after_at(&headers("ratelimit-reset", "10"), now),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(
+            retry_after_at(&headers("x-ratelimit-reset", "1700000010"), now),
+            Some(Duration::from_secs(10))
+        );
     }
 }
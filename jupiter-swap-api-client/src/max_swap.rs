@@ -0,0 +1,126 @@
+//! Largest input amount a wallet can swap while keeping enough lamports for the transaction to land.
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_pubkey::Pubkey;
+
+use crate::{
+    payment::NATIVE_MINT,
+    quote::{QuoteRequest, QuoteResponse, SwapMode},
+    ClientError, JupiterSwapApiClient, SYSTEM_PROGRAM_ID,
+};
+
+pub use crate::transaction_verification::{associated_token_address, ASSOCIATED_TOKEN_PROGRAM_ID};
+
+const TOKEN_ACCOUNT_LENGTH: usize = 165;
+const TOKEN_ACCOUNT_AMOUNT_OFFSET: usize = 64;
+
+#[derive(Clone, Debug)]
+pub struct MaxSwapConfig {
+    /// Lamports kept for the transaction and prioritization fees
+    pub fee_reserve_lamports: u64,
+}
+
+impl Default for MaxSwapConfig {
+    fn default() -> Self {
+        Self {
+            fee_reserve_lamports: 5_000_000,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MaxSwap {
+    pub amount: u64,
+    /// Lamports the wallet must keep for fees, rent of the accounts created by the swap and its own
+    /// rent exemption
+    pub reserved_lamports: u64,
+    pub quote_response: QuoteResponse,
+}
+
+fn token_account_amount(data: &[u8]) -> Option<u64> {
+    let amount = data.get(TOKEN_ACCOUNT_AMOUNT_OFFSET..TOKEN_ACCOUNT_AMOUNT_OFFSET + 8)?;
+    Some(u64::from_le_bytes(amount.try_into().ok()?))
+}
+
+impl JupiterSwapApiClient {
+    /// Computes the largest amount of `quote_request.input_mint` `wallet` can swap into
+    /// `quote_request.output_mint`, then quotes it
+    ///
+    /// SOL inputs are reduced by the fee reserve, the rent of the temporary WSOL and output token
+    /// accounts and the wallet's own rent exemption. Token inputs swap the whole associated token
+    /// account balance provided the wallet can pay for the same reserve. Returns `None` when
+    /// nothing can be swapped.
+    ///
+    /// The amount is an input amount, so the quote is `ExactIn` whatever the `swap_mode` of
+    /// `quote_request`.
+    pub async fn max_swap_amount(
+        &self,
+        rpc_client: &RpcClient,
+        wallet: &Pubkey,
+        quote_request: &QuoteRequest,
+        config: &MaxSwapConfig,
+    ) -> Result<Option<MaxSwap>, ClientError> {
+        self.in_flight(async {
+            let input_mint = quote_request.input_mint;
+            let output_mint = quote_request.output_mint;
+            let mint_accounts = rpc_client
+                .get_multiple_accounts(&[input_mint, output_mint])
+                .await?;
+            let [Some(input_mint_account), Some(output_mint_account)] = &mint_accounts[..] else {
+                return Ok(None);
+            };
+            let input_token_account =
+                associated_token_address(wallet, &input_mint, &input_mint_account.owner);
+            let output_token_account =
+                associated_token_address(wallet, &output_mint, &output_mint_account.owner);
+            let token_accounts = rpc_client
+                .get_multiple_accounts(&[input_token_account, output_token_account])
+                .await?;
+
+            let wallet_lamports = rpc_client.get_balance(wallet).await?;
+            let token_account_rent = rpc_client
+                .get_minimum_balance_for_rent_exemption(TOKEN_ACCOUNT_LENGTH)
+                .await?;
+
+            let mut reserved_lamports = config.fee_reserve_lamports;
+            let involves_sol = input_mint == NATIVE_MINT || output_mint == NATIVE_MINT;
+            if involves_sol {
+                reserved_lamports += token_account_rent;
+            }
+            if output_mint != NATIVE_MINT && token_accounts[1].is_none() {
+                reserved_lamports += token_account_rent;
+            }
+
+            let amount = if input_mint == NATIVE_MINT {
+                // The wallet keeps a system account, which must stay rent exempt unless emptied
+                reserved_lamports += rpc_client.get_minimum_balance_for_rent_exemption(0).await?;
+                wallet_lamports.saturating_sub(reserved_lamports)
+            } else if wallet_lamports < reserved_lamports {
+                0
+            } else {
+                token_accounts[0]
+                    .as_ref()
+                    .filter(|account| account.owner != SYSTEM_PROGRAM_ID)
+                    .and_then(|account| token_account_amount(&account.data))
+                    .unwrap_or_default()
+            };
+            if amount == 0 {
+                return Ok(None);
+            }
+
+            let quote_response = self
+                .quote(&QuoteRequest {
+                    amount,
+                    swap_mode: Some(SwapMode::ExactIn),
+                    ..quote_request.clone()
+                })
+                .await?;
+            Ok(Some(MaxSwap {
+                amount,
+                reserved_lamports,
+                quote_response,
+            }))
+        })
+        .await
+    }
+}
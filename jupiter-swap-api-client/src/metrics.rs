@@ -0,0 +1,32 @@
+//! Per-request hooks to feed latencies and error rates into a metrics pipeline such as Prometheus
+//! or StatsD, see
+//! [`JupiterSwapApiClient::with_metrics_observer`](crate::JupiterSwapApiClient::with_metrics_observer).
+
+use std::time::Duration;
+
+use reqwest::StatusCode;
+
+use crate::ClientError;
+
+/// A single HTTP attempt, every retry is observed as a request of its own
+#[derive(Clone, Copy, Debug)]
+pub struct RequestInfo<'a> {
+    /// Path requested, e.g. `/quote`, without base path nor query
+    pub endpoint: &'a str,
+    pub url: &'a str,
+    /// 0 for the first attempt of a call
+    pub retry: u32,
+}
+
+/// Called synchronously on the task sending the request, implementations should only update
+/// counters and histograms
+pub trait MetricsObserver: Send + Sync {
+    fn on_request_start(&self, _request: &RequestInfo<'_>) {}
+
+    /// Successful response, decoded
+    fn on_response(&self, _request: &RequestInfo<'_>, _status: StatusCode, _latency: Duration) {}
+
+    /// Failed attempt, the status of non-success responses is available through
+    /// [`ClientError::status`]
+    fn on_error(&self, _request: &RequestInfo<'_>, _error: &ClientError, _latency: Duration) {}
+}
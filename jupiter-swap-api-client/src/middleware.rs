@@ -0,0 +1,32 @@
+//! Hooks around every HTTP attempt to add authentication, inject headers or log traffic without
+//! wrapping the client methods, see
+//! [`JupiterSwapApiClient::with_middleware`](crate::JupiterSwapApiClient::with_middleware).
+
+use reqwest::{RequestBuilder, Response};
+
+use crate::metrics::RequestInfo;
+
+/// Middlewares run in registration order, after the client set its own headers
+pub trait Middleware: Send + Sync {
+    fn on_request(
+        &self,
+        _request_info: &RequestInfo<'_>,
+        request: RequestBuilder,
+    ) -> RequestBuilder {
+        request
+    }
+
+    /// Response as received, before its status is checked and its body read
+    fn on_response(&self, _request_info: &RequestInfo<'_>, _response: &Response) {}
+}
+
+/// Closures rewrite the outgoing requests
+impl<F: Fn(RequestBuilder) -> RequestBuilder + Send + Sync> Middleware for F {
+    fn on_request(
+        &self,
+        _request_info: &RequestInfo<'_>,
+        request: RequestBuilder,
+    ) -> RequestBuilder {
+        self(request)
+    }
+}
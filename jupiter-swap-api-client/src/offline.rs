@@ -0,0 +1,184 @@
+//! Transaction assembly from chain data captured ahead of time, so air-gapped signing setups can
+//! assemble and inspect swaps on disconnected machines, see [`OfflineSnapshot`].
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use solana_hash::Hash;
+use solana_message::{AddressLookupTableAccount, VersionedMessage};
+use solana_pubkey::Pubkey;
+
+use crate::{
+    blockhash::{BlockhashInfo, FixedBlockhashProvider},
+    quote::QuoteResponse,
+    serde_helpers::{comma_separated, field_as_string, hash_as_string},
+    swap::{base64_serialize_deserialize, SwapInstructionsResponse},
+    token_extensions::{
+        parse_mint_extensions, MintExtension, TokenExtensionReport, TransferTaxReport,
+    },
+    ClientError,
+};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct OfflineLookupTable {
+    #[serde(with = "field_as_string")]
+    pub key: Pubkey,
+    #[serde(with = "comma_separated")]
+    pub addresses: Vec<Pubkey>,
+}
+
+/// Raw mint account, its extensions are parsed on use
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct OfflineMintAccount {
+    #[serde(with = "field_as_string")]
+    pub mint: Pubkey,
+    /// Token program owning the mint
+    #[serde(with = "field_as_string")]
+    pub owner: Pubkey,
+    #[serde(with = "base64_serialize_deserialize")]
+    pub data: Vec<u8>,
+}
+
+/// Chain data needed to assemble and inspect swaps without RPC access
+///
+/// Captured on a connected machine, typically with `OfflineSnapshot::capture`, then carried to the
+/// signing machine as JSON.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct OfflineSnapshot {
+    /// Recent blockhash or durable nonce of the assembled transactions
+    #[serde(with = "hash_as_string")]
+    pub blockhash: Hash,
+    pub last_valid_block_height: u64,
+    /// Epoch at capture time, selects the transfer fees in effect
+    pub epoch: u64,
+    pub address_lookup_tables: Vec<OfflineLookupTable>,
+    pub mint_accounts: Vec<OfflineMintAccount>,
+}
+
+impl OfflineSnapshot {
+    pub fn blockhash_provider(&self) -> FixedBlockhashProvider {
+        FixedBlockhashProvider {
+            blockhash_info: BlockhashInfo {
+                blockhash: self.blockhash,
+                last_valid_block_height: self.last_valid_block_height,
+            },
+        }
+    }
+
+    /// Snapshotted tables among `addresses`, missing tables are skipped like
+    /// `fetch_address_lookup_tables` does
+    pub fn address_lookup_table_accounts(
+        &self,
+        addresses: &[Pubkey],
+    ) -> Vec<AddressLookupTableAccount> {
+        addresses
+            .iter()
+            .filter_map(|address| {
+                self.address_lookup_tables
+                    .iter()
+                    .find(|table| table.key == *address)
+            })
+            .map(|table| AddressLookupTableAccount {
+                key: table.key,
+                addresses: table.addresses.clone(),
+            })
+            .collect()
+    }
+
+    pub fn mint_extensions(&self) -> HashMap<Pubkey, Vec<MintExtension>> {
+        self.mint_accounts
+            .iter()
+            .map(|account| {
+                (
+                    account.mint,
+                    parse_mint_extensions(&account.owner, &account.data),
+                )
+            })
+            .collect()
+    }
+
+    /// Same as `token_extensions::check_quote`, from the snapshotted mints
+    pub fn check_quote(&self, quote_response: &QuoteResponse) -> TokenExtensionReport {
+        TokenExtensionReport::for_quote(quote_response, &self.mint_extensions())
+    }
+
+    /// Same as `token_extensions::check_transfer_tax`, at the snapshotted epoch
+    pub fn check_transfer_tax(&self, quote_response: &QuoteResponse) -> TransferTaxReport {
+        TransferTaxReport::for_quote(quote_response, &self.mint_extensions(), self.epoch)
+    }
+}
+
+impl SwapInstructionsResponse {
+    /// Same as [`Self::build_message`], with the lookup tables and blockhash of `snapshot`
+    pub async fn build_offline_message(
+        &self,
+        payer: &Pubkey,
+        snapshot: &OfflineSnapshot,
+    ) -> Result<(VersionedMessage, BlockhashInfo), ClientError> {
+        self.build_message(
+            payer,
+            &snapshot.address_lookup_table_accounts(&self.address_lookup_table_addresses),
+            &snapshot.blockhash_provider(),
+        )
+        .await
+    }
+}
+
+#[cfg(feature = "rpc")]
+mod rpc {
+    use solana_client::nonblocking::rpc_client::RpcClient;
+
+    use super::*;
+    use crate::{token_extensions::quote_mints, transaction_builder::fetch_address_lookup_tables};
+
+    impl OfflineSnapshot {
+        /// Captures the latest blockhash, the lookup tables of `swap_instructions` and the mints
+        /// traded through by `quote_response`
+        pub async fn capture(
+            rpc_client: &RpcClient,
+            quote_response: &QuoteResponse,
+            swap_instructions: &SwapInstructionsResponse,
+        ) -> Result<Self, ClientError> {
+            let (blockhash, last_valid_block_height) = rpc_client
+                .get_latest_blockhash_with_commitment(rpc_client.commitment())
+                .await?;
+            let epoch = rpc_client.get_epoch_info().await?.epoch;
+            let address_lookup_tables = fetch_address_lookup_tables(
+                rpc_client,
+                &swap_instructions.address_lookup_table_addresses,
+            )
+            .await?
+            .into_iter()
+            .map(|table| OfflineLookupTable {
+                key: table.key,
+                addresses: table.addresses,
+            })
+            .collect();
+            let mints = quote_mints(quote_response);
+            let mint_accounts = rpc_client
+                .get_multiple_accounts(&mints)
+                .await?
+                .into_iter()
+                .zip(mints)
+                .filter_map(|(account, mint)| {
+                    let account = account?;
+                    Some(OfflineMintAccount {
+                        mint,
+                        owner: account.owner,
+                        data: account.data,
+                    })
+                })
+                .collect();
+            Ok(Self {
+                blockhash,
+                last_valid_block_height,
+                epoch,
+                address_lookup_tables,
+                mint_accounts,
+            })
+        }
+    }
+}
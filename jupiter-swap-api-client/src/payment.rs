@@ -0,0 +1,277 @@
+//! Payment flow paying an exact output amount, e.g. a merchant invoice, from a bounded input amount.
+
+use solana_pubkey::Pubkey;
+use solana_transaction::versioned::VersionedTransaction;
+
+use crate::{
+    quote::{QuoteRequest, QuoteResponse, SwapMode},
+    swap::{SwapRequest, SwapResponse},
+    transaction_config::TransactionConfig,
+    transaction_verification::{other_amount_threshold, swap_transaction_other_amount_threshold},
+    ClientError, JupiterSwapApiClient,
+};
+
+pub use crate::NATIVE_MINT;
+
+#[derive(Clone, Debug)]
+pub struct ExactOutPaymentRequest {
+    /// `amount` is the exact output amount to pay, `swap_mode` is forced to [`SwapMode::ExactOut`]
+    pub quote_request: QuoteRequest,
+    pub user_public_key: Pubkey,
+    /// Extra slippage authorized on top of the quote's, in bps, to absorb price movements between
+    /// the quote and the swap
+    pub input_buffer_bps: u16,
+    pub config: TransactionConfig,
+}
+
+/// A built ExactOut payment, ready to be signed and sent
+#[derive(Clone, Debug)]
+pub struct ExactOutPayment {
+    pub user_public_key: Pubkey,
+    pub quote_response: QuoteResponse,
+    pub swap_response: SwapResponse,
+    /// Input amount the route instruction of the swap transaction spends at most, buffer included
+    pub max_input_amount: u64,
+    /// The unspent wrapped SOL is returned to the user when the swap closes its WSOL account
+    pub wraps_sol: bool,
+}
+
+/// Input actually spent by a confirmed [`ExactOutPayment`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExactOutSettlement {
+    pub max_input_amount: u64,
+    pub quoted_input_amount: u64,
+    /// Input amount spent by the user, excluding transaction fees and account rent
+    pub input_spent: u64,
+    /// Lamports wrapped but not spent, returned to the user when the WSOL account is closed
+    pub wsol_refund: Option<u64>,
+}
+
+impl ExactOutSettlement {
+    /// Authorized input left unspent
+    pub fn unspent(&self) -> u64 {
+        self.max_input_amount.saturating_sub(self.input_spent)
+    }
+}
+
+/// Raises the maximum input of an ExactOut quote by `input_buffer_bps`
+///
+/// The route instruction derives its maximum input from the quoted input amount and the quote's
+/// `slippage_bps`, so the buffer widens the slippage rather than `other_amount_threshold` alone.
+fn apply_input_buffer(quote_response: &mut QuoteResponse, input_buffer_bps: u16) {
+    let slippage_bps = quote_response.slippage_bps.saturating_add(input_buffer_bps);
+    quote_response.slippage_bps = slippage_bps;
+    quote_response.other_amount_threshold =
+        other_amount_threshold(SwapMode::ExactOut, quote_response.in_amount, slippage_bps);
+    // A quote parsed with `from_raw` is sent to `/swap` verbatim
+    if let Some(raw) = quote_response
+        .raw
+        .as_mut()
+        .and_then(serde_json::Value::as_object_mut)
+    {
+        raw.insert("slippageBps".to_string(), slippage_bps.into());
+        raw.insert(
+            "otherAmountThreshold".to_string(),
+            quote_response.other_amount_threshold.to_string().into(),
+        );
+    }
+}
+
+impl ExactOutPayment {
+    /// Reads the maximum input from the route instruction of the swap transaction, which is what
+    /// the program enforces even when the API adjusted the slippage of the quote
+    fn new(
+        payment_request: &ExactOutPaymentRequest,
+        quote_response: QuoteResponse,
+        swap_response: SwapResponse,
+    ) -> Result<Self, ClientError> {
+        let transaction: VersionedTransaction =
+            bincode::deserialize(&swap_response.swap_transaction)?;
+        let max_input_amount = swap_transaction_other_amount_threshold(&transaction)
+            .ok_or(ClientError::UnrecognizedSwapTransaction)?;
+        Ok(Self {
+            user_public_key: payment_request.user_public_key,
+            max_input_amount,
+            wraps_sol: quote_response.input_mint == NATIVE_MINT
+                && payment_request.config.wrap_and_unwrap_sol,
+            quote_response,
+            swap_response,
+        })
+    }
+}
+
+impl JupiterSwapApiClient {
+    /// Quotes the exact output amount, widens its slippage by the input buffer and builds the
+    /// swap transaction
+    pub async fn pay_exact_out(
+        &self,
+        payment_request: &ExactOutPaymentRequest,
+    ) -> Result<ExactOutPayment, ClientError> {
+        self.in_flight(async {
+            let mut quote_request = payment_request.quote_request.clone();
+            quote_request.swap_mode = Some(SwapMode::ExactOut);
+            let mut quote_response = self.quote(&quote_request).await?;
+            apply_input_buffer(&mut quote_response, payment_request.input_buffer_bps);
+
+            let swap_request = SwapRequest {
+                user_public_key: payment_request.user_public_key,
+                quote_response: quote_response.clone(),
+                config: payment_request.config.clone(),
+            };
+            let swap_response = self.swap(&swap_request, None).await?;
+            ExactOutPayment::new(payment_request, quote_response, swap_response)
+        })
+        .await
+    }
+}
+
+#[cfg(feature = "rpc")]
+mod rpc {
+    use solana_client::nonblocking::rpc_client::RpcClient;
+    use solana_signature::Signature;
+
+    use super::*;
+    use crate::receipt::{to_amount, ConfirmedTransaction};
+
+    impl ExactOutPayment {
+        /// Fetches the confirmed payment transaction and computes the input it actually spent,
+        /// `None` if the transaction has no status metadata
+        pub async fn settle(
+            &self,
+            rpc_client: &RpcClient,
+            signature: &Signature,
+        ) -> Result<Option<ExactOutSettlement>, ClientError> {
+            let Some(transaction) = ConfirmedTransaction::fetch(rpc_client, signature).await?
+            else {
+                return Ok(None);
+            };
+            let input_spent = to_amount(
+                -transaction.balance_change(&self.user_public_key, &self.quote_response.input_mint),
+            );
+            Ok(Some(ExactOutSettlement {
+                max_input_amount: self.max_input_amount,
+                quoted_input_amount: self.quote_response.in_amount,
+                input_spent,
+                wsol_refund: self
+                    .wraps_sol
+                    .then(|| self.max_input_amount.saturating_sub(input_spent)),
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_hash::Hash;
+    use solana_instruction::{AccountMeta, Instruction};
+    use solana_message::{Message, VersionedMessage};
+    use solana_signature::Signature;
+
+    use super::*;
+    use crate::transaction_verification::JUPITER_V6_PROGRAM_ID;
+
+    const EXACT_OUT_ROUTE_DISCRIMINATOR: [u8; 8] = [208, 51, 239, 151, 123, 43, 237, 92];
+
+    fn exact_out_quote(in_amount: u64, slippage_bps: u16) -> QuoteResponse {
+        serde_json::from_value(serde_json::json!({
+            "inputMint": NATIVE_MINT.to_string(),
+            "inAmount": in_amount.to_string(),
+            "outputMint": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+            "outAmount": "1000000",
+            "otherAmountThreshold": in_amount.to_string(),
+            "swapMode": "ExactOut",
+            "slippageBps": slippage_bps,
+            "platformFee": null,
+            "priceImpactPct": "0",
+            "routePlan": [],
+        }))
+        .unwrap()
+    }
+
+    /// `/swap` response whose route instruction quotes `quoted_in_amount` with `slippage_bps`
+    fn exact_out_swap_response(
+        user: Pubkey,
+        quoted_in_amount: u64,
+        slippage_bps: u16,
+    ) -> SwapResponse {
+        let mut data = EXACT_OUT_ROUTE_DISCRIMINATOR.to_vec();
+        // Empty route plan, then out amount, quoted in amount, slippage and platform fee
+        data.extend(0u32.to_le_bytes());
+        data.extend(1_000_000u64.to_le_bytes());
+        data.extend(quoted_in_amount.to_le_bytes());
+        data.extend(slippage_bps.to_le_bytes());
+        data.push(0);
+        let mut accounts = vec![AccountMeta::new_readonly(Pubkey::new_unique(), false); 9];
+        accounts[1] = AccountMeta::new_readonly(user, true);
+        let route = Instruction::new_with_bytes(JUPITER_V6_PROGRAM_ID, &data, accounts);
+        let transaction = VersionedTransaction {
+            signatures: vec![Signature::default()],
+            message: VersionedMessage::Legacy(Message::new_with_blockhash(
+                &[route],
+                Some(&user),
+                &Hash::default(),
+            )),
+        };
+        SwapResponse {
+            swap_transaction: bincode::serialize(&transaction).unwrap(),
+            last_valid_block_height: 0,
+            prioritization_fee_lamports: 0,
+            compute_unit_limit: 0,
+            prioritization_type: None,
+            dynamic_slippage_report: None,
+            simulation_error: None,
+        }
+    }
+
+    fn payment_request(user: Pubkey, input_buffer_bps: u16) -> ExactOutPaymentRequest {
+        ExactOutPaymentRequest {
+            quote_request: QuoteRequest::default(),
+            user_public_key: user,
+            input_buffer_bps,
+            config: TransactionConfig::default(),
+        }
+    }
+
+    #[test]
+    fn input_buffer_widens_the_slippage() {
+        let mut quote_response = exact_out_quote(1_000_000, 50);
+        apply_input_buffer(&mut quote_response, 100);
+        assert_eq!(quote_response.slippage_bps, 150);
+        assert_eq!(quote_response.other_amount_threshold, 1_015_000);
+    }
+
+    #[test]
+    fn input_buffer_is_applied_to_raw_quotes() {
+        let mut quote_response =
+            QuoteResponse::from_raw(serde_json::to_value(exact_out_quote(1_000_000, 50)).unwrap())
+                .unwrap();
+        apply_input_buffer(&mut quote_response, 100);
+        let raw = quote_response.raw.unwrap();
+        assert_eq!(raw["slippageBps"], 150);
+        assert_eq!(raw["otherAmountThreshold"], "1015000");
+    }
+
+    #[test]
+    fn max_input_amount_is_read_from_the_route_instruction() {
+        let user = Pubkey::new_unique();
+        let mut quote_response = exact_out_quote(1_000_000, 50);
+        apply_input_buffer(&mut quote_response, 100);
+
+        let swap_response = exact_out_swap_response(user, 1_000_000, quote_response.slippage_bps);
+        let payment = ExactOutPayment::new(
+            &payment_request(user, 100),
+            quote_response.clone(),
+            swap_response,
+        )
+        .unwrap();
+        assert_eq!(payment.max_input_amount, 1_015_000);
+        assert!(payment.wraps_sol);
+
+        // The transaction is authoritative when the API builds it with another slippage
+        let swap_response = exact_out_swap_response(user, 1_000_000, 300);
+        let payment =
+            ExactOutPayment::new(&payment_request(user, 100), quote_response, swap_response)
+                .unwrap();
+        assert_eq!(payment.max_input_amount, 1_030_000);
+    }
+}
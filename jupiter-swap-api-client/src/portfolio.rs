@@ -0,0 +1,395 @@
+//! Rebalancing of a wallet towards target portfolio weights with as few swaps as possible.
+
+use std::{collections::HashMap, str::FromStr};
+
+use futures::future::join_all;
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+
+use crate::{
+    quote::{QuoteRequest, QuoteResponse},
+    solana_types::Pubkey,
+    ultra::{UltraBalance, ULTRA_SOL_BALANCE_KEY},
+    ClientError, JupiterSwapApiClient, NATIVE_MINT,
+};
+
+#[derive(Clone, Debug)]
+pub struct PortfolioTarget {
+    pub mint: Pubkey,
+    /// Relative weight, weights are normalized by their sum
+    pub weight: Decimal,
+    /// Slippage of the swaps into or out of the mint, the tightest applies when both mints set one
+    pub slippage_bps: Option<u16>,
+}
+
+#[derive(Clone, Debug)]
+pub struct RebalanceConfig {
+    /// Mint every holding is valued in, e.g. USDC
+    pub valuation_mint: Pubkey,
+    /// Slippage of the swaps between mints with no slippage of their own
+    pub slippage_bps: u16,
+    /// Trades worth less than this amount of the valuation mint are skipped
+    pub min_trade_value: u64,
+    /// Lamports of native SOL left out of the portfolio, so that it is never sold below what the
+    /// wallet needs for transaction fees and rent
+    pub sol_reserve_lamports: u64,
+}
+
+/// A held token and its value in the valuation mint
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Holding {
+    pub mint: Pubkey,
+    /// Amount held, less the SOL reserve for native SOL
+    pub amount: u64,
+    pub value: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct PlannedSwap {
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub amount: u64,
+    /// Value of `amount` in the valuation mint
+    pub value: u64,
+    pub quote_response: QuoteResponse,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct RebalancePlan {
+    pub holdings: Vec<Holding>,
+    /// Balances that could not be valued, as `(mint, amount, error)`, they are left out of the
+    /// plan
+    pub unvalued: Vec<(Pubkey, u64, ClientError)>,
+    pub total_value: u64,
+    pub swaps: Vec<PlannedSwap>,
+    /// Swaps that could not be quoted, as `(input mint, output mint, amount, error)`
+    pub failures: Vec<(Pubkey, Pubkey, u64, ClientError)>,
+}
+
+/// Raw balances by mint from [`JupiterSwapApiClient::ultra_balances`], native SOL under
+/// [`NATIVE_MINT`], frozen balances are skipped
+pub fn balances_from_ultra(balances: &HashMap<String, UltraBalance>) -> HashMap<Pubkey, u64> {
+    balances
+        .iter()
+        .filter(|(_, balance)| !balance.is_frozen)
+        .filter_map(|(key, balance)| {
+            let mint = if key == ULTRA_SOL_BALANCE_KEY {
+                NATIVE_MINT
+            } else {
+                Pubkey::from_str(key).ok()?
+            };
+            Some((mint, balance.amount))
+        })
+        .collect()
+}
+
+impl JupiterSwapApiClient {
+    /// Values `balances` through quotes into the valuation mint and plans the swaps moving the
+    /// portfolio towards `targets`
+    ///
+    /// Held mints without a target are sold entirely, native SOL down to
+    /// [`RebalanceConfig::sol_reserve_lamports`]. Every swap moves value from an overweight
+    /// mint straight to an underweight one, so at most one swap less than the number of mints to
+    /// adjust is planned.
+    pub async fn plan_rebalance(
+        &self,
+        balances: &HashMap<Pubkey, u64>,
+        targets: &[PortfolioTarget],
+        config: &RebalanceConfig,
+    ) -> Result<RebalancePlan, ClientError> {
+        self.in_flight(async {
+            let (holdings, unvalued) = self.value_holdings(balances, config).await;
+            let total_value = holdings.iter().map(|holding| holding.value).sum::<u64>();
+            let weight_sum = targets.iter().map(|target| target.weight).sum::<Decimal>();
+            if total_value == 0 || weight_sum <= Decimal::ZERO {
+                return Ok(RebalancePlan {
+                    holdings,
+                    unvalued,
+                    total_value,
+                    ..Default::default()
+                });
+            }
+
+            let target_value = |mint: &Pubkey| {
+                targets
+                    .iter()
+                    .filter(|target| target.mint == *mint)
+                    .map(|target| Decimal::from(total_value) * target.weight / weight_sum)
+                    .sum::<Decimal>()
+                    .to_u64()
+                    .unwrap_or_default()
+            };
+            let mut surpluses = holdings
+                .iter()
+                .filter_map(|holding| {
+                    let surplus = holding.value.checked_sub(target_value(&holding.mint))?;
+                    (surplus > 0).then_some((holding, surplus))
+                })
+                .collect::<Vec<_>>();
+            let mut target_mints = targets.iter().map(|target| target.mint).collect::<Vec<_>>();
+            target_mints.sort();
+            target_mints.dedup();
+            let mut deficits = target_mints
+                .into_iter()
+                .filter_map(|mint| {
+                    let value = holdings
+                        .iter()
+                        .find(|holding| holding.mint == mint)
+                        .map_or(0, |holding| holding.value);
+                    let deficit = target_value(&mint).checked_sub(value)?;
+                    (deficit > 0).then_some((mint, deficit))
+                })
+                .collect::<Vec<_>>();
+            surpluses.sort_by_key(|(_, surplus)| std::cmp::Reverse(*surplus));
+            deficits.sort_by_key(|(_, deficit)| std::cmp::Reverse(*deficit));
+
+            // Largest surplus against largest deficit until either side runs out
+            let mut trades = Vec::new();
+            let (mut surplus_index, mut deficit_index) = (0, 0);
+            while let (Some((holding, surplus)), Some((output_mint, deficit))) = (
+                surpluses.get_mut(surplus_index),
+                deficits.get_mut(deficit_index),
+            ) {
+                let value = (*surplus).min(*deficit);
+                if value >= config.min_trade_value {
+                    let amount =
+                        u128::from(holding.amount) * u128::from(value) / u128::from(holding.value);
+                    trades.push((
+                        holding.mint,
+                        *output_mint,
+                        u64::try_from(amount).unwrap_or(holding.amount),
+                        value,
+                    ));
+                }
+                *surplus -= value;
+                *deficit -= value;
+                if *surplus == 0 {
+                    surplus_index += 1;
+                }
+                if *deficit == 0 {
+                    deficit_index += 1;
+                }
+            }
+
+            let slippage_bps = |input_mint: &Pubkey, output_mint: &Pubkey| {
+                targets
+                    .iter()
+                    .filter(|target| target.mint == *input_mint || target.mint == *output_mint)
+                    .filter_map(|target| target.slippage_bps)
+                    .min()
+                    .unwrap_or(config.slippage_bps)
+            };
+            let quote_responses =
+                join_all(trades.iter().map(|(input_mint, output_mint, amount, _)| {
+                    let quote_request = QuoteRequest {
+                        input_mint: *input_mint,
+                        output_mint: *output_mint,
+                        amount: *amount,
+                        slippage_bps: Some(slippage_bps(input_mint, output_mint)),
+                        ..QuoteRequest::default()
+                    };
+                    async move { self.quote(&quote_request).await }
+                }))
+                .await;
+
+            let mut plan = RebalancePlan {
+                holdings,
+                unvalued,
+                total_value,
+                ..Default::default()
+            };
+            for ((input_mint, output_mint, amount, value), quote_response) in
+                trades.into_iter().zip(quote_responses)
+            {
+                match quote_response {
+                    Ok(quote_response) => plan.swaps.push(PlannedSwap {
+                        input_mint,
+                        output_mint,
+                        amount,
+                        value,
+                        quote_response,
+                    }),
+                    Err(error) => plan.failures.push((input_mint, output_mint, amount, error)),
+                }
+            }
+            Ok(plan)
+        })
+        .await
+    }
+
+    /// Values every non-zero balance, less the SOL reserve, by quoting it into the valuation mint,
+    /// along with the balances that could not be quoted
+    async fn value_holdings(
+        &self,
+        balances: &HashMap<Pubkey, u64>,
+        config: &RebalanceConfig,
+    ) -> (Vec<Holding>, Vec<(Pubkey, u64, ClientError)>) {
+        let tradable_balances = balances
+            .iter()
+            .map(|(mint, amount)| match *mint {
+                NATIVE_MINT => (*mint, amount.saturating_sub(config.sol_reserve_lamports)),
+                _ => (*mint, *amount),
+            })
+            .filter(|(_, amount)| *amount > 0);
+        let values = join_all(tradable_balances.map(|(mint, amount)| async move {
+            let value = if mint == config.valuation_mint {
+                Ok(amount)
+            } else {
+                self.quote(&QuoteRequest {
+                    input_mint: mint,
+                    output_mint: config.valuation_mint,
+                    amount,
+                    ..QuoteRequest::default()
+                })
+                .await
+                .map(|quote_response| quote_response.out_amount)
+            };
+            (mint, amount, value)
+        }))
+        .await;
+
+        let mut holdings = Vec::new();
+        let mut unvalued = Vec::new();
+        for (mint, amount, value) in values {
+            match value {
+                Ok(value) => holdings.push(Holding {
+                    mint,
+                    amount,
+                    value,
+                }),
+                Err(error) => unvalued.push((mint, amount, error)),
+            }
+        }
+        holdings.sort_by_key(|holding| std::cmp::Reverse(holding.value));
+        (holdings, unvalued)
+    }
+}
+
+#[cfg(feature = "rpc")]
+mod rpc {
+    use solana_client::nonblocking::rpc_client::RpcClient;
+    use solana_signature::Signature;
+
+    use super::*;
+    use crate::{
+        execute::SendOptions, receipt::SwapReceipt, signer::AsyncSigner, swap::SwapRequest,
+        transaction_config::TransactionConfig,
+    };
+
+    /// Outcome of a planned swap
+    #[derive(Clone, Debug)]
+    pub struct RebalanceOutcome {
+        pub planned_swap: PlannedSwap,
+        pub signature: Option<Signature>,
+        /// Receipt of the landed swap, without executed amounts when its transaction could not be
+        /// fetched
+        pub receipt: Option<SwapReceipt>,
+        pub error: Option<ClientError>,
+    }
+
+    impl RebalanceOutcome {
+        pub fn is_success(&self) -> bool {
+            self.error.is_none() && self.receipt.as_ref().is_none_or(|receipt| receipt.success)
+        }
+    }
+
+    /// Consolidated execution report of a [`RebalancePlan`]
+    #[derive(Clone, Debug, Default)]
+    pub struct RebalanceReport {
+        pub outcomes: Vec<RebalanceOutcome>,
+    }
+
+    impl RebalanceReport {
+        pub fn succeeded(&self) -> impl Iterator<Item = &RebalanceOutcome> {
+            self.outcomes.iter().filter(|outcome| outcome.is_success())
+        }
+
+        pub fn failed(&self) -> impl Iterator<Item = &RebalanceOutcome> {
+            self.outcomes.iter().filter(|outcome| !outcome.is_success())
+        }
+
+        /// Realized change of every mint over the successful swaps with executed amounts, negative
+        /// when spent
+        pub fn net_changes(&self) -> HashMap<Pubkey, i128> {
+            let mut net_changes = HashMap::new();
+            for receipt in self
+                .succeeded()
+                .filter_map(|outcome| outcome.receipt.as_ref())
+            {
+                *net_changes.entry(receipt.input_mint).or_default() -=
+                    i128::from(receipt.in_amount.unwrap_or_default());
+                *net_changes.entry(receipt.output_mint).or_default() +=
+                    i128::from(receipt.out_amount.unwrap_or_default());
+            }
+            net_changes
+        }
+    }
+
+    impl JupiterSwapApiClient {
+        /// Executes the swaps of `plan` one after the other through [`Self::execute_swap`], signed
+        /// by `signer`, a failed swap does not stop the following ones
+        pub async fn execute_rebalance(
+            &self,
+            plan: &RebalancePlan,
+            rpc_client: &RpcClient,
+            signer: &dyn AsyncSigner,
+            config: &TransactionConfig,
+            send_options: &SendOptions,
+        ) -> RebalanceReport {
+            let mut report = RebalanceReport::default();
+            let in_flight = self
+                .in_flight(async {
+                    for planned_swap in &plan.swaps {
+                        let mut outcome = RebalanceOutcome {
+                            planned_swap: planned_swap.clone(),
+                            signature: None,
+                            receipt: None,
+                            error: None,
+                        };
+                        let swap_request = SwapRequest {
+                            user_public_key: signer.pubkey(),
+                            quote_response: planned_swap.quote_response.clone(),
+                            config: config.clone(),
+                        };
+                        match self
+                            .execute_swap(&swap_request, signer, rpc_client, send_options)
+                            .await
+                        {
+                            Ok(receipt) => {
+                                outcome.signature = Some(receipt.signature);
+                                outcome.receipt = Some(receipt);
+                            }
+                            Err(error) => {
+                                outcome.signature = match &error {
+                                    ClientError::TransactionFailed { signature, .. }
+                                    | ClientError::TransactionExpired { signature, .. } => {
+                                        Some(*signature)
+                                    }
+                                    _ => None,
+                                };
+                                outcome.error = Some(error);
+                            }
+                        }
+                        report.outcomes.push(outcome);
+                    }
+                    Ok(())
+                })
+                .await;
+            if let Err(error) = in_flight {
+                report.outcomes = plan
+                    .swaps
+                    .iter()
+                    .map(|planned_swap| RebalanceOutcome {
+                        planned_swap: planned_swap.clone(),
+                        signature: None,
+                        receipt: None,
+                        error: Some(error.clone()),
+                    })
+                    .collect();
+            }
+            report
+        }
+    }
+}
+
+#[cfg(feature = "rpc")]
+pub use rpc::{RebalanceOutcome, RebalanceReport};
@@ -0,0 +1,201 @@
+//! Named priority fee profiles whose compute unit prices are calibrated from recent network fees.
+
+use std::{
+    sync::{PoisonError, RwLock},
+    time::Instant,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::transaction_config::{ComputeUnitPriceMicroLamports, TransactionConfig};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Copy, Clone, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum FeeProfile {
+    Economy,
+    Standard,
+    Urgent,
+}
+
+impl FeeProfile {
+    pub const ALL: [FeeProfile; 3] = [Self::Economy, Self::Standard, Self::Urgent];
+
+    /// Percentile of recent prioritization fees the profile is calibrated to
+    pub fn percentile(&self) -> u8 {
+        match self {
+            Self::Economy => 25,
+            Self::Standard => 50,
+            Self::Urgent => 90,
+        }
+    }
+
+    /// Compute unit price used until the first calibration
+    pub fn default_micro_lamports(&self) -> u64 {
+        match self {
+            Self::Economy => 1_000,
+            Self::Standard => 10_000,
+            Self::Urgent => 100_000,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct FeeProfileValues {
+    pub economy: u64,
+    pub standard: u64,
+    pub urgent: u64,
+}
+
+impl Default for FeeProfileValues {
+    fn default() -> Self {
+        Self {
+            economy: FeeProfile::Economy.default_micro_lamports(),
+            standard: FeeProfile::Standard.default_micro_lamports(),
+            urgent: FeeProfile::Urgent.default_micro_lamports(),
+        }
+    }
+}
+
+impl FeeProfileValues {
+    pub fn get(&self, profile: FeeProfile) -> u64 {
+        match profile {
+            FeeProfile::Economy => self.economy,
+            FeeProfile::Standard => self.standard,
+            FeeProfile::Urgent => self.urgent,
+        }
+    }
+}
+
+/// Current compute unit price, in micro lamports, of every [`FeeProfile`]
+///
+/// Values start at [`FeeProfile::default_micro_lamports`] and follow the recent prioritization fees
+/// passed to [`FeeProfiles::calibrate_from_fees`], each value being floored at `min_micro_lamports`
+/// and capped at `max_micro_lamports`.
+#[derive(Debug)]
+pub struct FeeProfiles {
+    values: RwLock<(FeeProfileValues, Option<Instant>)>,
+    pub min_micro_lamports: u64,
+    pub max_micro_lamports: u64,
+}
+
+impl Default for FeeProfiles {
+    fn default() -> Self {
+        Self::new(FeeProfileValues::default())
+    }
+}
+
+impl FeeProfiles {
+    pub fn new(initial_values: FeeProfileValues) -> Self {
+        Self {
+            values: RwLock::new((initial_values, None)),
+            min_micro_lamports: 0,
+            max_micro_lamports: u64::MAX,
+        }
+    }
+
+    pub fn values(&self) -> FeeProfileValues {
+        self.values.read().unwrap_or_else(PoisonError::into_inner).0
+    }
+
+    pub fn micro_lamports(&self, profile: FeeProfile) -> u64 {
+        self.values().get(profile)
+    }
+
+    /// When the values were last calibrated, `None` if they never were
+    pub fn calibrated_at(&self) -> Option<Instant> {
+        self.values.read().unwrap_or_else(PoisonError::into_inner).1
+    }
+
+    /// Recomputes every profile from recent prioritization fees (micro lamports per compute unit),
+    /// an empty sample leaves the values untouched
+    pub fn calibrate_from_fees(&self, recent_fees: &[u64]) {
+        if recent_fees.is_empty() {
+            return;
+        }
+        let mut recent_fees = recent_fees.to_vec();
+        recent_fees.sort_unstable();
+        let at_percentile = |profile: FeeProfile| {
+            let index = (recent_fees.len() * usize::from(profile.percentile())).div_ceil(100);
+            recent_fees[index.saturating_sub(1)]
+                .clamp(self.min_micro_lamports, self.max_micro_lamports)
+        };
+        let values = FeeProfileValues {
+            economy: at_percentile(FeeProfile::Economy),
+            standard: at_percentile(FeeProfile::Standard),
+            urgent: at_percentile(FeeProfile::Urgent),
+        };
+        *self.values.write().unwrap_or_else(PoisonError::into_inner) =
+            (values, Some(Instant::now()));
+    }
+}
+
+impl TransactionConfig {
+    /// Sets the compute unit price from the current value of `fee_profile`, or of
+    /// [`TransactionConfig::fee_profile`] when `None`
+    ///
+    /// Does nothing when no profile is selected or a prioritization fee is already set explicitly.
+    pub fn apply_fee_profile(
+        &mut self,
+        fee_profiles: &FeeProfiles,
+        fee_profile: Option<FeeProfile>,
+    ) {
+        let Some(fee_profile) = fee_profile.or(self.fee_profile) else {
+            return;
+        };
+        if self.compute_unit_price_micro_lamports.is_some()
+            || self.prioritization_fee_lamports.is_some()
+        {
+            return;
+        }
+        self.compute_unit_price_micro_lamports = Some(
+            ComputeUnitPriceMicroLamports::MicroLamports(fee_profiles.micro_lamports(fee_profile)),
+        );
+    }
+}
+
+#[cfg(feature = "rpc")]
+mod rpc {
+    use std::{sync::Arc, time::Duration};
+
+    use solana_client::nonblocking::rpc_client::RpcClient;
+    use solana_pubkey::Pubkey;
+    use tokio::task::JoinHandle;
+
+    use super::FeeProfiles;
+    use crate::ClientError;
+
+    impl FeeProfiles {
+        /// Calibrates from `getRecentPrioritizationFees` for transactions write locking `accounts`
+        pub async fn calibrate(
+            &self,
+            rpc_client: &RpcClient,
+            accounts: &[Pubkey],
+        ) -> Result<(), ClientError> {
+            let recent_fees = rpc_client
+                .get_recent_prioritization_fees(accounts)
+                .await?
+                .into_iter()
+                .map(|fee| fee.prioritization_fee)
+                .collect::<Vec<_>>();
+            self.calibrate_from_fees(&recent_fees);
+            Ok(())
+        }
+
+        /// Calibrates every `interval` until the returned task is aborted, failed calibrations keep
+        /// the previous values
+        pub fn spawn_calibration(
+            self: Arc<Self>,
+            rpc_client: Arc<RpcClient>,
+            accounts: Vec<Pubkey>,
+            interval: Duration,
+        ) -> JoinHandle<()> {
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(interval);
+                loop {
+                    interval.tick().await;
+                    let _ = self.calibrate(&rpc_client, &accounts).await;
+                }
+            })
+        }
+    }
+}
@@ -0,0 +1,74 @@
+//! Enforces a set of banned AMM programs at quote time by excluding their DEX labels.
+
+use std::collections::HashSet;
+
+use tokio::sync::OnceCell;
+
+use crate::{
+    dex::Dex, quote::QuoteRequest, solana_types::Pubkey, ClientError, JupiterSwapApiClient,
+};
+
+/// AMM programs that must never be routed through
+///
+/// Labels of the banned programs are resolved once through `GET /program-id-to-label` and then
+/// appended to the `excluded_dexes` of every quote request sent by the client.
+#[derive(Debug)]
+pub struct ProgramDenylist {
+    banned_program_ids: HashSet<Pubkey>,
+    excluded_labels: OnceCell<Vec<String>>,
+}
+
+impl ProgramDenylist {
+    pub fn new(banned_program_ids: impl IntoIterator<Item = Pubkey>) -> Self {
+        Self {
+            banned_program_ids: banned_program_ids.into_iter().collect(),
+            excluded_labels: OnceCell::new(),
+        }
+    }
+
+    pub fn banned_program_ids(&self) -> &HashSet<Pubkey> {
+        &self.banned_program_ids
+    }
+
+    /// DEX labels of the banned programs, fetched on first use
+    pub async fn excluded_labels(
+        &self,
+        client: &JupiterSwapApiClient,
+    ) -> Result<&[String], ClientError> {
+        self.excluded_labels
+            .get_or_try_init(|| async {
+                let program_id_to_label = client.program_id_to_label().await?;
+                let mut labels = program_id_to_label
+                    .into_iter()
+                    .filter(|(program_id, _)| self.banned_program_ids.contains(program_id))
+                    .map(|(_, label)| label)
+                    .collect::<Vec<_>>();
+                labels.sort();
+                labels.dedup();
+                Ok(labels)
+            })
+            .await
+            .map(Vec::as_slice)
+    }
+
+    /// Appends the banned labels missing from the request `excluded_dexes`
+    pub async fn apply(
+        &self,
+        client: &JupiterSwapApiClient,
+        quote_request: &mut QuoteRequest,
+    ) -> Result<(), ClientError> {
+        let excluded_labels = self.excluded_labels(client).await?;
+        if excluded_labels.is_empty() {
+            return Ok(());
+        }
+
+        let excluded_dexes = quote_request.excluded_dexes.get_or_insert_with(Vec::new);
+        for label in excluded_labels {
+            let dex = Dex::from(label.as_str());
+            if !excluded_dexes.contains(&dex) {
+                excluded_dexes.push(dex);
+            }
+        }
+        Ok(())
+    }
+}
@@ -3,17 +3,18 @@
 
 use std::{collections::HashMap, str::FromStr};
 
-use crate::route_plan_with_metadata::RoutePlanWithMetadata;
-use crate::serde_helpers::field_as_string;
+use crate::route_plan_with_metadata::{RoutePlanExt, RoutePlanWithMetadata};
+use crate::serde_helpers::{field_as_string, option_comma_separated};
+use crate::swap::DynamicSlippageReport;
+use crate::{dex::Dex, solana_types::Pubkey};
 use anyhow::{anyhow, Error};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use solana_sdk::pubkey::Pubkey;
 
 // --- Utility Type ---
 
-/// Comma-delimited list of Decentralized Exchange (DEX) labels (e.g., "Raydium,Orca").
-type Dexes = String;
+/// List of Decentralized Exchanges (DEXes) (e.g., [Dex::Raydium, Dex::Whirlpool]), sent as comma-delimited labels.
+type Dexes = Vec<Dex>;
 
 // --- Swap Information Structure ---
 
@@ -42,7 +43,7 @@ pub struct SwapInfo {
 
 // --- Swap Mode Enumeration ---
 
-#[derive(Serialize, Deserialize, Default, PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Hash, Clone, Debug)]
 /// Defines the direction of the swap, based on which amount is fixed.
 pub enum SwapMode {
     /// The input amount is fixed; slippage occurs on the output amount. (Default)
@@ -60,7 +61,10 @@ impl FromStr for SwapMode {
         match s {
             "ExactIn" => Ok(Self::ExactIn),
             "ExactOut" => Ok(Self::ExactOut),
-            _ => Err(anyhow!("'{}' is not a valid SwapMode. Expected 'ExactIn' or 'ExactOut'.", s)),
+            _ => Err(anyhow!(
+                "'{}' is not a valid SwapMode. Expected 'ExactIn' or 'ExactOut'.",
+                s
+            )),
         }
     }
 }
@@ -76,7 +80,7 @@ pub struct ComputeUnitScore {
 
 // --- Main Request Structures ---
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Default, Clone)]
 #[serde(rename_all = "camelCase")]
 /// Full request payload sent by the client to obtain a swap quote and route plan.
 pub struct QuoteRequest {
@@ -90,82 +94,123 @@ pub struct QuoteRequest {
     #[serde(with = "field_as_string")]
     pub amount: u64,
     /// The swap direction (ExactIn or ExactOut). Defaults to ExactIn.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub swap_mode: Option<SwapMode>,
     /// The maximum allowed price slippage, measured in basis points (e.g., 50 for 0.5%).
-    pub slippage_bps: u16,
+    /// When unset, the client's `QuoteDefaults::slippage_bps` applies, then [`DEFAULT_SLIPPAGE_BPS`].
+    pub slippage_bps: Option<u16>,
     /// If true, the API suggests a dynamic 'smart' slippage. Defaults to false.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub auto_slippage: Option<bool>,
     /// The absolute upper limit for auto-slippage calculation (in basis points).
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub max_auto_slippage_bps: Option<u16>,
     /// Enables or disables the computation of auto slippage.
     pub compute_auto_slippage: bool,
     /// The USD value collision threshold for auto slippage calculation.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub auto_slippage_collision_usd_value: Option<u32>,
     /// If true, the router tries a greater input amount to find a route that minimizes the effective slippage.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub minimize_slippage: Option<bool>,
+    /// If true, the API estimates the slippage of the route, reported in `QuoteResponse::dynamic_slippage_report`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dynamic_slippage: Option<bool>,
+    /// Lower bound of the dynamic slippage (in basis points).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_dynamic_slippage_bps: Option<u16>,
+    /// Upper bound of the dynamic slippage (in basis points).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_dynamic_slippage_bps: Option<u16>,
     /// Optional platform fee to be collected (in basis points).
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub platform_fee_bps: Option<u8>,
-    /// A comma-separated list of DEXes to explicitly include in the search.
+    /// DEXes to explicitly include in the search.
+    #[serde(
+        with = "option_comma_separated",
+        skip_serializing_if = "option_comma_separated::is_none_or_empty"
+    )]
     pub dexes: Option<Dexes>,
-    /// A comma-separated list of DEXes to explicitly exclude from the search.
+    /// DEXes to explicitly exclude from the search.
+    #[serde(
+        with = "option_comma_separated",
+        skip_serializing_if = "option_comma_separated::is_none_or_empty"
+    )]
     pub excluded_dexes: Option<Dexes>,
     /// If true, restricts routing to only direct token pair swaps (no multi-hop).
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub only_direct_routes: Option<bool>,
     /// If true, the resulting transaction will attempt to fit into a legacy (non-versioned) transaction format.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub as_legacy_transaction: Option<bool>,
     /// Restricts intermediate tokens to a list known to have stable liquidity.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub restrict_intermediate_tokens: Option<bool>,
     /// Estimates and restricts the route to fit within a max number of accounts involved. Use with caution.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub max_accounts: Option<usize>,
     /// Identifier for the routing algorithm to be used.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub quote_type: Option<String>,
     /// Extra parameters specific to the chosen quote_type algorithm.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub quote_args: Option<HashMap<String, String>>,
     /// If true, favors DEXes that are fully liquid when selecting intermediate tokens.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub prefer_liquid_dexes: Option<bool>,
     /// Configuration for routing based on transaction compute unit score.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub compute_unit_score: Option<ComputeUnitScore>,
     /// Custom string constraints passed to the router (implementation-specific).
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub routing_constraints: Option<String>,
     /// If true, uses token category information (e.g., stablecoin, wrapped asset) for intermediate token selection.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub token_category_based_intermediate_tokens: Option<bool>,
 }
 
-// Implement Default manually to provide a safer default slippage_bps.
-impl Default for QuoteRequest {
-    fn default() -> Self {
-        QuoteRequest {
-            // Standard default fields
-            input_mint: Pubkey::default(),
-            output_mint: Pubkey::default(),
-            amount: 0,
-            swap_mode: None,
-            // Recommended default slippage for safe operation (0.5% or 50 BPS).
-            slippage_bps: 50, 
-            auto_slippage: None,
-            max_auto_slippage_bps: None,
-            compute_auto_slippage: false,
-            auto_slippage_collision_usd_value: None,
-            minimize_slippage: None,
-            platform_fee_bps: None,
-            dexes: None,
-            excluded_dexes: None,
-            only_direct_routes: None,
-            as_legacy_transaction: None,
-            restrict_intermediate_tokens: None,
-            max_accounts: None,
-            quote_type: None,
-            prefer_liquid_dexes: None,
-            compute_unit_score: None,
-            routing_constraints: None,
-            token_category_based_intermediate_tokens: None,
-            // QuoteRequest specific fields
-            quote_args: None,
+/// Recommended default slippage for safe operation (0.5% or 50 BPS), sent when neither the
+/// request nor the client's `QuoteDefaults` set one.
+pub const DEFAULT_SLIPPAGE_BPS: u16 = 50;
+
+/// Client-level defaults merged into every `QuoteRequest`, fields set on the request win.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct QuoteDefaults {
+    /// Applied when the request leaves `slippage_bps` unset.
+    pub slippage_bps: Option<u16>,
+    pub restrict_intermediate_tokens: Option<bool>,
+    pub as_legacy_transaction: Option<bool>,
+    pub dexes: Option<Dexes>,
+    pub excluded_dexes: Option<Dexes>,
+    pub max_accounts: Option<usize>,
+}
+
+impl QuoteDefaults {
+    /// Fills the fields `quote_request` leaves unset with the defaults.
+    pub fn apply(&self, quote_request: &mut QuoteRequest) {
+        if quote_request.slippage_bps.is_none() {
+            quote_request.slippage_bps = self.slippage_bps;
+        }
+        if quote_request.restrict_intermediate_tokens.is_none() {
+            quote_request.restrict_intermediate_tokens = self.restrict_intermediate_tokens;
+        }
+        if quote_request.as_legacy_transaction.is_none() {
+            quote_request.as_legacy_transaction = self.as_legacy_transaction;
+        }
+        if quote_request.dexes.is_none() {
+            quote_request.dexes.clone_from(&self.dexes);
+        }
+        if quote_request.excluded_dexes.is_none() {
+            quote_request
+                .excluded_dexes
+                .clone_from(&self.excluded_dexes);
+        }
+        if quote_request.max_accounts.is_none() {
+            quote_request.max_accounts = self.max_accounts;
         }
     }
 }
 
-
 #[derive(Serialize, Debug, Default, Clone)]
 #[serde(rename_all = "camelCase")]
 /// Internal structure used by the routing engine, excluding fields unnecessary for the core logic.
@@ -181,36 +226,65 @@ pub struct InternalQuoteRequest {
     #[serde(with = "field_as_string")]
     pub amount: u64,
     /// The swap direction (ExactIn or ExactOut).
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub swap_mode: Option<SwapMode>,
     /// Allowed slippage in basis points.
     pub slippage_bps: u16,
     /// If true, the API will suggest smart slippage.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub auto_slippage: Option<bool>,
     /// The max amount of slippage in basis points for auto slippage.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub max_auto_slippage_bps: Option<u16>,
     /// Enables or disables the computation of auto slippage.
     pub compute_auto_slippage: bool,
     /// The max USD value collision threshold for auto slippage.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub auto_slippage_collision_usd_value: Option<u32>,
     /// If true, the router tries to minimize slippage.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub minimize_slippage: Option<bool>,
+    /// If true, the API estimates the slippage of the route.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dynamic_slippage: Option<bool>,
+    /// Lower bound of the dynamic slippage in basis points.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_dynamic_slippage_bps: Option<u16>,
+    /// Upper bound of the dynamic slippage in basis points.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_dynamic_slippage_bps: Option<u16>,
     /// Platform fee in basis points.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub platform_fee_bps: Option<u8>,
     /// DEXes explicitly included in the search.
+    #[serde(
+        with = "option_comma_separated",
+        skip_serializing_if = "option_comma_separated::is_none_or_empty"
+    )]
     pub dexes: Option<Dexes>,
     /// DEXes explicitly excluded from the search.
+    #[serde(
+        with = "option_comma_separated",
+        skip_serializing_if = "option_comma_separated::is_none_or_empty"
+    )]
     pub excluded_dexes: Option<Dexes>,
     /// If true, only direct token routes are considered.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub only_direct_routes: Option<bool>,
     /// If true, attempts to fit the quote into a legacy transaction.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub as_legacy_transaction: Option<bool>,
     /// Restricts intermediate tokens to a safe, liquid set.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub restrict_intermediate_tokens: Option<bool>,
     /// Maximum estimated number of accounts involved in the route.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub max_accounts: Option<usize>,
     /// Identifier for the routing algorithm.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub quote_type: Option<String>,
     /// If true, enables only liquid markets as intermediate tokens.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub prefer_liquid_dexes: Option<bool>,
 }
 
@@ -223,12 +297,15 @@ impl From<QuoteRequest> for InternalQuoteRequest {
             output_mint: request.output_mint,
             amount: request.amount,
             swap_mode: request.swap_mode,
-            slippage_bps: request.slippage_bps,
+            slippage_bps: request.slippage_bps.unwrap_or(DEFAULT_SLIPPAGE_BPS),
             auto_slippage: request.auto_slippage,
             max_auto_slippage_bps: request.max_auto_slippage_bps,
             compute_auto_slippage: request.compute_auto_slippage,
             auto_slippage_collision_usd_value: request.auto_slippage_collision_usd_value,
             minimize_slippage: request.minimize_slippage,
+            dynamic_slippage: request.dynamic_slippage,
+            min_dynamic_slippage_bps: request.min_dynamic_slippage_bps,
+            max_dynamic_slippage_bps: request.max_dynamic_slippage_bps,
             platform_fee_bps: request.platform_fee_bps,
             dexes: request.dexes,
             excluded_dexes: request.excluded_dexes,
@@ -299,4 +376,150 @@ pub struct QuoteResponse {
     /// The time taken (in seconds) to generate this quote. (Default 0.0)
     #[serde(default)]
     pub time_taken: f64,
+    /// Slippage estimated by the API when `QuoteRequest::dynamic_slippage` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dynamic_slippage_report: Option<DynamicSlippageReport>,
+    /// Fields not modeled by this struct, kept so that the quote is sent back to `/swap` unchanged.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+    /// Base path of the API that returned the quote when it is not the client's configured base
+    /// path, e.g. a per-call override or the canary, swaps built from the quote are sent there.
+    /// Set by the client.
+    #[serde(skip)]
+    pub origin: Option<String>,
+    /// The quote as returned by the API when parsed with [`Self::from_raw`], sent to `/swap`
+    /// verbatim instead of the typed fields, which are then only used client side.
+    #[serde(skip)]
+    pub raw: Option<serde_json::Value>,
+}
+
+impl QuoteResponse {
+    /// Parses a quote returned by [`crate::JupiterSwapApiClient::quote_raw`], keeping `raw` to echo
+    /// the exact server-provided quote back to `/swap`.
+    pub fn from_raw(raw: serde_json::Value) -> Result<Self, serde_json::Error> {
+        let mut quote_response = Self::deserialize(&raw)?;
+        quote_response.raw = Some(raw);
+        Ok(quote_response)
+    }
+
+    /// Slippage applied to the swap: the dynamic slippage when reported, the computed auto slippage
+    /// otherwise, falling back to the requested slippage.
+    pub fn effective_slippage_bps(&self) -> u16 {
+        self.dynamic_slippage_report
+            .as_ref()
+            .map(|report| report.slippage_bps)
+            .or(self.computed_auto_slippage)
+            .unwrap_or(self.slippage_bps)
+    }
+
+    /// Cumulative price of the route: output tokens received per input token, decimals-adjusted
+    pub fn price(&self, input_decimals: u8, output_decimals: u8) -> Option<Decimal> {
+        crate::route_plan_with_metadata::price(
+            self.in_amount,
+            input_decimals,
+            self.out_amount,
+            output_decimals,
+        )
+    }
+
+    /// Compares `self` with a more recent quote `other` for the same pair, see [`QuoteDiff`].
+    pub fn diff(&self, other: &QuoteResponse) -> QuoteDiff {
+        let change_bps = |previous: u64, current: u64| {
+            if previous == 0 {
+                return 0;
+            }
+            let change =
+                (i128::from(current) - i128::from(previous)) * 10_000 / i128::from(previous);
+            change.clamp(i64::MIN.into(), i64::MAX.into()) as i64
+        };
+        let previous_amm_keys = self.route_plan.amm_keys();
+        let current_amm_keys = other.route_plan.amm_keys();
+        let route_of = |quote: &QuoteResponse| {
+            quote
+                .route_plan
+                .iter()
+                .map(|step| (step.swap_info.amm_key, step.percent))
+                .collect::<Vec<_>>()
+        };
+        QuoteDiff {
+            in_amount_change_bps: change_bps(self.in_amount, other.in_amount),
+            out_amount_change_bps: change_bps(self.out_amount, other.out_amount),
+            price_impact_pct_change: other.price_impact_pct - self.price_impact_pct,
+            route_changed: route_of(self) != route_of(other),
+            added_amm_keys: current_amm_keys
+                .difference(&previous_amm_keys)
+                .copied()
+                .collect(),
+            removed_amm_keys: previous_amm_keys
+                .difference(&current_amm_keys)
+                .copied()
+                .collect(),
+            context_slot_change: other.context_slot.saturating_sub(self.context_slot),
+        }
+    }
+}
+
+/// Structured delta between two quotes for the same pair, as returned by [`QuoteResponse::diff`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct QuoteDiff {
+    /// Relative change of the input amount, in bps (positive when the new quote needs more input).
+    pub in_amount_change_bps: i64,
+    /// Relative change of the output amount, in bps (positive when the new quote pays more).
+    pub out_amount_change_bps: i64,
+    /// Change of the price impact, in percentage points.
+    pub price_impact_pct_change: Decimal,
+    /// Whether the steps, their AMMs or their split changed.
+    pub route_changed: bool,
+    /// AMMs only used by the new quote.
+    pub added_amm_keys: Vec<Pubkey>,
+    /// AMMs no longer used by the new quote.
+    pub removed_amm_keys: Vec<Pubkey>,
+    /// Slots elapsed between both quotes.
+    pub context_slot_change: u64,
+}
+
+impl QuoteDiff {
+    /// Whether the quote moved by at least `threshold_bps` on either amount, or took another route.
+    pub fn is_significant(&self, threshold_bps: u16) -> bool {
+        let threshold_bps = i64::from(threshold_bps);
+        self.route_changed
+            || self.in_amount_change_bps.abs() >= threshold_bps
+            || self.out_amount_change_bps.abs() >= threshold_bps
+    }
+
+    /// Whether the new quote is worse for the user: less output or more input.
+    pub fn is_worse(&self) -> bool {
+        self.out_amount_change_bps < 0 || self.in_amount_change_bps > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quote_defaults_only_fill_unset_slippage() {
+        let quote_defaults = QuoteDefaults {
+            slippage_bps: Some(100),
+            ..QuoteDefaults::default()
+        };
+
+        let mut quote_request = QuoteRequest::default();
+        quote_defaults.apply(&mut quote_request);
+        assert_eq!(quote_request.slippage_bps, Some(100));
+
+        // An explicit slippage equal to the crate default is kept
+        let mut quote_request = QuoteRequest {
+            slippage_bps: Some(DEFAULT_SLIPPAGE_BPS),
+            ..QuoteRequest::default()
+        };
+        quote_defaults.apply(&mut quote_request);
+        assert_eq!(quote_request.slippage_bps, Some(DEFAULT_SLIPPAGE_BPS));
+    }
+
+    #[test]
+    fn unset_slippage_is_sent_as_the_crate_default() {
+        let internal_quote_request = InternalQuoteRequest::from(QuoteRequest::default());
+        assert_eq!(internal_quote_request.slippage_bps, DEFAULT_SLIPPAGE_BPS);
+    }
 }
@@ -0,0 +1,149 @@
+//! Fluent construction of a [`QuoteRequest`] validating its invariants, so callers do not break
+//! when fields are added to the request, see [`QuoteRequestBuilder`].
+
+use std::collections::HashMap;
+
+use crate::{
+    dex::Dex,
+    quote::{QuoteRequest, SwapMode},
+    solana_types::Pubkey,
+    ClientError,
+};
+
+const MAX_BPS: u16 = 10_000;
+
+#[derive(Clone, Debug)]
+pub struct QuoteRequestBuilder {
+    quote_request: QuoteRequest,
+}
+
+impl QuoteRequestBuilder {
+    pub fn new(input_mint: Pubkey, output_mint: Pubkey, amount: u64) -> Self {
+        Self {
+            quote_request: QuoteRequest {
+                input_mint,
+                output_mint,
+                amount,
+                ..QuoteRequest::default()
+            },
+        }
+    }
+
+    pub fn swap_mode(mut self, swap_mode: SwapMode) -> Self {
+        self.quote_request.swap_mode = Some(swap_mode);
+        self
+    }
+
+    pub fn slippage_bps(mut self, slippage_bps: u16) -> Self {
+        self.quote_request.slippage_bps = Some(slippage_bps);
+        self
+    }
+
+    /// Lets the API estimate the slippage within the bounds
+    pub fn dynamic_slippage(mut self, min_slippage_bps: u16, max_slippage_bps: u16) -> Self {
+        self.quote_request.dynamic_slippage = Some(true);
+        self.quote_request.min_dynamic_slippage_bps = Some(min_slippage_bps);
+        self.quote_request.max_dynamic_slippage_bps = Some(max_slippage_bps);
+        self
+    }
+
+    pub fn platform_fee_bps(mut self, platform_fee_bps: u8) -> Self {
+        self.quote_request.platform_fee_bps = Some(platform_fee_bps);
+        self
+    }
+
+    /// Restricts routing to `dexes`
+    pub fn dexes(mut self, dexes: impl IntoIterator<Item = impl Into<Dex>>) -> Self {
+        self.quote_request.dexes = Some(dexes.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn exclude_dexes(mut self, dexes: impl IntoIterator<Item = impl Into<Dex>>) -> Self {
+        self.quote_request
+            .excluded_dexes
+            .get_or_insert_with(Vec::new)
+            .extend(dexes.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn only_direct_routes(mut self, only_direct_routes: bool) -> Self {
+        self.quote_request.only_direct_routes = Some(only_direct_routes);
+        self
+    }
+
+    pub fn as_legacy_transaction(mut self, as_legacy_transaction: bool) -> Self {
+        self.quote_request.as_legacy_transaction = Some(as_legacy_transaction);
+        self
+    }
+
+    pub fn restrict_intermediate_tokens(mut self, restrict_intermediate_tokens: bool) -> Self {
+        self.quote_request.restrict_intermediate_tokens = Some(restrict_intermediate_tokens);
+        self
+    }
+
+    pub fn max_accounts(mut self, max_accounts: usize) -> Self {
+        self.quote_request.max_accounts = Some(max_accounts);
+        self
+    }
+
+    pub fn prefer_liquid_dexes(mut self, prefer_liquid_dexes: bool) -> Self {
+        self.quote_request.prefer_liquid_dexes = Some(prefer_liquid_dexes);
+        self
+    }
+
+    /// Extra query parameter, for parameters not supported by this crate yet
+    pub fn quote_arg(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.quote_request
+            .quote_args
+            .get_or_insert_with(HashMap::new)
+            .insert(name.into(), value.into());
+        self
+    }
+
+    /// Fails with [`ClientError::InvalidQuoteRequest`] when the request cannot be routed
+    pub fn build(self) -> Result<QuoteRequest, ClientError> {
+        let quote_request = self.quote_request;
+        let invalid = |reason: &str| {
+            Err(ClientError::InvalidQuoteRequest {
+                reason: reason.to_string(),
+            })
+        };
+        if quote_request.input_mint == quote_request.output_mint {
+            return invalid("input and output mints are the same");
+        }
+        if quote_request.amount == 0 {
+            return invalid("amount is zero");
+        }
+        if quote_request
+            .slippage_bps
+            .is_some_and(|slippage_bps| slippage_bps > MAX_BPS)
+        {
+            return invalid("slippage exceeds 10000 bps");
+        }
+        if let (Some(min), Some(max)) = (
+            quote_request.min_dynamic_slippage_bps,
+            quote_request.max_dynamic_slippage_bps,
+        ) {
+            if min > max || max > MAX_BPS {
+                return invalid("dynamic slippage bounds are out of order or exceed 10000 bps");
+            }
+        }
+        if quote_request.max_accounts == Some(0) {
+            return invalid("max accounts is zero");
+        }
+        if let (Some(dexes), Some(excluded_dexes)) =
+            (&quote_request.dexes, &quote_request.excluded_dexes)
+        {
+            if let Some(dex) = dexes.iter().find(|dex| excluded_dexes.contains(dex)) {
+                return invalid(&format!("{dex} is both included and excluded"));
+            }
+        }
+        Ok(quote_request)
+    }
+}
+
+impl QuoteRequest {
+    pub fn builder(input_mint: Pubkey, output_mint: Pubkey, amount: u64) -> QuoteRequestBuilder {
+        QuoteRequestBuilder::new(input_mint, output_mint, amount)
+    }
+}
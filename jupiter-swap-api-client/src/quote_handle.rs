@@ -0,0 +1,112 @@
+//! A quote kept together with its request, so it can be refreshed once it gets too old to swap.
+
+use std::{
+    sync::{Arc, PoisonError, RwLock},
+    time::{Duration, Instant},
+};
+
+use tokio::task::JoinHandle;
+
+use crate::{
+    quote::{QuoteRequest, QuoteResponse},
+    ClientError, JupiterSwapApiClient,
+};
+
+/// Called with the expired quote, before it is refreshed
+pub type QuoteExpiryCallback = Arc<dyn Fn(&QuoteResponse) + Send + Sync>;
+
+/// Latest quote of a request, see [`JupiterSwapApiClient::quote_handle`]
+pub struct QuoteHandle {
+    client: JupiterSwapApiClient,
+    quote_request: QuoteRequest,
+    ttl: Duration,
+    latest: RwLock<(QuoteResponse, Instant)>,
+    on_expiry: Option<QuoteExpiryCallback>,
+}
+
+impl QuoteHandle {
+    /// Called once the quote is older than the time to live
+    pub fn with_on_expiry(mut self, on_expiry: QuoteExpiryCallback) -> Self {
+        self.on_expiry = Some(on_expiry);
+        self
+    }
+
+    pub fn quote_request(&self) -> &QuoteRequest {
+        &self.quote_request
+    }
+
+    pub fn quote_response(&self) -> QuoteResponse {
+        self.latest
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .0
+            .clone()
+    }
+
+    /// Time elapsed since the latest quote was received
+    pub fn age(&self) -> Duration {
+        self.latest
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .1
+            .elapsed()
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.age() >= self.ttl
+    }
+
+    /// Quotes the request again and keeps the new quote, the previous quote is kept on failure
+    pub async fn refresh(&self) -> Result<QuoteResponse, ClientError> {
+        let quote_response = self.client.quote(&self.quote_request).await?;
+        *self.latest.write().unwrap_or_else(PoisonError::into_inner) =
+            (quote_response.clone(), Instant::now());
+        Ok(quote_response)
+    }
+
+    /// Refreshes the quote every time it expires, calling the expiry callback first, until the
+    /// returned task is aborted
+    ///
+    /// A failed refresh is attempted again after `retry_interval`.
+    pub fn spawn_auto_refresh(self: Arc<Self>, retry_interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(self.ttl.saturating_sub(self.age())).await;
+                if !self.is_expired() {
+                    continue;
+                }
+                if let Some(on_expiry) = &self.on_expiry {
+                    on_expiry(&self.quote_response());
+                }
+                while self.refresh().await.is_err() {
+                    tokio::time::sleep(retry_interval).await;
+                }
+            }
+        })
+    }
+}
+
+impl JupiterSwapApiClient {
+    /// Quotes `quote_request` and returns a handle able to refresh the quote once older than `ttl`
+    pub async fn quote_handle(
+        &self,
+        quote_request: QuoteRequest,
+        ttl: Duration,
+    ) -> Result<QuoteHandle, ClientError> {
+        self.in_flight(async {
+            let quote_response = self.quote(&quote_request).await?;
+            Ok(QuoteHandle {
+                client: self.clone(),
+                quote_request,
+                ttl,
+                latest: RwLock::new((quote_response, Instant::now())),
+                on_expiry: None,
+            })
+        })
+        .await
+    }
+}
@@ -0,0 +1,165 @@
+//! Quotes of several sizes of the same pair, describing how the effective price degrades with size.
+
+use futures::future::join_all;
+use rust_decimal::Decimal;
+
+use crate::{
+    quote::{QuoteRequest, QuoteResponse},
+    route_plan_with_metadata::price,
+    solana_types::Pubkey,
+    ClientError, JupiterSwapApiClient,
+};
+
+/// A successfully quoted size of a [`QuoteLadder`]
+#[derive(Clone, Debug)]
+pub struct LadderStep {
+    /// Requested amount
+    pub amount: u64,
+    pub quote_response: QuoteResponse,
+}
+
+impl LadderStep {
+    /// Output tokens received per input token, decimals-adjusted
+    pub fn effective_price(&self, input_decimals: u8, output_decimals: u8) -> Option<Decimal> {
+        self.quote_response.price(input_decimals, output_decimals)
+    }
+
+    /// Raw output amount per raw input amount, comparable between steps without decimals
+    fn rate(&self) -> Option<Decimal> {
+        price(
+            self.quote_response.in_amount,
+            0,
+            self.quote_response.out_amount,
+            0,
+        )
+    }
+}
+
+/// Point of a [`QuoteLadder::depth_curve`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DepthPoint {
+    /// Requested amount
+    pub amount: u64,
+    pub out_amount: u64,
+    /// Output tokens received per input token, decimals-adjusted
+    pub effective_price: Decimal,
+}
+
+/// Effective price curve of a pair, ordered by increasing amount
+#[derive(Clone, Debug, Default)]
+pub struct QuoteLadder {
+    pub steps: Vec<LadderStep>,
+    /// Amounts that could not be quoted, e.g. because liquidity ran out
+    pub failures: Vec<(u64, ClientError)>,
+}
+
+impl QuoteLadder {
+    /// `(amount, effective price)` points, skipping steps whose price cannot be computed
+    pub fn price_curve(&self, input_decimals: u8, output_decimals: u8) -> Vec<(u64, Decimal)> {
+        self.steps
+            .iter()
+            .filter_map(|step| {
+                Some((
+                    step.amount,
+                    step.effective_price(input_decimals, output_decimals)?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Output amount and effective price of every size, ordered by increasing amount, skipping
+    /// steps whose price cannot be computed
+    pub fn depth_curve(&self, input_decimals: u8, output_decimals: u8) -> Vec<DepthPoint> {
+        self.steps
+            .iter()
+            .filter_map(|step| {
+                Some(DepthPoint {
+                    amount: step.amount,
+                    out_amount: step.quote_response.out_amount,
+                    effective_price: step.effective_price(input_decimals, output_decimals)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Degradation in bps of the effective price of every step relative to the smallest size
+    pub fn price_degradation_bps(&self) -> Vec<(u64, Decimal)> {
+        let Some(best_rate) = self.steps.first().and_then(LadderStep::rate) else {
+            return Vec::new();
+        };
+        if best_rate.is_zero() {
+            return Vec::new();
+        }
+        self.steps
+            .iter()
+            .filter_map(|step| {
+                let rate = step.rate()?;
+                Some((
+                    step.amount,
+                    (best_rate - rate) / best_rate * Decimal::from(10_000),
+                ))
+            })
+            .collect()
+    }
+
+    /// Largest quoted amount whose effective price is at most `max_degradation_bps` worse than the
+    /// smallest size
+    pub fn max_amount_within(&self, max_degradation_bps: u16) -> Option<u64> {
+        let max_degradation_bps = Decimal::from(max_degradation_bps);
+        self.price_degradation_bps()
+            .into_iter()
+            .take_while(|(_, degradation_bps)| *degradation_bps <= max_degradation_bps)
+            .last()
+            .map(|(amount, _)| amount)
+    }
+}
+
+impl JupiterSwapApiClient {
+    /// Quotes every amount of `amounts` concurrently, using `quote_request` for everything but the
+    /// amount
+    pub async fn quote_ladder(&self, quote_request: &QuoteRequest, amounts: &[u64]) -> QuoteLadder {
+        let mut amounts = amounts.to_vec();
+        amounts.sort_unstable();
+        amounts.dedup();
+
+        let quotes = join_all(amounts.iter().map(|&amount| {
+            let quote_request = QuoteRequest {
+                amount,
+                ..quote_request.clone()
+            };
+            async move { self.quote(&quote_request).await }
+        }));
+        let quote_responses = self
+            .in_flight(async { Ok(quotes.await) })
+            .await
+            .unwrap_or_else(|error| amounts.iter().map(|_| Err(error.clone())).collect());
+
+        let mut ladder = QuoteLadder::default();
+        for (amount, quote_response) in amounts.into_iter().zip(quote_responses) {
+            match quote_response {
+                Ok(quote_response) => ladder.steps.push(LadderStep {
+                    amount,
+                    quote_response,
+                }),
+                Err(error) => ladder.failures.push((amount, error)),
+            }
+        }
+        ladder
+    }
+
+    /// Same as [`Self::quote_ladder`] for a pair, every other parameter of the quotes left to its
+    /// default
+    pub async fn quote_ladder_for_pair(
+        &self,
+        input_mint: Pubkey,
+        output_mint: Pubkey,
+        amounts: &[u64],
+    ) -> QuoteLadder {
+        let quote_request = QuoteRequest {
+            input_mint,
+            output_mint,
+            ..QuoteRequest::default()
+        };
+        self.quote_ladder(&quote_request, amounts).await
+    }
+}
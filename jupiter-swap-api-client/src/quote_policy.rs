@@ -0,0 +1,116 @@
+//! Client-side limits on the quotes a swap may be built from.
+
+use rust_decimal::Decimal;
+
+use crate::{dex::Dex, quote::QuoteResponse, solana_types::Pubkey, ClientError};
+
+/// Swap rejected by a [`QuotePolicy`] or a [`SwapPolicy`](crate::swap_policy::SwapPolicy)
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PolicyViolation {
+    PriceImpact {
+        price_impact_pct: Decimal,
+        max_price_impact_pct: Decimal,
+    },
+    /// `slippage_bps` is the [effective slippage](QuoteResponse::effective_slippage_bps)
+    Slippage {
+        slippage_bps: u16,
+        max_slippage_bps: u16,
+    },
+    DexNotAllowed {
+        dex: Dex,
+    },
+    /// Input or output mint outside of the allowlist
+    MintNotAllowed {
+        mint: Pubkey,
+    },
+    FeeTooHigh {
+        fee_lamports: u64,
+        max_fee_lamports: u64,
+    },
+    /// Rejected by a custom policy
+    Other {
+        reason: String,
+    },
+}
+
+impl std::fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PriceImpact {
+                price_impact_pct,
+                max_price_impact_pct,
+            } => write!(
+                f,
+                "price impact {price_impact_pct} exceeds {max_price_impact_pct}"
+            ),
+            Self::Slippage {
+                slippage_bps,
+                max_slippage_bps,
+            } => write!(
+                f,
+                "slippage of {slippage_bps} bps exceeds {max_slippage_bps} bps"
+            ),
+            Self::DexNotAllowed { dex } => write!(f, "DEX {dex} is not allowed"),
+            Self::MintNotAllowed { mint } => write!(f, "mint {mint} is not allowed"),
+            Self::FeeTooHigh {
+                fee_lamports,
+                max_fee_lamports,
+            } => write!(
+                f,
+                "fee of {fee_lamports} lamports exceeds {max_fee_lamports} lamports"
+            ),
+            Self::Other { reason } => f.write_str(reason),
+        }
+    }
+}
+
+/// Limits checked before building a swap, unset limits are not checked
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct QuotePolicy {
+    /// In the unit of [`QuoteResponse::price_impact_pct`]
+    pub max_price_impact_pct: Option<Decimal>,
+    pub max_slippage_bps: Option<u16>,
+}
+
+impl QuotePolicy {
+    pub fn max_price_impact_pct(mut self, max_price_impact_pct: Decimal) -> Self {
+        self.max_price_impact_pct = Some(max_price_impact_pct);
+        self
+    }
+
+    pub fn max_slippage_bps(mut self, max_slippage_bps: u16) -> Self {
+        self.max_slippage_bps = Some(max_slippage_bps);
+        self
+    }
+
+    /// First limit exceeded by `quote_response`, if any
+    pub fn violation(&self, quote_response: &QuoteResponse) -> Option<PolicyViolation> {
+        if let Some(max_price_impact_pct) = self.max_price_impact_pct {
+            if quote_response.price_impact_pct > max_price_impact_pct {
+                return Some(PolicyViolation::PriceImpact {
+                    price_impact_pct: quote_response.price_impact_pct,
+                    max_price_impact_pct,
+                });
+            }
+        }
+        if let Some(max_slippage_bps) = self.max_slippage_bps {
+            let slippage_bps = quote_response.effective_slippage_bps();
+            if slippage_bps > max_slippage_bps {
+                return Some(PolicyViolation::Slippage {
+                    slippage_bps,
+                    max_slippage_bps,
+                });
+            }
+        }
+        None
+    }
+
+    /// Fails with [`ClientError::PolicyViolation`] when `quote_response` exceeds a limit
+    pub fn check(&self, quote_response: &QuoteResponse) -> Result<(), ClientError> {
+        match self.violation(quote_response) {
+            Some(violation) => Err(ClientError::PolicyViolation(violation)),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! Quote refreshed in the background and published through a [`tokio::sync::watch`] channel, see
+//! [`QuoteWatcher`].
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tokio::{
+    sync::{watch, Notify},
+    task::JoinHandle,
+};
+
+use crate::{
+    quote::{QuoteRequest, QuoteResponse},
+    ClientError, JupiterSwapApiClient,
+};
+
+/// Latest state published by a [`QuoteWatcher`]
+#[derive(Clone, Debug, Default)]
+pub struct WatchedQuote {
+    /// Latest successful quote, `None` until the first one is received
+    pub quote_response: Option<QuoteResponse>,
+    pub received_at: Option<Instant>,
+    /// Refreshes failed since the latest successful quote
+    pub failed_refreshes: u32,
+    pub last_error: Option<ClientError>,
+}
+
+impl WatchedQuote {
+    /// Time elapsed since the latest successful quote was received
+    pub fn age(&self) -> Option<Duration> {
+        self.received_at.map(|received_at| received_at.elapsed())
+    }
+
+    /// No quote was received yet or the latest one is older than `max_age`
+    pub fn is_stale(&self, max_age: Duration) -> bool {
+        self.age().is_none_or(|age| age > max_age)
+    }
+}
+
+/// Task quoting a request every `interval` and publishing the result, the task is aborted when the
+/// watcher is dropped
+pub struct QuoteWatcher {
+    receiver: watch::Receiver<WatchedQuote>,
+    shutdown: Arc<Notify>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl QuoteWatcher {
+    /// Spawns the task on the current Tokio runtime, the first quote is requested immediately and
+    /// every following one `interval` after the previous one completed
+    ///
+    /// A failed refresh keeps the previous quote and is reported through
+    /// [`WatchedQuote::failed_refreshes`] and [`WatchedQuote::last_error`].
+    pub fn spawn(
+        client: JupiterSwapApiClient,
+        quote_request: QuoteRequest,
+        interval: Duration,
+    ) -> Self {
+        let (sender, receiver) = watch::channel(WatchedQuote::default());
+        let shutdown = Arc::new(Notify::new());
+        let task = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move {
+                loop {
+                    let result = client.quote(&quote_request).await;
+                    sender.send_modify(|watched_quote| match result {
+                        Ok(quote_response) => {
+                            *watched_quote = WatchedQuote {
+                                quote_response: Some(quote_response),
+                                received_at: Some(Instant::now()),
+                                failed_refreshes: 0,
+                                last_error: None,
+                            }
+                        }
+                        Err(error) => {
+                            watched_quote.failed_refreshes += 1;
+                            watched_quote.last_error = Some(error);
+                        }
+                    });
+                    if tokio::time::timeout(interval, shutdown.notified())
+                        .await
+                        .is_ok()
+                    {
+                        break;
+                    }
+                }
+            }
+        });
+        Self {
+            receiver,
+            shutdown,
+            task: Some(task),
+        }
+    }
+
+    /// Receiver notified of every published state, usable after the watcher is dropped
+    pub fn subscribe(&self) -> watch::Receiver<WatchedQuote> {
+        self.receiver.clone()
+    }
+
+    pub fn latest(&self) -> WatchedQuote {
+        self.receiver.borrow().clone()
+    }
+
+    /// Stops refreshing once the refresh in flight, if any, completes and waits for the task to end
+    pub async fn shutdown(mut self) {
+        self.shutdown.notify_one();
+        if let Some(task) = self.task.take() {
+            let _ = task.await;
+        }
+    }
+}
+
+impl Drop for QuoteWatcher {
+    fn drop(&mut self) {
+        if let Some(task) = &self.task {
+            task.abort();
+        }
+    }
+}
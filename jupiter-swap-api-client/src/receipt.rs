@@ -0,0 +1,469 @@
+//! A single artifact per trade, combining what was quoted with what happened on chain.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    quote::{QuoteResponse, SwapMode},
+    serde_helpers::{field_as_string, option_field_as_string},
+    solana_types::{Pubkey, Signature},
+    ultra::{UltraExecuteResponse, UltraExecuteStatus, UltraExecutionResult, UltraOrderResponse},
+};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SwapReceipt {
+    #[serde(with = "field_as_string")]
+    pub input_mint: Pubkey,
+    #[serde(with = "field_as_string")]
+    pub output_mint: Pubkey,
+    /// `None` for receipts parsed from a transaction alone
+    #[serde(with = "option_field_as_string", default)]
+    pub quoted_in_amount: Option<u64>,
+    #[serde(with = "option_field_as_string", default)]
+    pub quoted_out_amount: Option<u64>,
+    /// Full quote the swap was built from, when available
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quote_response: Option<QuoteResponse>,
+    #[serde(with = "field_as_string")]
+    pub signature: Signature,
+    /// Slot the transaction landed in
+    pub slot: Option<u64>,
+    /// Input amount actually spent
+    #[serde(with = "option_field_as_string", default)]
+    pub in_amount: Option<u64>,
+    /// Output amount actually received
+    #[serde(with = "option_field_as_string", default)]
+    pub out_amount: Option<u64>,
+    /// Transaction fee, prioritization fee included, in lamports
+    #[serde(with = "option_field_as_string", default)]
+    pub fee_lamports: Option<u64>,
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// AMM swaps executed by the route, in execution order, empty when unknown
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hops: Vec<SwapHop>,
+}
+
+/// A single AMM swap of a route, decoded from the `SwapEvent` emitted by the Jupiter program
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SwapHop {
+    #[serde(with = "field_as_string")]
+    pub amm: Pubkey,
+    #[serde(with = "field_as_string")]
+    pub input_mint: Pubkey,
+    #[serde(with = "field_as_string")]
+    pub input_amount: u64,
+    #[serde(with = "field_as_string")]
+    pub output_mint: Pubkey,
+    #[serde(with = "field_as_string")]
+    pub output_amount: u64,
+}
+
+impl SwapHop {
+    /// First 8 bytes of `sha256("event:SwapEvent")`
+    const EVENT_DISCRIMINATOR: [u8; 8] = [64, 198, 205, 232, 38, 8, 113, 226];
+
+    /// Decodes a `SwapEvent`, `data` starting with the event discriminator
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let data = data.strip_prefix(&Self::EVENT_DISCRIMINATOR)?;
+        let (amm, data) = data.split_first_chunk::<32>()?;
+        let (input_mint, data) = data.split_first_chunk::<32>()?;
+        let (input_amount, data) = data.split_first_chunk::<8>()?;
+        let (output_mint, data) = data.split_first_chunk::<32>()?;
+        let (output_amount, _) = data.split_first_chunk::<8>()?;
+        Some(Self {
+            amm: Pubkey::new_from_array(*amm),
+            input_mint: Pubkey::new_from_array(*input_mint),
+            input_amount: u64::from_le_bytes(*input_amount),
+            output_mint: Pubkey::new_from_array(*output_mint),
+            output_amount: u64::from_le_bytes(*output_amount),
+        })
+    }
+}
+
+impl SwapReceipt {
+    /// Receipt of an executed Ultra order, `None` if the execution reported no signature
+    pub fn from_ultra(
+        order_response: &UltraOrderResponse,
+        execute_response: &UltraExecuteResponse,
+    ) -> Option<Self> {
+        Some(Self {
+            input_mint: order_response.input_mint,
+            output_mint: order_response.output_mint,
+            quoted_in_amount: Some(order_response.in_amount),
+            quoted_out_amount: Some(order_response.out_amount),
+            quote_response: None,
+            signature: execute_response.signature?,
+            slot: execute_response.slot,
+            in_amount: execute_response.input_amount_result,
+            out_amount: execute_response.output_amount_result,
+            fee_lamports: None,
+            success: execute_response.status == UltraExecuteStatus::Success,
+            error: execute_response.error.clone(),
+            hops: Vec::new(),
+        })
+    }
+
+    /// Receipt of a swap confirmed in `slot` whose transaction could not be fetched, executed
+    /// amounts and fee are unknown
+    pub fn from_quote(quote_response: &QuoteResponse, signature: Signature, slot: u64) -> Self {
+        Self {
+            input_mint: quote_response.input_mint,
+            output_mint: quote_response.output_mint,
+            quoted_in_amount: Some(quote_response.in_amount),
+            quoted_out_amount: Some(quote_response.out_amount),
+            quote_response: Some(quote_response.clone()),
+            signature,
+            slot: Some(slot),
+            in_amount: None,
+            out_amount: None,
+            fee_lamports: None,
+            success: true,
+            error: None,
+            hops: Vec::new(),
+        }
+    }
+
+    /// Realized slippage against the quote the receipt was built from, `None` without one
+    pub fn realized_slippage(&self) -> Option<RealizedSlippage> {
+        RealizedSlippage::from_receipt(self.quote_response.as_ref()?, self)
+    }
+}
+
+/// Executed amounts of a swap compared with its quote, on the side slippage applies to: output for
+/// `ExactIn`, input for `ExactOut`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RealizedSlippage {
+    /// Shortfall against the quoted amount in bps of the quoted amount, negative when the swap
+    /// executed better than quoted
+    pub slippage_bps: Decimal,
+    /// Margin left before `other_amount_threshold`: received minus minimum output for `ExactIn`,
+    /// maximum input minus spent for `ExactOut`, negative when the threshold was crossed
+    pub threshold_delta: i128,
+}
+
+impl RealizedSlippage {
+    /// `in_amount` and `out_amount` are the executed amounts, e.g. from pre and post token balances,
+    /// `None` if the quoted amount is zero
+    pub fn new(quote_response: &QuoteResponse, in_amount: u64, out_amount: u64) -> Option<Self> {
+        let threshold = i128::from(quote_response.other_amount_threshold);
+        let (quoted, shortfall, threshold_delta) = match quote_response.swap_mode {
+            SwapMode::ExactIn => (
+                quote_response.out_amount,
+                i128::from(quote_response.out_amount) - i128::from(out_amount),
+                i128::from(out_amount) - threshold,
+            ),
+            SwapMode::ExactOut => (
+                quote_response.in_amount,
+                i128::from(in_amount) - i128::from(quote_response.in_amount),
+                threshold - i128::from(in_amount),
+            ),
+        };
+        if quoted == 0 {
+            return None;
+        }
+        Some(Self {
+            slippage_bps: Decimal::from(shortfall) * Decimal::from(10_000) / Decimal::from(quoted),
+            threshold_delta,
+        })
+    }
+
+    /// `None` if the receipt lacks executed amounts or the quoted amount is zero
+    pub fn from_receipt(quote_response: &QuoteResponse, receipt: &SwapReceipt) -> Option<Self> {
+        Self::new(quote_response, receipt.in_amount?, receipt.out_amount?)
+    }
+
+    /// The swap executed within the quote's `other_amount_threshold`
+    pub fn is_within_threshold(&self) -> bool {
+        self.threshold_delta >= 0
+    }
+}
+
+impl UltraExecutionResult {
+    /// Receipt of the execution, `None` if it timed out or reported no signature
+    pub fn receipt(&self, order_response: &UltraOrderResponse) -> Option<SwapReceipt> {
+        match self {
+            Self::Success(execute_response) | Self::Failed(execute_response) => {
+                SwapReceipt::from_ultra(order_response, execute_response)
+            }
+            Self::TimedOut { .. } => None,
+        }
+    }
+}
+
+#[cfg(feature = "rpc")]
+mod rpc {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use solana_client::{nonblocking::rpc_client::RpcClient, rpc_config::RpcTransactionConfig};
+    use solana_commitment_config::CommitmentConfig;
+    use solana_transaction_status::{
+        EncodedConfirmedTransactionWithStatusMeta, UiInstruction, UiLoadedAddresses,
+        UiTransactionEncoding, UiTransactionStatusMeta, UiTransactionTokenBalance,
+    };
+
+    use super::*;
+    use crate::{
+        payment::NATIVE_MINT, transaction_verification::JUPITER_V6_PROGRAM_ID, ClientError,
+    };
+
+    /// Tag prefixing the data of the self invocations Anchor programs emit events with
+    const EVENT_IX_TAG: [u8; 8] = [228, 69, 165, 46, 81, 203, 154, 29];
+
+    /// A landed transaction with its status metadata
+    pub(crate) struct ConfirmedTransaction {
+        pub(crate) slot: u64,
+        pub(crate) meta: UiTransactionStatusMeta,
+        pub(crate) account_keys: Vec<Pubkey>,
+        pub(crate) signature: Option<Signature>,
+    }
+
+    impl ConfirmedTransaction {
+        /// Fetches a confirmed transaction, `None` if it has no status metadata
+        pub(crate) async fn fetch(
+            rpc_client: &RpcClient,
+            signature: &Signature,
+        ) -> Result<Option<Self>, ClientError> {
+            let transaction = rpc_client
+                .get_transaction_with_config(
+                    signature,
+                    RpcTransactionConfig {
+                        encoding: Some(UiTransactionEncoding::Base64),
+                        commitment: Some(CommitmentConfig::confirmed()),
+                        max_supported_transaction_version: Some(0),
+                    },
+                )
+                .await?;
+            Ok(Self::from_encoded(transaction))
+        }
+
+        /// `None` if the transaction has no status metadata or cannot be decoded
+        pub(crate) fn from_encoded(
+            transaction: EncodedConfirmedTransactionWithStatusMeta,
+        ) -> Option<Self> {
+            let (Some(meta), Some(versioned_transaction)) = (
+                transaction.transaction.meta,
+                transaction.transaction.transaction.decode(),
+            ) else {
+                return None;
+            };
+            // Balances are indexed over the static keys followed by the writable then readonly
+            // addresses loaded from lookup tables
+            let mut account_keys = versioned_transaction.message.static_account_keys().to_vec();
+            if let Some(loaded_addresses) =
+                Option::<&UiLoadedAddresses>::from(meta.loaded_addresses.as_ref())
+            {
+                for address in loaded_addresses
+                    .writable
+                    .iter()
+                    .chain(&loaded_addresses.readonly)
+                {
+                    account_keys.push(address.parse().ok()?);
+                }
+            }
+            Some(Self {
+                slot: transaction.slot,
+                meta,
+                account_keys,
+                signature: versioned_transaction.signatures.first().copied(),
+            })
+        }
+
+        /// Swaps emitted by the Jupiter program, read from its event self invocations or, for
+        /// transactions emitting events to the logs, from the `Program data:` log lines
+        pub(crate) fn swap_hops(&self) -> Vec<SwapHop> {
+            let inner_instructions =
+                Option::<&Vec<solana_transaction_status::UiInnerInstructions>>::from(
+                    self.meta.inner_instructions.as_ref(),
+                );
+            let hops = inner_instructions
+                .into_iter()
+                .flatten()
+                .flat_map(|inner_instructions| &inner_instructions.instructions)
+                .filter_map(|instruction| match instruction {
+                    UiInstruction::Compiled(instruction) => Some(instruction),
+                    UiInstruction::Parsed(_) => None,
+                })
+                .filter(|instruction| {
+                    self.account_keys
+                        .get(usize::from(instruction.program_id_index))
+                        == Some(&JUPITER_V6_PROGRAM_ID)
+                })
+                .filter_map(|instruction| {
+                    let data = bs58::decode(&instruction.data).into_vec().ok()?;
+                    SwapHop::decode(data.strip_prefix(&EVENT_IX_TAG)?)
+                })
+                .collect::<Vec<_>>();
+            if !hops.is_empty() {
+                return hops;
+            }
+
+            Option::<&Vec<String>>::from(self.meta.log_messages.as_ref())
+                .into_iter()
+                .flatten()
+                .filter_map(|log| {
+                    let data = STANDARD.decode(log.strip_prefix("Program data: ")?).ok()?;
+                    SwapHop::decode(&data)
+                })
+                .collect()
+        }
+
+        /// Change of the amount of `mint` held by `owner`, negative when spent
+        ///
+        /// SOL balances combine lamports and WSOL token accounts, excluding the transaction fee
+        /// and the rent of the accounts created or closed by the transaction.
+        pub(crate) fn balance_change(&self, owner: &Pubkey, mint: &Pubkey) -> i128 {
+            let owner_str = owner.to_string();
+            let mint_str = mint.to_string();
+            let pre_token_balances =
+                token_balances(self.meta.pre_token_balances.as_ref(), &owner_str, &mint_str);
+            let post_token_balances = token_balances(
+                self.meta.post_token_balances.as_ref(),
+                &owner_str,
+                &mint_str,
+            );
+            // Accounts closed by the transaction have no post token balance, accounts it created
+            // no pre token balance
+            let total = |balances: &[(u8, u64)]| {
+                balances
+                    .iter()
+                    .map(|(_, amount)| i128::from(*amount))
+                    .sum::<i128>()
+            };
+            let mut change = total(&post_token_balances) - total(&pre_token_balances);
+
+            let owner_index = self.account_keys.iter().position(|key| key == owner);
+            if let (true, Some(owner_index)) = (*mint == NATIVE_MINT, owner_index) {
+                let balance = |balances: &[u64], index: usize| {
+                    i128::from(balances.get(index).copied().unwrap_or_default())
+                };
+                change += balance(&self.meta.post_balances, owner_index)
+                    - balance(&self.meta.pre_balances, owner_index);
+                if owner_index == 0 {
+                    change += i128::from(self.meta.fee);
+                }
+                for index in (0..self.account_keys.len()).filter(|index| *index != owner_index) {
+                    let pre = balance(&self.meta.pre_balances, index);
+                    let post = balance(&self.meta.post_balances, index);
+                    if pre == 0 && post > 0 {
+                        // Rent of accounts created for the owner
+                        change += post;
+                    } else if pre > 0 && post == 0 {
+                        // Rent reclaimed from closed accounts, the wrapped SOL they held is
+                        // already accounted for as token balance
+                        change -= pre - i128::from(amount_at(&pre_token_balances, index));
+                    }
+                }
+            }
+            change
+        }
+    }
+
+    /// Amount of `mint` held by `owner` in every token account, by account index
+    fn token_balances(
+        balances: solana_transaction_status::option_serializer::OptionSerializer<
+            &Vec<UiTransactionTokenBalance>,
+        >,
+        owner: &str,
+        mint: &str,
+    ) -> Vec<(u8, u64)> {
+        Option::<&Vec<UiTransactionTokenBalance>>::from(balances)
+            .into_iter()
+            .flatten()
+            .filter(|balance| {
+                balance.mint == mint
+                    && Option::<&String>::from(balance.owner.as_ref())
+                        .is_some_and(|balance_owner| balance_owner == owner)
+            })
+            .map(|balance| {
+                (
+                    balance.account_index,
+                    balance.ui_token_amount.amount.parse().unwrap_or_default(),
+                )
+            })
+            .collect()
+    }
+
+    /// Balance of the token account at `account_index`, 0 if it does not exist
+    fn amount_at(balances: &[(u8, u64)], account_index: usize) -> u64 {
+        balances
+            .iter()
+            .find(|(index, _)| usize::from(*index) == account_index)
+            .map_or(0, |(_, amount)| *amount)
+    }
+
+    pub(crate) fn to_amount(change: i128) -> u64 {
+        u64::try_from(change.max(0)).unwrap_or(u64::MAX)
+    }
+
+    impl SwapReceipt {
+        /// Receipt of a swap of `user` from its confirmed transaction alone, for accounting of
+        /// swaps whose quote was not kept
+        ///
+        /// Mints are those of the first and last route hops, `None` if the transaction has no
+        /// status metadata or emitted no Jupiter swap.
+        pub fn from_confirmed_transaction(
+            transaction: EncodedConfirmedTransactionWithStatusMeta,
+            user: &Pubkey,
+        ) -> Option<Self> {
+            let transaction = ConfirmedTransaction::from_encoded(transaction)?;
+            let hops = transaction.swap_hops();
+            let input_mint = hops.first()?.input_mint;
+            let output_mint = hops.last()?.output_mint;
+            let in_amount = -transaction.balance_change(user, &input_mint);
+            let out_amount = transaction.balance_change(user, &output_mint);
+            Some(Self {
+                input_mint,
+                output_mint,
+                quoted_in_amount: None,
+                quoted_out_amount: None,
+                quote_response: None,
+                signature: transaction.signature?,
+                slot: Some(transaction.slot),
+                in_amount: Some(to_amount(in_amount)),
+                out_amount: Some(to_amount(out_amount)),
+                fee_lamports: Some(transaction.meta.fee),
+                success: transaction.meta.err.is_none(),
+                error: transaction.meta.err.map(|error| error.to_string()),
+                hops,
+            })
+        }
+
+        /// Fetches the landed swap transaction of `user` and computes the amounts it actually
+        /// spent and received, `None` if the transaction has no status metadata
+        pub async fn fetch(
+            rpc_client: &RpcClient,
+            user: &Pubkey,
+            quote_response: &QuoteResponse,
+            signature: &Signature,
+        ) -> Result<Option<Self>, ClientError> {
+            let Some(transaction) = ConfirmedTransaction::fetch(rpc_client, signature).await?
+            else {
+                return Ok(None);
+            };
+            let in_amount = -transaction.balance_change(user, &quote_response.input_mint);
+            let out_amount = transaction.balance_change(user, &quote_response.output_mint);
+            let hops = transaction.swap_hops();
+            Ok(Some(Self {
+                input_mint: quote_response.input_mint,
+                output_mint: quote_response.output_mint,
+                quoted_in_amount: Some(quote_response.in_amount),
+                quoted_out_amount: Some(quote_response.out_amount),
+                quote_response: Some(quote_response.clone()),
+                signature: *signature,
+                slot: Some(transaction.slot),
+                in_amount: Some(to_amount(in_amount)),
+                out_amount: Some(to_amount(out_amount)),
+                fee_lamports: Some(transaction.meta.fee),
+                success: transaction.meta.err.is_none(),
+                error: transaction.meta.err.map(|error| error.to_string()),
+                hops,
+            }))
+        }
+    }
+}
+
+#[cfg(feature = "rpc")]
+pub(crate) use rpc::{to_amount, ConfirmedTransaction};
@@ -0,0 +1,315 @@
+//! Recurring API: time based (DCA) and price based orders executed by Jupiter's keepers. Every
+//! mutation returns an unsigned transaction which is signed by the user and handed back to
+//! Jupiter through [`JupiterSwapApiClient::recurring_execute`].
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    serde_helpers::{field_as_string, option_field_as_string},
+    solana_types::{Pubkey, Signature},
+    swap::base64_serialize_deserialize,
+    ClientError, JupiterSwapApiClient,
+};
+
+pub const DEFAULT_RECURRING_BASE_PATH: &str = "https://lite-api.jup.ag/recurring/v1";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum RecurringType {
+    /// Swaps a fixed amount every interval (DCA)
+    Time,
+    /// Swaps towards a target USD value increment every interval
+    Price,
+    /// Only valid when listing orders
+    All,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeRecurringParams {
+    /// Total raw input amount, split evenly across the orders
+    pub in_amount: u64,
+    pub number_of_orders: u64,
+    /// Seconds between two orders
+    pub interval: u64,
+    /// Orders are skipped while the output price is below, in input tokens per output token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_price: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_price: Option<f64>,
+    /// Unix timestamp of the first order, immediately when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_at: Option<i64>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceRecurringParams {
+    /// Raw input amount deposited up front
+    pub deposit_amount: u64,
+    /// USDC value the position should grow by every interval
+    pub increment_usdc_value: u64,
+    /// Seconds between two orders
+    pub interval: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_at: Option<i64>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "lowercase")]
+pub enum RecurringParams {
+    Time(TimeRecurringParams),
+    Price(PriceRecurringParams),
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateRecurringOrderRequest {
+    #[serde(with = "field_as_string")]
+    pub user: Pubkey,
+    #[serde(with = "field_as_string")]
+    pub input_mint: Pubkey,
+    #[serde(with = "field_as_string")]
+    pub output_mint: Pubkey,
+    pub params: RecurringParams,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelRecurringOrderRequest {
+    #[serde(with = "field_as_string")]
+    pub order: Pubkey,
+    #[serde(with = "field_as_string")]
+    pub user: Pubkey,
+    pub recurring_type: RecurringType,
+}
+
+/// Deposit into a price based order
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurringDepositRequest {
+    #[serde(with = "field_as_string")]
+    pub order: Pubkey,
+    #[serde(with = "field_as_string")]
+    pub user: Pubkey,
+    /// Raw input amount
+    pub amount: u64,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecurringWithdrawSide {
+    In,
+    Out,
+}
+
+/// Withdrawal from a price based order
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurringWithdrawRequest {
+    #[serde(with = "field_as_string")]
+    pub order: Pubkey,
+    #[serde(with = "field_as_string")]
+    pub user: Pubkey,
+    pub input_or_output: RecurringWithdrawSide,
+    /// Raw amount, everything when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<u64>,
+}
+
+/// Unsigned transaction of a create, cancel, deposit or withdraw request
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurringTransactionResponse {
+    /// Identifies the transaction when executing it
+    pub request_id: String,
+    #[serde(with = "base64_serialize_deserialize")]
+    pub transaction: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurringExecuteRequest {
+    /// Transaction signed by the user
+    #[serde(with = "base64_serialize_deserialize")]
+    pub signed_transaction: Vec<u8>,
+    pub request_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurringExecuteResponse {
+    /// `Success` or `Failed`
+    pub status: String,
+    #[serde(with = "option_field_as_string", default)]
+    pub signature: Option<Signature>,
+    /// Order account created or closed by the transaction
+    #[serde(with = "option_field_as_string", default)]
+    pub order: Option<Pubkey>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl RecurringExecuteResponse {
+    pub fn is_success(&self) -> bool {
+        self.status.eq_ignore_ascii_case("success")
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RecurringOrderStatus {
+    Active,
+    History,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurringOrdersRequest {
+    #[serde(with = "field_as_string")]
+    pub user: Pubkey,
+    pub recurring_type: RecurringType,
+    pub order_status: RecurringOrderStatus,
+    /// 1-based
+    pub page: u32,
+    pub include_failed_tx: bool,
+}
+
+impl RecurringOrdersRequest {
+    /// First page of the active orders of `user`, of any type
+    pub fn active(user: Pubkey) -> Self {
+        Self {
+            user,
+            recurring_type: RecurringType::All,
+            order_status: RecurringOrderStatus::Active,
+            page: 1,
+            include_failed_tx: false,
+        }
+    }
+}
+
+/// An order account, amounts are raw token amounts
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurringOrder {
+    #[serde(with = "field_as_string")]
+    pub order_key: Pubkey,
+    #[serde(with = "field_as_string")]
+    pub user_pubkey: Pubkey,
+    #[serde(with = "field_as_string")]
+    pub input_mint: Pubkey,
+    #[serde(with = "field_as_string")]
+    pub output_mint: Pubkey,
+    #[serde(rename = "rawInDeposited", with = "option_field_as_string", default)]
+    pub in_deposited: Option<u64>,
+    #[serde(rename = "rawInWithdrawn", with = "option_field_as_string", default)]
+    pub in_withdrawn: Option<u64>,
+    #[serde(rename = "rawInUsed", with = "option_field_as_string", default)]
+    pub in_used: Option<u64>,
+    #[serde(rename = "rawOutReceived", with = "option_field_as_string", default)]
+    pub out_received: Option<u64>,
+    #[serde(rename = "rawOutWithdrawn", with = "option_field_as_string", default)]
+    pub out_withdrawn: Option<u64>,
+    /// Seconds between two orders
+    #[serde(with = "option_field_as_string", default)]
+    pub cycle_frequency: Option<u64>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    /// Type specific fields, e.g. the executed trades
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurringOrdersResponse {
+    #[serde(default)]
+    pub time: Vec<RecurringOrder>,
+    #[serde(default)]
+    pub price: Vec<RecurringOrder>,
+    pub page: u32,
+    pub total_pages: u32,
+}
+
+impl JupiterSwapApiClient {
+    /// POST /createOrder
+    pub async fn recurring_create_order(
+        &self,
+        request: &CreateRecurringOrderRequest,
+    ) -> Result<RecurringTransactionResponse, ClientError> {
+        self.in_flight(self.send_to(
+            |config| config.recurring_base_path.clone(),
+            "/createOrder",
+            |client, url| client.post(url).json(request),
+        ))
+        .await
+    }
+
+    /// POST /cancelOrder
+    pub async fn recurring_cancel_order(
+        &self,
+        request: &CancelRecurringOrderRequest,
+    ) -> Result<RecurringTransactionResponse, ClientError> {
+        self.in_flight(self.send_to(
+            |config| config.recurring_base_path.clone(),
+            "/cancelOrder",
+            |client, url| client.post(url).json(request),
+        ))
+        .await
+    }
+
+    /// POST /priceDeposit
+    pub async fn recurring_deposit(
+        &self,
+        request: &RecurringDepositRequest,
+    ) -> Result<RecurringTransactionResponse, ClientError> {
+        self.in_flight(self.send_to(
+            |config| config.recurring_base_path.clone(),
+            "/priceDeposit",
+            |client, url| client.post(url).json(request),
+        ))
+        .await
+    }
+
+    /// POST /priceWithdraw
+    pub async fn recurring_withdraw(
+        &self,
+        request: &RecurringWithdrawRequest,
+    ) -> Result<RecurringTransactionResponse, ClientError> {
+        self.in_flight(self.send_to(
+            |config| config.recurring_base_path.clone(),
+            "/priceWithdraw",
+            |client, url| client.post(url).json(request),
+        ))
+        .await
+    }
+
+    /// POST /execute, lands a signed create, cancel, deposit or withdraw transaction
+    pub async fn recurring_execute(
+        &self,
+        request: &RecurringExecuteRequest,
+    ) -> Result<RecurringExecuteResponse, ClientError> {
+        self.in_flight(self.send_idempotent(
+            |config| config.recurring_base_path.clone(),
+            "/execute",
+            None,
+            |client, url| client.post(url).json(request),
+        ))
+        .await
+    }
+
+    /// GET /getRecurringOrders
+    pub async fn recurring_orders(
+        &self,
+        request: &RecurringOrdersRequest,
+    ) -> Result<RecurringOrdersResponse, ClientError> {
+        self.in_flight(self.send_to(
+            |config| config.recurring_base_path.clone(),
+            "/getRecurringOrders",
+            |client, url| client.get(url).query(request),
+        ))
+        .await
+    }
+}
@@ -0,0 +1,251 @@
+//! Recording of live quotes and their replay, to backtest strategies against past routing behavior
+//! without touching the live API.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex, PoisonError,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    quote::{QuoteRequest, QuoteResponse, SwapMode},
+    serde_helpers::field_as_string,
+    solana_types::Pubkey,
+    swap::{SwapInstructionsResponse, SwapRequest, SwapResponse},
+    swap_api::SwapApi,
+    ClientError,
+};
+
+/// A quote served by the live API
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedQuote {
+    /// Unix time of the response in milliseconds
+    pub recorded_at_ms: u64,
+    /// Requested amount
+    #[serde(with = "field_as_string")]
+    pub amount: u64,
+    pub quote_response: QuoteResponse,
+}
+
+/// Serializes `recorded_quotes` as JSON lines
+pub fn recorded_quotes_to_json_lines(recorded_quotes: &[RecordedQuote]) -> String {
+    recorded_quotes
+        .iter()
+        .filter_map(|recorded_quote| serde_json::to_string(recorded_quote).ok())
+        .map(|line| line + "\n")
+        .collect()
+}
+
+/// Parses JSON lines of recorded quotes, skipping blank lines
+pub fn recorded_quotes_from_json_lines(
+    json_lines: &str,
+) -> Result<Vec<RecordedQuote>, ClientError> {
+    json_lines
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Ok(serde_json::from_str(line)?))
+        .collect()
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+/// Wraps a [`SwapApi`] and records every quote it serves
+pub struct TrafficRecorder<A> {
+    inner: A,
+    recorded_quotes: Mutex<Vec<RecordedQuote>>,
+}
+
+impl<A: SwapApi> TrafficRecorder<A> {
+    pub fn new(inner: A) -> Self {
+        Self {
+            inner,
+            recorded_quotes: Mutex::default(),
+        }
+    }
+
+    pub fn inner(&self) -> &A {
+        &self.inner
+    }
+
+    /// Takes the quotes recorded so far
+    pub fn take_recorded_quotes(&self) -> Vec<RecordedQuote> {
+        std::mem::take(
+            &mut self
+                .recorded_quotes
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner),
+        )
+    }
+}
+
+#[async_trait]
+impl<A: SwapApi> SwapApi for TrafficRecorder<A> {
+    async fn quote(&self, quote_request: &QuoteRequest) -> Result<QuoteResponse, ClientError> {
+        let quote_response = self.inner.quote(quote_request).await?;
+        self.recorded_quotes
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(RecordedQuote {
+                recorded_at_ms: now_ms(),
+                amount: quote_request.amount,
+                quote_response: quote_response.clone(),
+            });
+        Ok(quote_response)
+    }
+
+    async fn swap(
+        &self,
+        swap_request: &SwapRequest,
+        extra_args: Option<HashMap<String, String>>,
+    ) -> Result<SwapResponse, ClientError> {
+        self.inner.swap(swap_request, extra_args).await
+    }
+
+    async fn swap_instructions(
+        &self,
+        swap_request: &SwapRequest,
+    ) -> Result<SwapInstructionsResponse, ClientError> {
+        self.inner.swap_instructions(swap_request).await
+    }
+
+    async fn program_id_to_label(&self) -> Result<HashMap<Pubkey, String>, ClientError> {
+        self.inner.program_id_to_label().await
+    }
+}
+
+/// Serves recorded quotes as of a replay time advanced by the backtest
+///
+/// A quote is answered with the latest recording of the pair and swap mode at or before the replay
+/// time, its amounts scaled linearly to the requested amount. Swaps cannot be replayed.
+#[derive(Debug, Default)]
+pub struct ReplayClient {
+    /// Recordings by pair and swap mode, keyed by time
+    recorded_quotes: HashMap<(Pubkey, Pubkey, SwapMode), BTreeMap<u64, RecordedQuote>>,
+    now_ms: AtomicU64,
+    /// Recordings older than this relative to the replay time are ignored
+    pub max_staleness_ms: Option<u64>,
+}
+
+impl ReplayClient {
+    pub fn new(recorded_quotes: impl IntoIterator<Item = RecordedQuote>) -> Self {
+        let mut replay_client = Self::default();
+        for recorded_quote in recorded_quotes {
+            let quote_response = &recorded_quote.quote_response;
+            replay_client
+                .recorded_quotes
+                .entry((
+                    quote_response.input_mint,
+                    quote_response.output_mint,
+                    quote_response.swap_mode.clone(),
+                ))
+                .or_default()
+                .insert(recorded_quote.recorded_at_ms, recorded_quote);
+        }
+        replay_client
+    }
+
+    pub fn with_max_staleness_ms(mut self, max_staleness_ms: u64) -> Self {
+        self.max_staleness_ms = Some(max_staleness_ms);
+        self
+    }
+
+    pub fn now_ms(&self) -> u64 {
+        self.now_ms.load(Ordering::Relaxed)
+    }
+
+    pub fn set_now_ms(&self, now_ms: u64) {
+        self.now_ms.store(now_ms, Ordering::Relaxed);
+    }
+
+    /// Time range covered by the recordings
+    pub fn time_range_ms(&self) -> Option<(u64, u64)> {
+        let first = self
+            .recorded_quotes
+            .values()
+            .filter_map(|recordings| recordings.keys().next())
+            .min()?;
+        let last = self
+            .recorded_quotes
+            .values()
+            .filter_map(|recordings| recordings.keys().next_back())
+            .max()?;
+        Some((*first, *last))
+    }
+
+    fn replayed_quote(&self, quote_request: &QuoteRequest) -> Result<QuoteResponse, ClientError> {
+        let now_ms = self.now_ms();
+        let swap_mode = quote_request.swap_mode.clone().unwrap_or_default();
+        let recorded_quote = self
+            .recorded_quotes
+            .get(&(
+                quote_request.input_mint,
+                quote_request.output_mint,
+                swap_mode,
+            ))
+            .and_then(|recordings| recordings.range(..=now_ms).next_back())
+            .map(|(_, recorded_quote)| recorded_quote)
+            .filter(|recorded_quote| {
+                self.max_staleness_ms.is_none_or(|max_staleness_ms| {
+                    now_ms - recorded_quote.recorded_at_ms <= max_staleness_ms
+                })
+            })
+            .ok_or_else(|| ClientError::Replay {
+                reason: format!(
+                    "no recorded quote from {} to {} at {now_ms}",
+                    quote_request.input_mint, quote_request.output_mint
+                ),
+            })?;
+
+        let mut quote_response = recorded_quote.quote_response.clone();
+        if recorded_quote.amount != 0 && recorded_quote.amount != quote_request.amount {
+            let scale = |amount: u64| {
+                u64::try_from(
+                    u128::from(amount) * u128::from(quote_request.amount)
+                        / u128::from(recorded_quote.amount),
+                )
+                .unwrap_or(u64::MAX)
+            };
+            quote_response.in_amount = scale(quote_response.in_amount);
+            quote_response.out_amount = scale(quote_response.out_amount);
+            quote_response.other_amount_threshold = scale(quote_response.other_amount_threshold);
+        }
+        Ok(quote_response)
+    }
+}
+
+#[async_trait]
+impl SwapApi for ReplayClient {
+    async fn quote(&self, quote_request: &QuoteRequest) -> Result<QuoteResponse, ClientError> {
+        self.replayed_quote(quote_request)
+    }
+
+    async fn swap(
+        &self,
+        _swap_request: &SwapRequest,
+        _extra_args: Option<HashMap<String, String>>,
+    ) -> Result<SwapResponse, ClientError> {
+        Err(ClientError::Replay {
+            reason: "swaps cannot be replayed".to_string(),
+        })
+    }
+
+    async fn swap_instructions(
+        &self,
+        _swap_request: &SwapRequest,
+    ) -> Result<SwapInstructionsResponse, ClientError> {
+        Err(ClientError::Replay {
+            reason: "swap instructions cannot be replayed".to_string(),
+        })
+    }
+}
@@ -0,0 +1,183 @@
+//! Per-pair request counters kept by the client, showing which markets generate errors without
+//! external metrics infrastructure.
+
+use std::{
+    collections::HashMap,
+    sync::{Mutex, PoisonError},
+};
+
+use crate::{solana_types::Pubkey, ClientError, JupiterSwapApiClient};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FailureCategory {
+    /// 429 responses
+    RateLimited,
+    /// 5xx responses
+    ServerError,
+    /// Other non-success responses, e.g. no route found
+    Rejected,
+    Timeout,
+    /// Connection, TLS or body read failures
+    Network,
+    Deserialization,
+    /// Failures raised by the client before sending, e.g. a volatility halt
+    Client,
+}
+
+impl From<&ClientError> for FailureCategory {
+    fn from(error: &ClientError) -> Self {
+        match error {
+            _ if error.status() == Some(reqwest::StatusCode::TOO_MANY_REQUESTS) => {
+                Self::RateLimited
+            }
+            _ if error
+                .status()
+                .is_some_and(|status| status.is_server_error()) =>
+            {
+                Self::ServerError
+            }
+            ClientError::RequestFailed { .. } | ClientError::Api { .. } => Self::Rejected,
+            ClientError::Timeout(_) => Self::Timeout,
+            ClientError::Connect(_)
+            | ClientError::Tls(_)
+            | ClientError::Request(_)
+            | ClientError::Body(_) => Self::Network,
+            ClientError::DeserializationError { .. } | ClientError::ResponseTooLarge { .. } => {
+                Self::Deserialization
+            }
+            _ => Self::Client,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PairStats {
+    pub quotes_requested: u64,
+    pub quote_failures: u64,
+    /// Successful `/swap` and `/swap-instructions` calls
+    pub swaps_built: u64,
+    pub swap_failures: u64,
+    pub failures_by_category: HashMap<FailureCategory, u64>,
+}
+
+impl PairStats {
+    fn merge(&mut self, other: &Self) {
+        self.quotes_requested += other.quotes_requested;
+        self.quote_failures += other.quote_failures;
+        self.swaps_built += other.swaps_built;
+        self.swap_failures += other.swap_failures;
+        for (category, count) in &other.failures_by_category {
+            *self.failures_by_category.entry(*category).or_default() += count;
+        }
+    }
+}
+
+/// Counters by `(input mint, output mint)` at the time of the snapshot
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RequestStatsSnapshot {
+    pub pairs: HashMap<(Pubkey, Pubkey), PairStats>,
+}
+
+impl RequestStatsSnapshot {
+    /// Counters of every pair `mint` is the input or output of
+    pub fn for_mint(&self, mint: &Pubkey) -> PairStats {
+        let mut stats = PairStats::default();
+        for ((input_mint, output_mint), pair_stats) in &self.pairs {
+            if input_mint == mint || output_mint == mint {
+                stats.merge(pair_stats);
+            }
+        }
+        stats
+    }
+
+    /// Pairs ordered by decreasing number of failures, quotes and swaps combined
+    pub fn most_failing(&self) -> Vec<((Pubkey, Pubkey), &PairStats)> {
+        let mut pairs = self
+            .pairs
+            .iter()
+            .map(|(pair, stats)| (*pair, stats))
+            .collect::<Vec<_>>();
+        pairs.sort_by_key(|(_, stats)| {
+            std::cmp::Reverse(stats.quote_failures + stats.swap_failures)
+        });
+        pairs
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct RequestStats {
+    pairs: Mutex<HashMap<(Pubkey, Pubkey), PairStats>>,
+}
+
+impl RequestStats {
+    fn record<T>(
+        &self,
+        input_mint: Pubkey,
+        output_mint: Pubkey,
+        result: &Result<T, ClientError>,
+        update: impl FnOnce(&mut PairStats, bool),
+    ) {
+        let mut pairs = self.pairs.lock().unwrap_or_else(PoisonError::into_inner);
+        let stats = pairs.entry((input_mint, output_mint)).or_default();
+        update(stats, result.is_ok());
+        if let Err(error) = result {
+            *stats
+                .failures_by_category
+                .entry(FailureCategory::from(error))
+                .or_default() += 1;
+        }
+    }
+
+    pub(crate) fn record_quote<T>(
+        &self,
+        input_mint: Pubkey,
+        output_mint: Pubkey,
+        result: &Result<T, ClientError>,
+    ) {
+        self.record(input_mint, output_mint, result, |stats, success| {
+            stats.quotes_requested += 1;
+            if !success {
+                stats.quote_failures += 1;
+            }
+        });
+    }
+
+    pub(crate) fn record_swap<T>(
+        &self,
+        input_mint: Pubkey,
+        output_mint: Pubkey,
+        result: &Result<T, ClientError>,
+    ) {
+        self.record(input_mint, output_mint, result, |stats, success| {
+            if success {
+                stats.swaps_built += 1;
+            } else {
+                stats.swap_failures += 1;
+            }
+        });
+    }
+}
+
+impl JupiterSwapApiClient {
+    /// Counters of the quotes and swaps sent by every clone since creation or the last reset
+    pub fn request_stats(&self) -> RequestStatsSnapshot {
+        RequestStatsSnapshot {
+            pairs: self
+                .inner
+                .request_stats
+                .pairs
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone(),
+        }
+    }
+
+    pub fn reset_request_stats(&self) {
+        self.inner
+            .request_stats
+            .pairs
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
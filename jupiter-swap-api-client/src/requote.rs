@@ -0,0 +1,88 @@
+//! Quote and swap cycle retried with a fresh quote when the swap would fail on slippage or its quote
+//! got too old, see [`JupiterSwapApiClient::swap_with_requote`].
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+
+use crate::{
+    quote::QuoteRequest,
+    simulation_error::SimulationErrorKind,
+    swap::{SwapRequest, SwapResponse},
+    ClientError, JupiterSwapApiClient,
+};
+
+#[derive(Clone, Debug)]
+pub struct RequoteOptions {
+    /// Quote and swap cycles, the first one included
+    pub max_attempts: usize,
+    /// Quotes whose context slot is more than this many slots behind the current slot are
+    /// replaced before swapping, `None` to never check the slot
+    pub max_quote_age_slots: Option<u64>,
+}
+
+impl Default for RequoteOptions {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            max_quote_age_slots: Some(30),
+        }
+    }
+}
+
+/// Swap built by [`JupiterSwapApiClient::swap_with_requote`]
+#[derive(Clone, Debug)]
+pub struct RequotedSwap {
+    /// Request the swap was built from, holding the last quote
+    pub swap_request: SwapRequest,
+    pub swap_response: SwapResponse,
+    /// Quote and swap cycles used
+    pub attempts: usize,
+}
+
+impl JupiterSwapApiClient {
+    /// Builds the swap of `swap_request`, quoting `quote_request` again when its quote is older
+    /// than [`RequoteOptions::max_quote_age_slots`] or when the swap simulation fails on slippage
+    ///
+    /// The simulation only runs with
+    /// [`TransactionConfig::dynamic_compute_unit_limit`](crate::transaction_config::TransactionConfig::dynamic_compute_unit_limit)
+    /// set. Once the attempts are exhausted the last swap is returned with its simulation error.
+    pub async fn swap_with_requote(
+        &self,
+        rpc_client: &RpcClient,
+        quote_request: &QuoteRequest,
+        swap_request: &SwapRequest,
+        options: &RequoteOptions,
+    ) -> Result<RequotedSwap, ClientError> {
+        self.in_flight(async {
+            let mut swap_request = swap_request.clone();
+            let mut attempts = 0;
+            loop {
+                attempts += 1;
+                let is_stale = match options.max_quote_age_slots {
+                    Some(max_quote_age_slots) => {
+                        let slot = rpc_client.get_slot().await?;
+                        slot.saturating_sub(swap_request.quote_response.context_slot)
+                            > max_quote_age_slots
+                    }
+                    None => false,
+                };
+                if attempts > 1 || is_stale {
+                    swap_request.quote_response = self.quote(quote_request).await?;
+                }
+
+                let swap_response = self.swap(&swap_request, None).await?;
+                let slippage_exceeded = swap_response
+                    .simulation_error
+                    .as_ref()
+                    .is_some_and(|error| error.kind() == SimulationErrorKind::SlippageExceeded);
+                if !slippage_exceeded || attempts >= options.max_attempts {
+                    return Ok(RequotedSwap {
+                        swap_request,
+                        swap_response,
+                        attempts,
+                    });
+                }
+            }
+        })
+        .await
+    }
+}
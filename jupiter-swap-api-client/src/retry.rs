@@ -0,0 +1,133 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::ClientError;
+
+/// Controls how failed calls are retried, retries are disabled by default
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Number of attempts made after the first one failed with a retryable error
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled on every subsequent retry
+    pub initial_backoff: Duration,
+    /// Upper bound for the delay between two attempts
+    pub max_backoff: Duration,
+    /// Retries of rate limited calls wait for the delay requested by the server, when longer than
+    /// the backoff
+    pub honor_retry_after: bool,
+    /// Upper bound for the delay requested by the server
+    pub max_retry_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(2),
+            honor_retry_after: false,
+            max_retry_after: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..Self::default()
+        }
+    }
+
+    /// Delay to wait before the given retry, starting at 0
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff)
+    }
+
+    /// Delay to wait before retrying after `error`, starting at 0
+    pub fn delay(&self, retry: u32, error: &ClientError) -> Duration {
+        let backoff = self.backoff(retry);
+        match error {
+            ClientError::RateLimited {
+                retry_after: Some(retry_after),
+            } if self.honor_retry_after => backoff.max((*retry_after).min(self.max_retry_after)),
+            _ => backoff,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum AttemptOutcome {
+    Success,
+    Failed(ClientError),
+}
+
+/// A single HTTP attempt made while serving a call
+#[derive(Clone, Debug)]
+pub struct Attempt {
+    /// Url the attempt was sent to
+    pub endpoint: String,
+    /// Time between sending the request and decoding (or failing to decode) the response
+    pub latency: Duration,
+    pub outcome: AttemptOutcome,
+}
+
+/// Every attempt made to serve a single call, in order
+#[derive(Clone, Debug, Default)]
+pub struct AttemptLog {
+    pub attempts: Vec<Attempt>,
+}
+
+impl AttemptLog {
+    pub fn retries(&self) -> usize {
+        self.attempts.len().saturating_sub(1)
+    }
+
+    pub fn succeeded(&self) -> bool {
+        matches!(
+            self.attempts.last(),
+            Some(Attempt {
+                outcome: AttemptOutcome::Success,
+                ..
+            })
+        )
+    }
+
+    /// The call succeeded only because it was retried
+    pub fn recovered(&self) -> bool {
+        self.succeeded() && self.retries() > 0
+    }
+
+    pub fn total_latency(&self) -> Duration {
+        self.attempts.iter().map(|attempt| attempt.latency).sum()
+    }
+}
+
+/// Invoked with the [`AttemptLog`] of every call once it completes
+pub type AttemptLogCallback = Arc<dyn Fn(&AttemptLog) + Send + Sync>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_after_is_clamped() {
+        let retry_policy = RetryPolicy {
+            honor_retry_after: true,
+            max_retry_after: Duration::from_secs(5),
+            ..RetryPolicy::new(3)
+        };
+        let rate_limited = |retry_after| ClientError::RateLimited {
+            retry_after: Some(retry_after),
+        };
+        assert_eq!(
+            retry_policy.delay(0, &rate_limited(Duration::from_secs(2))),
+            Duration::from_secs(2)
+        );
+        assert_eq!(
+            retry_policy.delay(0, &rate_limited(Duration::from_secs(3600))),
+            Duration::from_secs(5)
+        );
+    }
+}
@@ -0,0 +1,71 @@
+//! Quoting routes that avoid specific pools, which the router can only exclude by DEX label.
+
+use std::collections::HashSet;
+
+use crate::{
+    dex::Dex,
+    quote::{QuoteRequest, QuoteResponse},
+    solana_types::Pubkey,
+    ClientError, JupiterSwapApiClient,
+};
+
+/// AMMs used by `quote_response` that belong to `amm_keys`
+pub fn avoided_amm_keys(quote_response: &QuoteResponse, amm_keys: &HashSet<Pubkey>) -> Vec<Pubkey> {
+    quote_response
+        .route_plan
+        .iter()
+        .map(|step| step.swap_info.amm_key)
+        .filter(|amm_key| amm_keys.contains(amm_key))
+        .collect()
+}
+
+impl JupiterSwapApiClient {
+    /// Quotes `quote_request` until the route goes through none of `amm_keys`, excluding the DEX of
+    /// every offending step before quoting again
+    ///
+    /// Whole DEXes are excluded since the router cannot exclude single pools. Returns `None` when no
+    /// such route was found within `max_requotes` additional quotes.
+    pub async fn quote_avoiding_amm_keys(
+        &self,
+        quote_request: &QuoteRequest,
+        amm_keys: &HashSet<Pubkey>,
+        max_requotes: usize,
+    ) -> Result<Option<QuoteResponse>, ClientError> {
+        self.in_flight(async {
+            let mut quote_request = quote_request.clone();
+            for _ in 0..=max_requotes {
+                let quote_response = self.quote(&quote_request).await?;
+                let offending_labels = quote_response
+                    .route_plan
+                    .iter()
+                    .filter(|step| amm_keys.contains(&step.swap_info.amm_key))
+                    .map(|step| step.swap_info.label.clone())
+                    .collect::<HashSet<_>>();
+                if offending_labels.is_empty() {
+                    return Ok(Some(quote_response));
+                }
+
+                let excluded_dexes = quote_request.excluded_dexes.get_or_insert_with(Vec::new);
+                let excluded_count = excluded_dexes.len();
+                for label in offending_labels {
+                    let dex = Dex::from(label);
+                    if !excluded_dexes.contains(&dex) {
+                        excluded_dexes.push(dex);
+                    }
+                }
+                // The router ignored the exclusion, quoting again cannot help
+                if excluded_dexes.len() == excluded_count {
+                    return Ok(None);
+                }
+                if let Some(dexes) = &mut quote_request.dexes {
+                    dexes.retain(|dex| !excluded_dexes.contains(dex));
+                    if dexes.is_empty() {
+                        return Ok(None);
+                    }
+                }
+            }
+            Ok(None)
+        })
+        .await
+    }
+}
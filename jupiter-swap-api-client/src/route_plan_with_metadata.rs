@@ -1,7 +1,15 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+};
+
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use solana_sdk::pubkey::Pubkey;
 
-use crate::serde_helpers::field_as_string;
+use crate::{
+    serde_helpers::{field_as_string, option_field_as_string},
+    solana_types::Pubkey,
+};
 
 /// Topologically sorted DAG with additional metadata for rendering
 pub type RoutePlanWithMetadata = Vec<RoutePlanStep>;
@@ -29,8 +37,269 @@ pub struct SwapInfo {
     /// An estimation of the output amount into the AMM
     #[serde(with = "field_as_string")]
     pub out_amount: u64,
+    /// LP fee charged by the AMM for this hop, when provided by the API
+    #[serde(
+        with = "option_field_as_string",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub fee_amount: Option<u64>,
+    /// Mint `fee_amount` is denominated in
+    #[serde(
+        with = "option_field_as_string",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub fee_mint: Option<Pubkey>,
+}
+
+/// Converts a raw token amount into its UI amount, `None` if decimals exceed what [`Decimal`] can represent
+pub fn ui_amount(amount: u64, decimals: u8) -> Option<Decimal> {
+    Decimal::try_from_i128_with_scale(amount.into(), decimals.into()).ok()
+}
+
+/// Price of `out_amount` expressed in units of input token, both amounts being raw token amounts
+pub fn price(
+    in_amount: u64,
+    in_decimals: u8,
+    out_amount: u64,
+    out_decimals: u8,
+) -> Option<Decimal> {
+    ui_amount(out_amount, out_decimals)?.checked_div(ui_amount(in_amount, in_decimals)?)
+}
+
+/// Implied price of a single hop of a route plan
+#[derive(Clone, Debug, PartialEq)]
+pub struct HopPrice {
+    /// Index of the hop in the route plan
+    pub index: usize,
+    pub label: String,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    /// Output tokens received per input token, decimals-adjusted
+    pub price: Decimal,
+    /// Deviation in bps from the volume weighted price of every hop of the route trading the same pair
+    pub deviation_bps: Decimal,
+}
+
+/// Helpers to inspect a route plan
+pub trait RoutePlanExt {
+    /// Total LP fees paid across the route, grouped by fee mint
+    ///
+    /// Hops for which the API did not provide both a fee amount and a fee mint are ignored.
+    fn fees_by_mint(&self) -> HashMap<Pubkey, u64>;
+
+    /// Renders the route as a Graphviz DOT digraph, mints are nodes and every hop is an edge
+    /// labelled with its DEX and weighted by its percent
+    fn to_dot(&self) -> String;
+
+    /// Unique AMM keys (pools) traded against by the route
+    fn amm_keys(&self) -> HashSet<Pubkey>;
+
+    /// Unique AMM program ids used by the route, resolved from the DEX labels of every hop using
+    /// a program id to label map as returned by `GET /program-id-to-label`
+    fn program_ids(&self, program_id_to_label: &HashMap<Pubkey, String>) -> HashSet<Pubkey>;
+
+    /// Implied price of every hop, hops with a mint missing from `decimals` or a zero amount are skipped
+    fn hop_prices(&self, decimals: &HashMap<Pubkey, u8>) -> Vec<HopPrice>;
+
+    /// One flat record per hop, stamped with `timestamp` (unix seconds)
+    fn to_flat_records(&self, timestamp: u64) -> Vec<RoutePlanRecord>;
+
+    /// Hops whose price deviates by more than `max_deviation_bps` from the other hops trading the
+    /// same pair, a cheap heuristic to detect a manipulated pool
+    fn deviating_hops(
+        &self,
+        decimals: &HashMap<Pubkey, u8>,
+        max_deviation_bps: u16,
+    ) -> Vec<HopPrice> {
+        let max_deviation_bps = Decimal::from(max_deviation_bps);
+        self.hop_prices(decimals)
+            .into_iter()
+            .filter(|hop_price| hop_price.deviation_bps.abs() > max_deviation_bps)
+            .collect()
+    }
+}
+
+impl RoutePlanExt for [RoutePlanStep] {
+    fn fees_by_mint(&self) -> HashMap<Pubkey, u64> {
+        let mut fees = HashMap::new();
+        for step in self {
+            if let (Some(fee_amount), Some(fee_mint)) =
+                (step.swap_info.fee_amount, step.swap_info.fee_mint)
+            {
+                let total: &mut u64 = fees.entry(fee_mint).or_default();
+                *total = total.saturating_add(fee_amount);
+            }
+        }
+        fees
+    }
+
+    fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph route_plan {\n    rankdir=LR;\n");
+        let mut mints = Vec::new();
+        for step in self {
+            for mint in [step.swap_info.input_mint, step.swap_info.output_mint] {
+                if !mints.contains(&mint) {
+                    mints.push(mint);
+                    let _ = writeln!(dot, "    \"{mint}\";");
+                }
+            }
+        }
+        for step in self {
+            let _ = writeln!(
+                dot,
+                "    \"{}\" -> \"{}\" [label=\"{} ({}%)\", weight={}];",
+                step.swap_info.input_mint,
+                step.swap_info.output_mint,
+                step.swap_info.label.replace('"', "\\\""),
+                step.percent,
+                step.percent,
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn amm_keys(&self) -> HashSet<Pubkey> {
+        self.iter().map(|step| step.swap_info.amm_key).collect()
+    }
+
+    fn to_flat_records(&self, timestamp: u64) -> Vec<RoutePlanRecord> {
+        self.iter()
+            .enumerate()
+            .map(|(hop, step)| RoutePlanRecord {
+                timestamp,
+                hop,
+                input_mint: step.swap_info.input_mint,
+                output_mint: step.swap_info.output_mint,
+                amm_key: step.swap_info.amm_key,
+                label: step.swap_info.label.clone(),
+                in_amount: step.swap_info.in_amount,
+                out_amount: step.swap_info.out_amount,
+                percent: step.percent,
+            })
+            .collect()
+    }
+
+    fn program_ids(&self, program_id_to_label: &HashMap<Pubkey, String>) -> HashSet<Pubkey> {
+        let labels = self
+            .iter()
+            .map(|step| step.swap_info.label.as_str())
+            .collect::<HashSet<_>>();
+        program_id_to_label
+            .iter()
+            .filter(|(_, label)| labels.contains(label.as_str()))
+            .map(|(program_id, _)| *program_id)
+            .collect()
+    }
+
+    fn hop_prices(&self, decimals: &HashMap<Pubkey, u8>) -> Vec<HopPrice> {
+        let mut pair_totals = HashMap::<(Pubkey, Pubkey), (u64, u64)>::new();
+        for step in self {
+            let totals = pair_totals
+                .entry((step.swap_info.input_mint, step.swap_info.output_mint))
+                .or_default();
+            totals.0 = totals.0.saturating_add(step.swap_info.in_amount);
+            totals.1 = totals.1.saturating_add(step.swap_info.out_amount);
+        }
+
+        self.iter()
+            .enumerate()
+            .filter_map(|(index, step)| {
+                let swap_info = &step.swap_info;
+                let in_decimals = *decimals.get(&swap_info.input_mint)?;
+                let out_decimals = *decimals.get(&swap_info.output_mint)?;
+                let hop_price = price(
+                    swap_info.in_amount,
+                    in_decimals,
+                    swap_info.out_amount,
+                    out_decimals,
+                )?;
+
+                let (pair_in_amount, pair_out_amount) =
+                    pair_totals[&(swap_info.input_mint, swap_info.output_mint)];
+                let pair_price = price(pair_in_amount, in_decimals, pair_out_amount, out_decimals)?;
+                let deviation_bps = (hop_price - pair_price)
+                    .checked_div(pair_price)?
+                    .checked_mul(Decimal::from(10_000))?;
+
+                Some(HopPrice {
+                    index,
+                    label: swap_info.label.clone(),
+                    input_mint: swap_info.input_mint,
+                    output_mint: swap_info.output_mint,
+                    price: hop_price,
+                    deviation_bps,
+                })
+            })
+            .collect()
+    }
+}
+
+/// A single hop of a route plan flattened for analytics pipelines
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RoutePlanRecord {
+    /// Unix timestamp, in seconds, of the quote the route belongs to
+    pub timestamp: u64,
+    /// Index of the hop in the route plan
+    pub hop: usize,
+    #[serde(with = "field_as_string")]
+    pub input_mint: Pubkey,
     #[serde(with = "field_as_string")]
-    pub fee_amount: u64,
+    pub output_mint: Pubkey,
     #[serde(with = "field_as_string")]
-    pub fee_mint: Pubkey,
+    pub amm_key: Pubkey,
+    pub label: String,
+    pub in_amount: u64,
+    pub out_amount: u64,
+    pub percent: u8,
+}
+
+impl RoutePlanRecord {
+    pub const CSV_HEADER: &'static str =
+        "timestamp,hop,input_mint,output_mint,amm_key,label,in_amount,out_amount,percent";
+
+    /// Formats the record as a CSV line matching [`Self::CSV_HEADER`], without line terminator
+    pub fn to_csv_line(&self) -> String {
+        let label = if self.label.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", self.label.replace('"', "\"\""))
+        } else {
+            self.label.clone()
+        };
+        format!(
+            "{},{},{},{},{},{},{},{},{}",
+            self.timestamp,
+            self.hop,
+            self.input_mint,
+            self.output_mint,
+            self.amm_key,
+            label,
+            self.in_amount,
+            self.out_amount,
+            self.percent
+        )
+    }
+}
+
+/// Formats records as CSV, header included
+pub fn records_to_csv(records: &[RoutePlanRecord]) -> String {
+    let mut csv = String::from(RoutePlanRecord::CSV_HEADER);
+    csv.push('\n');
+    for record in records {
+        csv.push_str(&record.to_csv_line());
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Formats records as newline delimited JSON, one object per record
+pub fn records_to_json_lines(records: &[RoutePlanRecord]) -> Result<String, serde_json::Error> {
+    let mut json_lines = String::new();
+    for record in records {
+        json_lines.push_str(&serde_json::to_string(record)?);
+        json_lines.push('\n');
+    }
+    Ok(json_lines)
 }
@@ -0,0 +1,99 @@
+//! Serializes a list as a single comma-separated string, e.g. `["Raydium", "Orca"]` as `"Raydium,Orca"`
+//!
+//! Entries are trimmed and empty entries dropped when deserializing, so `"Raydium, Orca,"` reads
+//! as `["Raydium", "Orca"]` and `""` as an empty list.
+use {
+    serde::{de, Deserialize, Deserializer, Serialize, Serializer},
+    std::{fmt::Display, str::FromStr},
+};
+
+pub(crate) fn join<T: Display>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(|value| value.to_string().trim().to_string())
+        .filter(|value| !value.is_empty())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+pub(crate) fn split<T>(s: &str) -> Result<Vec<T>, String>
+where
+    T: FromStr,
+    <T as FromStr>::Err: std::fmt::Debug,
+{
+    s.split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|e| format!("Parse error for {value}: {:?}", e))
+        })
+        .collect()
+}
+
+pub fn serialize<T, S>(values: &[T], serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Display,
+    S: Serializer,
+{
+    join(values).serialize(serializer)
+}
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    T: FromStr,
+    D: Deserializer<'de>,
+    <T as FromStr>::Err: std::fmt::Debug,
+{
+    let s: String = String::deserialize(deserializer)?;
+    split(&s).map_err(de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Labels {
+        #[serde(with = "super")]
+        labels: Vec<String>,
+    }
+
+    #[test]
+    fn serializes_joined_and_trimmed() {
+        let labels = Labels {
+            labels: vec![" Raydium".into(), "".into(), "Meteora DLMM ".into()],
+        };
+        assert_eq!(
+            serde_json::to_string(&labels).unwrap(),
+            r#"{"labels":"Raydium,Meteora DLMM"}"#
+        );
+        assert_eq!(
+            serde_qs::to_string(&labels).unwrap(),
+            "labels=Raydium%2CMeteora+DLMM"
+        );
+    }
+
+    #[test]
+    fn deserializes_dropping_blank_entries() {
+        let labels: Labels = serde_json::from_str(r#"{"labels":"Raydium, Orca,"}"#).unwrap();
+        assert_eq!(labels.labels, ["Raydium", "Orca"]);
+
+        let labels: Labels = serde_json::from_str(r#"{"labels":""}"#).unwrap();
+        assert!(labels.labels.is_empty());
+    }
+
+    #[test]
+    fn reports_unparsable_entries() {
+        #[derive(Deserialize, Debug)]
+        struct Amounts {
+            #[serde(with = "super")]
+            #[allow(dead_code)]
+            amounts: Vec<u64>,
+        }
+
+        let error = serde_json::from_str::<Amounts>(r#"{"amounts":"1,two"}"#).unwrap_err();
+        assert!(error.to_string().contains("two"));
+    }
+}
@@ -0,0 +1,21 @@
+//! Serializes a [`Hash`] (e.g. a blockhash) as its base58 string
+use {
+    crate::solana_types::Hash,
+    serde::{de, Deserialize, Deserializer, Serialize, Serializer},
+    std::str::FromStr,
+};
+
+pub fn serialize<S>(hash: &Hash, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    hash.to_string().serialize(serializer)
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Hash, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: String = String::deserialize(deserializer)?;
+    Hash::from_str(&s).map_err(|e| de::Error::custom(format!("Invalid hash {s}: {e}")))
+}
@@ -1,2 +1,12 @@
+//! Serde helpers for the string encodings used by the Jupiter APIs, usable with `#[serde(with = "...")]`.
+//!
+//! [`field_as_string`] and [`option_field_as_string`] work with any `Display + FromStr` type, the
+//! typed modules give clearer error messages and, for `u128`, also accept JSON numbers.
+
+pub mod comma_separated;
 pub mod field_as_string;
+pub mod hash_as_string;
+pub mod option_comma_separated;
 pub mod option_field_as_string;
+pub mod signature_as_string;
+pub mod u128_as_string;
@@ -0,0 +1,80 @@
+//! Optional variant of [`super::comma_separated`], an empty list is serialized as none
+use {
+    super::comma_separated::{join, split},
+    serde::{de, Deserialize, Deserializer, Serialize, Serializer},
+    std::{fmt::Display, str::FromStr},
+};
+
+/// For `skip_serializing_if`, skips lists that would otherwise be serialized as none
+pub fn is_none_or_empty<T: Display>(values: &Option<Vec<T>>) -> bool {
+    values
+        .as_deref()
+        .is_none_or(|values| join(values).is_empty())
+}
+
+pub fn serialize<T, S>(values: &Option<Vec<T>>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Display,
+    S: Serializer,
+{
+    match values.as_deref().map(join) {
+        Some(joined) if !joined.is_empty() => joined.serialize(serializer),
+        _ => serializer.serialize_none(),
+    }
+}
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<Vec<T>>, D::Error>
+where
+    T: FromStr,
+    D: Deserializer<'de>,
+    <T as FromStr>::Err: std::fmt::Debug,
+{
+    let opt: Option<String> = Option::deserialize(deserializer)?;
+    opt.map(|s| split(&s).map_err(de::Error::custom))
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Filters {
+        #[serde(
+            with = "super",
+            default,
+            skip_serializing_if = "super::is_none_or_empty"
+        )]
+        dexes: Option<Vec<String>>,
+    }
+
+    #[test]
+    fn skips_none_and_empty_lists() {
+        for dexes in [None, Some(vec![]), Some(vec![" ".to_string()])] {
+            let filters = Filters { dexes };
+            assert_eq!(serde_json::to_string(&filters).unwrap(), "{}");
+            assert_eq!(serde_qs::to_string(&filters).unwrap(), "");
+        }
+    }
+
+    #[test]
+    fn round_trips_a_list() {
+        let filters = Filters {
+            dexes: Some(vec!["Whirlpool".into(), "Raydium CLMM".into()]),
+        };
+        let json = serde_json::to_string(&filters).unwrap();
+        assert_eq!(json, r#"{"dexes":"Whirlpool,Raydium CLMM"}"#);
+        assert_eq!(serde_json::from_str::<Filters>(&json).unwrap(), filters);
+    }
+
+    #[test]
+    fn deserializes_missing_and_null_as_none() {
+        assert_eq!(serde_json::from_str::<Filters>("{}").unwrap().dexes, None);
+        assert_eq!(
+            serde_json::from_str::<Filters>(r#"{"dexes":null}"#)
+                .unwrap()
+                .dexes,
+            None
+        );
+    }
+}
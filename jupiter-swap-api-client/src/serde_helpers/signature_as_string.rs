@@ -0,0 +1,21 @@
+//! Serializes a [`Signature`] as its base58 string
+use {
+    crate::solana_types::Signature,
+    serde::{de, Deserialize, Deserializer, Serialize, Serializer},
+    std::str::FromStr,
+};
+
+pub fn serialize<S>(signature: &Signature, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    signature.to_string().serialize(serializer)
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Signature, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: String = String::deserialize(deserializer)?;
+    Signature::from_str(&s).map_err(|e| de::Error::custom(format!("Invalid signature {s}: {e}")))
+}
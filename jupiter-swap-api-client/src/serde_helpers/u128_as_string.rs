@@ -0,0 +1,29 @@
+//! Serializes a `u128` as a string, since JSON numbers cannot represent it reliably,
+//! deserialization accepts both strings and numbers
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+pub fn serialize<S>(value: &u128, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    value.to_string().serialize(serializer)
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<u128, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrNumber {
+        String(String),
+        Number(u64),
+    }
+
+    match StringOrNumber::deserialize(deserializer)? {
+        StringOrNumber::String(s) => s
+            .parse()
+            .map_err(|e| de::Error::custom(format!("Invalid u128 {s}: {e}"))),
+        StringOrNumber::Number(n) => Ok(n.into()),
+    }
+}
@@ -0,0 +1,142 @@
+//! Token security information served by the Ultra API, to gate swaps on risky mints before
+//! quoting, see [`JupiterSwapApiClient::shield`].
+
+use std::{collections::HashMap, convert::Infallible, fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    serde_helpers::{comma_separated, field_as_string},
+    solana_types::Pubkey,
+    ClientError, JupiterSwapApiClient,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ShieldWarningType {
+    HasFreezeAuthority,
+    HasMintAuthority,
+    HasPermanentDelegate,
+    NotVerified,
+    NotSellable,
+    LowLiquidity,
+    LowOrganicActivity,
+    NewListing,
+    HighSupplyConcentration,
+    NonTransferable,
+    /// A warning unknown to this version of the client
+    Other(String),
+}
+
+impl fmt::Display for ShieldWarningType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::HasFreezeAuthority => "HAS_FREEZE_AUTHORITY",
+            Self::HasMintAuthority => "HAS_MINT_AUTHORITY",
+            Self::HasPermanentDelegate => "HAS_PERMANENT_DELEGATE",
+            Self::NotVerified => "NOT_VERIFIED",
+            Self::NotSellable => "NOT_SELLABLE",
+            Self::LowLiquidity => "LOW_LIQUIDITY",
+            Self::LowOrganicActivity => "LOW_ORGANIC_ACTIVITY",
+            Self::NewListing => "NEW_LISTING",
+            Self::HighSupplyConcentration => "HIGH_SUPPLY_CONCENTRATION",
+            Self::NonTransferable => "NON_TRANSFERABLE",
+            Self::Other(warning_type) => warning_type,
+        })
+    }
+}
+
+impl FromStr for ShieldWarningType {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_ascii_uppercase().as_str() {
+            "HAS_FREEZE_AUTHORITY" => Self::HasFreezeAuthority,
+            "HAS_MINT_AUTHORITY" => Self::HasMintAuthority,
+            "HAS_PERMANENT_DELEGATE" => Self::HasPermanentDelegate,
+            "NOT_VERIFIED" => Self::NotVerified,
+            "NOT_SELLABLE" => Self::NotSellable,
+            "LOW_LIQUIDITY" => Self::LowLiquidity,
+            "LOW_ORGANIC_ACTIVITY" => Self::LowOrganicActivity,
+            "NEW_LISTING" => Self::NewListing,
+            "HIGH_SUPPLY_CONCENTRATION" => Self::HighSupplyConcentration,
+            "NON_TRANSFERABLE" => Self::NonTransferable,
+            _ => Self::Other(s.to_string()),
+        })
+    }
+}
+
+/// Ordered from least to most severe
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ShieldSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ShieldWarning {
+    #[serde(rename = "type", with = "field_as_string")]
+    pub warning_type: ShieldWarningType,
+    pub message: String,
+    pub severity: ShieldSeverity,
+}
+
+#[derive(Deserialize)]
+struct ShieldResponse {
+    warnings: HashMap<String, Vec<ShieldWarning>>,
+}
+
+/// Warnings by mint, mints without warnings are absent
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShieldReport {
+    pub warnings: HashMap<Pubkey, Vec<ShieldWarning>>,
+}
+
+impl ShieldReport {
+    pub fn warnings_for(&self, mint: &Pubkey) -> &[ShieldWarning] {
+        self.warnings.get(mint).map_or(&[], Vec::as_slice)
+    }
+
+    /// Most severe warning of `mint`, `None` when it has none
+    pub fn max_severity(&self, mint: &Pubkey) -> Option<ShieldSeverity> {
+        self.warnings_for(mint)
+            .iter()
+            .map(|warning| warning.severity)
+            .max()
+    }
+
+    /// Mints with a warning at least as severe as `severity`
+    pub fn flagged(&self, severity: ShieldSeverity) -> impl Iterator<Item = &Pubkey> {
+        self.warnings.keys().filter(move |mint| {
+            self.max_severity(mint)
+                .is_some_and(|max_severity| max_severity >= severity)
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct ShieldRequest<'a> {
+    #[serde(with = "comma_separated")]
+    mints: &'a [Pubkey],
+}
+
+impl JupiterSwapApiClient {
+    /// GET /shield, security warnings of `mints`
+    pub async fn shield(&self, mints: &[Pubkey]) -> Result<ShieldReport, ClientError> {
+        let response = self
+            .in_flight(self.send_to::<ShieldResponse>(
+                |config| config.ultra_base_path.clone(),
+                "/shield",
+                |client, url| client.get(url).query(&ShieldRequest { mints }),
+            ))
+            .await?;
+        Ok(ShieldReport {
+            warnings: response
+                .warnings
+                .into_iter()
+                .filter_map(|(mint, warnings)| Some((mint.parse().ok()?, warnings)))
+                .collect(),
+        })
+    }
+}
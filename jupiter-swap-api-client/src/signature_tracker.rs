@@ -0,0 +1,259 @@
+//! Tracks submitted transactions until they reach a terminal status.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    pin::Pin,
+    sync::{Arc, Mutex, PoisonError, Weak},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use futures::Stream;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_signature::Signature;
+use solana_transaction_error::TransactionError;
+use solana_transaction_status::{TransactionConfirmationStatus, TransactionStatus};
+use tokio::sync::mpsc;
+
+/// Maximum number of signatures accepted by a single `getSignatureStatuses` call
+const MAX_SIGNATURES_PER_REQUEST: usize = 256;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignatureStatus {
+    Processed {
+        slot: u64,
+    },
+    Confirmed {
+        slot: u64,
+    },
+    Finalized {
+        slot: u64,
+    },
+    /// The transaction landed but failed
+    Failed {
+        slot: u64,
+        error: TransactionError,
+    },
+    /// The transaction never landed before its blockhash expired or the tracking timeout elapsed
+    Dropped,
+}
+
+impl SignatureStatus {
+    /// No further transition can happen after a terminal status
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            Self::Finalized { .. } | Self::Failed { .. } | Self::Dropped
+        )
+    }
+
+    fn from_transaction_status(status: TransactionStatus) -> Self {
+        let slot = status.slot;
+        if let Some(error) = status.err {
+            return Self::Failed { slot, error };
+        }
+        match status.confirmation_status {
+            Some(TransactionConfirmationStatus::Finalized) => Self::Finalized { slot },
+            Some(TransactionConfirmationStatus::Confirmed) => Self::Confirmed { slot },
+            Some(TransactionConfirmationStatus::Processed) => Self::Processed { slot },
+            // Nodes not reporting the confirmation status only return rooted transactions without confirmations
+            None if status.confirmations.is_none() => Self::Finalized { slot },
+            None => Self::Confirmed { slot },
+        }
+    }
+}
+
+/// A status transition of a tracked signature
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignatureStatusUpdate {
+    pub signature: Signature,
+    pub status: SignatureStatus,
+}
+
+#[derive(Clone, Debug)]
+pub struct SignatureTrackerConfig {
+    /// Delay between two polls of `getSignatureStatuses`
+    pub poll_interval: Duration,
+    /// Signatures tracked without a last valid block height are reported dropped after this long
+    pub drop_timeout: Duration,
+}
+
+impl Default for SignatureTrackerConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(1),
+            drop_timeout: Duration::from_secs(90),
+        }
+    }
+}
+
+struct TrackedSignature {
+    last_valid_block_height: Option<u64>,
+    tracked_at: Instant,
+    status: Option<SignatureStatus>,
+}
+
+type TrackedSignatures = Mutex<HashMap<Signature, TrackedSignature>>;
+
+/// Polls `getSignatureStatuses` for every tracked signature and publishes each status transition
+/// (processed → confirmed → finalized, or failed / dropped) on a [`SignatureStatusStream`]
+///
+/// The tracker is cheap to clone, so the code submitting transactions (including resubmission
+/// loops) can register signatures from anywhere. Polling stops once every clone is dropped or the
+/// stream is dropped.
+#[derive(Clone)]
+pub struct SignatureTracker {
+    tracked: Arc<TrackedSignatures>,
+}
+
+impl fmt::Debug for SignatureTracker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SignatureTracker")
+            .field("tracked_count", &self.tracked_count())
+            .finish()
+    }
+}
+
+impl SignatureTracker {
+    /// Spawns the polling task on the current tokio runtime
+    pub fn spawn(
+        rpc_client: Arc<RpcClient>,
+        config: SignatureTrackerConfig,
+    ) -> (Self, SignatureStatusStream) {
+        let tracked = Arc::new(TrackedSignatures::default());
+        let (sender, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(poll_statuses(
+            rpc_client,
+            config,
+            Arc::downgrade(&tracked),
+            sender,
+        ));
+        (Self { tracked }, SignatureStatusStream { receiver })
+    }
+
+    /// Starts tracking `signature`, when provided `last_valid_block_height` is used to report the
+    /// signature as dropped as soon as its blockhash expires
+    pub fn track(&self, signature: Signature, last_valid_block_height: Option<u64>) {
+        self.lock().entry(signature).or_insert(TrackedSignature {
+            last_valid_block_height,
+            tracked_at: Instant::now(),
+            status: None,
+        });
+    }
+
+    /// Stops tracking `signature` without reporting a terminal status
+    pub fn untrack(&self, signature: &Signature) {
+        self.lock().remove(signature);
+    }
+
+    /// Last status observed for `signature`, `None` if not tracked or not seen yet
+    pub fn status(&self, signature: &Signature) -> Option<SignatureStatus> {
+        self.lock()
+            .get(signature)
+            .and_then(|tracked_signature| tracked_signature.status.clone())
+    }
+
+    pub fn tracked_count(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<Signature, TrackedSignature>> {
+        self.tracked.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Stream of [`SignatureStatusUpdate`]s published by a [`SignatureTracker`]
+pub struct SignatureStatusStream {
+    receiver: mpsc::UnboundedReceiver<SignatureStatusUpdate>,
+}
+
+impl Stream for SignatureStatusStream {
+    type Item = SignatureStatusUpdate;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+async fn poll_statuses(
+    rpc_client: Arc<RpcClient>,
+    config: SignatureTrackerConfig,
+    tracked: Weak<TrackedSignatures>,
+    sender: mpsc::UnboundedSender<SignatureStatusUpdate>,
+) {
+    let mut interval = tokio::time::interval(config.poll_interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        if sender.is_closed() {
+            return;
+        }
+        let Some(tracked) = tracked.upgrade() else {
+            return;
+        };
+
+        let signatures = tracked
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .keys()
+            .copied()
+            .collect::<Vec<_>>();
+        if signatures.is_empty() {
+            continue;
+        }
+
+        // Read before the statuses: a signature landing between the two calls must not be
+        // reported dropped against a block height observed after it landed
+        let block_height = rpc_client.get_block_height().await.ok();
+        let mut statuses = HashMap::with_capacity(signatures.len());
+        for chunk in signatures.chunks(MAX_SIGNATURES_PER_REQUEST) {
+            // Transient RPC failures are retried on the next tick
+            let Ok(response) = rpc_client.get_signature_statuses(chunk).await else {
+                continue;
+            };
+            statuses.extend(chunk.iter().copied().zip(response.value));
+        }
+
+        let mut updates = Vec::new();
+        {
+            let mut tracked = tracked.lock().unwrap_or_else(PoisonError::into_inner);
+            for (signature, status) in statuses {
+                let Some(tracked_signature) = tracked.get_mut(&signature) else {
+                    continue;
+                };
+                let status = match status {
+                    Some(status) => SignatureStatus::from_transaction_status(status),
+                    None => {
+                        let expired = match tracked_signature.last_valid_block_height {
+                            Some(last_valid_block_height) => block_height
+                                .is_some_and(|block_height| block_height > last_valid_block_height),
+                            None => tracked_signature.tracked_at.elapsed() > config.drop_timeout,
+                        };
+                        if !expired {
+                            continue;
+                        }
+                        SignatureStatus::Dropped
+                    }
+                };
+
+                if tracked_signature.status.as_ref() != Some(&status) {
+                    tracked_signature.status = Some(status.clone());
+                    updates.push(SignatureStatusUpdate { signature, status });
+                }
+            }
+            tracked.retain(|_, tracked_signature| {
+                !tracked_signature
+                    .status
+                    .as_ref()
+                    .is_some_and(SignatureStatus::is_terminal)
+            });
+        }
+
+        for update in updates {
+            if sender.send(update).is_err() {
+                return;
+            }
+        }
+    }
+}
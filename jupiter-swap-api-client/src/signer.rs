@@ -0,0 +1,125 @@
+//! Signing of swap transactions through [`AsyncSigner`], which remote signers, HSMs and MPC
+//! services can implement without blocking the runtime.
+
+use async_trait::async_trait;
+use solana_message::VersionedMessage;
+use solana_pubkey::Pubkey;
+use solana_signature::Signature;
+use solana_signer::{Signer, SignerError};
+use solana_transaction::versioned::VersionedTransaction;
+
+use crate::{
+    recurring::{RecurringExecuteRequest, RecurringTransactionResponse},
+    swap::SwapResponse,
+    ultra::{UltraExecuteRequest, UltraOrderResponse},
+    ClientError,
+};
+
+#[async_trait]
+pub trait AsyncSigner: Send + Sync {
+    fn pubkey(&self) -> Pubkey;
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature, SignerError>;
+}
+
+/// Local signers such as `Keypair` sign synchronously
+#[async_trait]
+impl<T: Signer + Send + Sync> AsyncSigner for T {
+    fn pubkey(&self) -> Pubkey {
+        Signer::pubkey(self)
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        self.try_sign_message(message)
+    }
+}
+
+/// Signs `transaction` with `signers`, keeping the signatures of signers not provided
+///
+/// Fails with [`SignerError::KeypairPubkeyMismatch`] when a signer is not a required signer of the
+/// message.
+pub async fn sign_transaction(
+    transaction: &mut VersionedTransaction,
+    signers: &[&dyn AsyncSigner],
+) -> Result<(), ClientError> {
+    let message_data = transaction.message.serialize();
+    let num_required_signatures = usize::from(transaction.message.header().num_required_signatures);
+    transaction
+        .signatures
+        .resize(num_required_signatures, Signature::default());
+    for signer in signers {
+        let position = transaction.message.static_account_keys()[..num_required_signatures]
+            .iter()
+            .position(|key| *key == signer.pubkey())
+            .ok_or(SignerError::KeypairPubkeyMismatch)?;
+        transaction.signatures[position] = signer.sign_message(&message_data).await?;
+    }
+    Ok(())
+}
+
+/// Signs `message` with `signers`, which must include every required signer
+pub async fn sign_message(
+    message: VersionedMessage,
+    signers: &[&dyn AsyncSigner],
+) -> Result<VersionedTransaction, ClientError> {
+    let mut transaction = VersionedTransaction {
+        signatures: Vec::new(),
+        message,
+    };
+    sign_transaction(&mut transaction, signers).await?;
+    if transaction
+        .signatures
+        .iter()
+        .any(|signature| *signature == Signature::default())
+    {
+        return Err(SignerError::NotEnoughSigners.into());
+    }
+    Ok(transaction)
+}
+
+impl SwapResponse {
+    /// Decodes the swap transaction and signs it with `signers`
+    pub async fn sign(
+        &self,
+        signers: &[&dyn AsyncSigner],
+    ) -> Result<VersionedTransaction, ClientError> {
+        let mut transaction: VersionedTransaction = bincode::deserialize(&self.swap_transaction)?;
+        sign_transaction(&mut transaction, signers).await?;
+        Ok(transaction)
+    }
+}
+
+impl UltraOrderResponse {
+    /// Signs the order transaction as the taker and builds the matching execute request, `None`
+    /// when the order has no transaction because no taker was provided
+    pub async fn sign(
+        &self,
+        taker: &dyn AsyncSigner,
+    ) -> Result<Option<UltraExecuteRequest>, ClientError> {
+        let Some(transaction) = &self.transaction else {
+            return Ok(None);
+        };
+        let mut transaction: VersionedTransaction = bincode::deserialize(transaction)?;
+        sign_transaction(&mut transaction, &[taker]).await?;
+        Ok(Some(UltraExecuteRequest {
+            signed_transaction: bincode::serialize(&transaction)?,
+            request_id: self.request_id.clone(),
+            idempotency_key: None,
+        }))
+    }
+}
+
+impl RecurringTransactionResponse {
+    /// Signs the transaction as the user and builds the matching execute request
+    pub async fn sign(
+        &self,
+        user: &dyn AsyncSigner,
+    ) -> Result<RecurringExecuteRequest, ClientError> {
+        let mut transaction: VersionedTransaction = bincode::deserialize(&self.transaction)?;
+        sign_transaction(&mut transaction, &[user]).await?;
+        Ok(RecurringExecuteRequest {
+            signed_transaction: bincode::serialize(&transaction)?,
+            request_id: self.request_id.clone(),
+        })
+    }
+}
@@ -0,0 +1,178 @@
+//! Classification of swap simulation errors into the failures callers commonly react to, and
+//! simulation of swap transactions against an RPC node.
+
+use crate::swap::UiSimulationError;
+
+/// `SlippageToleranceExceeded` error of the Jupiter program
+const SLIPPAGE_TOLERANCE_EXCEEDED: &str = "0x1771";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SimulationErrorKind {
+    /// The output fell below the minimum, or the input rose above the maximum, of the quote
+    SlippageExceeded,
+    /// The wallet lacks the input tokens
+    InsufficientFunds,
+    /// The wallet lacks lamports for fees or rent
+    InsufficientLamports,
+    /// Another transaction holds a lock on one of the accounts
+    AccountInUse,
+    /// An AMM relies on an oracle price that is too old
+    StaleOracle,
+    /// The transaction ran out of compute units
+    ComputeBudgetExceeded,
+    /// The blockhash expired or is unknown to the simulating node
+    BlockhashNotFound,
+    Unknown,
+}
+
+impl SimulationErrorKind {
+    /// Classifies an error code and message (or program logs) of a failed simulation
+    pub fn parse(error_code: &str, message: &str) -> Self {
+        let message = message.to_lowercase();
+        let error_code = error_code.to_lowercase();
+        let mentions = |pattern: &str| message.contains(pattern) || error_code.contains(pattern);
+
+        if mentions(SLIPPAGE_TOLERANCE_EXCEEDED) || mentions("slippage") {
+            Self::SlippageExceeded
+        } else if mentions("insufficient lamports") || mentions("insufficientfundsforfee") {
+            Self::InsufficientLamports
+        } else if mentions("insufficient funds") || mentions("insufficientfunds") {
+            Self::InsufficientFunds
+        } else if mentions("accountinuse") || mentions("account in use") {
+            Self::AccountInUse
+        } else if mentions("oracle") && mentions("stale") {
+            Self::StaleOracle
+        } else if mentions("exceeded cus")
+            || mentions("computational budget exceeded")
+            || mentions("computebudgetexceeded")
+        {
+            Self::ComputeBudgetExceeded
+        } else if mentions("blockhashnotfound") || mentions("blockhash not found") {
+            Self::BlockhashNotFound
+        } else {
+            Self::Unknown
+        }
+    }
+
+    /// Classifies the program logs of a failed simulation
+    pub fn from_logs(logs: &[String]) -> Self {
+        logs.iter()
+            .map(|log| Self::parse("", log))
+            .find(|kind| *kind != Self::Unknown)
+            .unwrap_or(Self::Unknown)
+    }
+
+    /// Simulating again shortly may succeed without changing the request
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            Self::AccountInUse | Self::StaleOracle | Self::BlockhashNotFound
+        )
+    }
+
+    /// What the caller can do about the failure
+    pub fn remediation(&self) -> &'static str {
+        match self {
+            Self::SlippageExceeded => "quote again or increase the slippage tolerance",
+            Self::InsufficientFunds => "reduce the input amount to the available balance",
+            Self::InsufficientLamports => "keep more SOL in the wallet for fees and rent",
+            Self::AccountInUse => "retry once the conflicting transaction has landed",
+            Self::StaleOracle => "retry shortly or exclude the DEX relying on the oracle",
+            Self::ComputeBudgetExceeded => {
+                "raise the compute unit limit or restrict the route with max accounts"
+            }
+            Self::BlockhashNotFound => "build the transaction again with a recent blockhash",
+            Self::Unknown => "inspect the simulation error",
+        }
+    }
+}
+
+impl UiSimulationError {
+    pub fn error_code(&self) -> &str {
+        &self.error_code
+    }
+
+    pub fn error(&self) -> &str {
+        &self.error
+    }
+
+    pub fn kind(&self) -> SimulationErrorKind {
+        SimulationErrorKind::parse(&self.error_code, &self.error)
+    }
+}
+
+#[cfg(feature = "rpc")]
+mod rpc {
+    use solana_client::{
+        nonblocking::rpc_client::RpcClient, rpc_config::RpcSimulateTransactionConfig,
+    };
+    use solana_pubkey::Pubkey;
+    use solana_transaction::versioned::VersionedTransaction;
+    use solana_transaction_error::TransactionError;
+
+    use super::*;
+    use crate::{swap::SwapResponse, ClientError};
+
+    /// Outcome of simulating a swap transaction against an RPC node
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct SwapSimulation {
+        pub error: Option<TransactionError>,
+        pub logs: Vec<String>,
+        pub units_consumed: Option<u64>,
+        /// Compute unit limit set by the API on the transaction
+        pub compute_unit_limit: u32,
+    }
+
+    impl SwapSimulation {
+        pub fn is_success(&self) -> bool {
+            self.error.is_none()
+        }
+
+        /// Innermost program that failed, read from the `Program <id> failed` log
+        pub fn failing_program(&self) -> Option<Pubkey> {
+            self.logs.iter().find_map(|log| {
+                let (program_id, _) = log.strip_prefix("Program ")?.split_once(" failed")?;
+                program_id.parse().ok()
+            })
+        }
+
+        /// Classification of the failure, `None` when the simulation succeeded
+        pub fn error_kind(&self) -> Option<SimulationErrorKind> {
+            if self.is_success() {
+                return None;
+            }
+            Some(SimulationErrorKind::from_logs(&self.logs))
+        }
+    }
+
+    /// Simulates the unsigned swap transaction returned by `/swap`, with its compute unit limit and
+    /// a fresh blockhash, to pre-flight a swap before signing it
+    pub async fn simulate_swap_transaction(
+        swap_response: &SwapResponse,
+        rpc_client: &RpcClient,
+    ) -> Result<SwapSimulation, ClientError> {
+        let transaction: VersionedTransaction =
+            bincode::deserialize(&swap_response.swap_transaction)?;
+        let result = rpc_client
+            .simulate_transaction_with_config(
+                &transaction,
+                RpcSimulateTransactionConfig {
+                    sig_verify: false,
+                    replace_recent_blockhash: true,
+                    ..RpcSimulateTransactionConfig::default()
+                },
+            )
+            .await?
+            .value;
+        Ok(SwapSimulation {
+            error: result.err,
+            logs: result.logs.unwrap_or_default(),
+            units_consumed: result.units_consumed,
+            compute_unit_limit: swap_response.compute_unit_limit,
+        })
+    }
+}
+
+#[cfg(feature = "rpc")]
+pub use rpc::{simulate_swap_transaction, SwapSimulation};
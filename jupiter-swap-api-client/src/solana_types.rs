@@ -0,0 +1,283 @@
+//! Pubkeys, signatures, hashes and instructions exposed by the request and response types.
+//!
+//! With the default `solana` feature these are the types of the split Solana crates. Without it,
+//! they are lightweight types of this crate holding the same bytes, so the HTTP APIs can be used
+//! without depending on the Solana crates. They convert to the types of any Solana version
+//! through their bytes, e.g. `Pubkey::new_from_array(pubkey.to_bytes())`, and instructions field
+//! by field.
+
+#[cfg(feature = "solana")]
+pub use {
+    solana_account_decoder_client_types::UiAccount,
+    solana_hash::Hash,
+    solana_instruction::{AccountMeta, Instruction},
+    solana_pubkey::Pubkey,
+    solana_signature::Signature,
+};
+
+#[cfg(not(feature = "solana"))]
+pub use lightweight::{AccountMeta, Hash, Instruction, ParseError, Pubkey, Signature, UiAccount};
+
+#[cfg(not(feature = "solana"))]
+mod lightweight {
+    use std::{array::TryFromSliceError, fmt, str::FromStr};
+
+    use serde::{Deserialize, Serialize};
+    use serde_json::{Map, Value};
+    use thiserror::Error;
+
+    /// Account of a keyed UI account, as returned by the `getAccountInfo` RPC method
+    pub type UiAccount = Map<String, Value>;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Error)]
+    #[non_exhaustive]
+    pub enum ParseError {
+        #[error("Invalid base58 string")]
+        InvalidBase58,
+        #[error("Invalid length, expected {expected} bytes, got {actual}")]
+        InvalidLength { expected: usize, actual: usize },
+    }
+
+    fn decode_base58<const N: usize>(s: &str) -> Result<[u8; N], ParseError> {
+        let bytes = bs58::decode(s)
+            .into_vec()
+            .map_err(|_| ParseError::InvalidBase58)?;
+        <[u8; N]>::try_from(bytes.as_slice()).map_err(|_| ParseError::InvalidLength {
+            expected: N,
+            actual: bytes.len(),
+        })
+    }
+
+    #[derive(
+        Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+    )]
+    pub struct Pubkey([u8; 32]);
+
+    impl Pubkey {
+        pub const fn new_from_array(bytes: [u8; 32]) -> Self {
+            Self(bytes)
+        }
+
+        /// Decodes a base58 pubkey at compile time, panics when it is not valid
+        pub const fn from_str_const(s: &str) -> Self {
+            const ALPHABET: &[u8; 58] =
+                b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+            let s = s.as_bytes();
+            let mut bytes = [0u8; 32];
+            let mut i = 0;
+            while i < s.len() {
+                let mut digit = 0;
+                while digit < ALPHABET.len() && ALPHABET[digit] != s[i] {
+                    digit += 1;
+                }
+                assert!(digit < ALPHABET.len(), "invalid base58 character");
+                // Big endian multiplication of the decoded bytes by 58, plus the digit
+                let mut carry = digit as u32;
+                let mut j = bytes.len();
+                while j > 0 {
+                    j -= 1;
+                    carry += bytes[j] as u32 * 58;
+                    bytes[j] = carry as u8;
+                    carry >>= 8;
+                }
+                assert!(carry == 0, "pubkey longer than 32 bytes");
+                i += 1;
+            }
+            Self(bytes)
+        }
+
+        pub const fn to_bytes(self) -> [u8; 32] {
+            self.0
+        }
+
+        pub const fn as_array(&self) -> &[u8; 32] {
+            &self.0
+        }
+    }
+
+    impl AsRef<[u8]> for Pubkey {
+        fn as_ref(&self) -> &[u8] {
+            &self.0
+        }
+    }
+
+    impl From<[u8; 32]> for Pubkey {
+        fn from(bytes: [u8; 32]) -> Self {
+            Self(bytes)
+        }
+    }
+
+    impl TryFrom<&[u8]> for Pubkey {
+        type Error = TryFromSliceError;
+
+        fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+            <[u8; 32]>::try_from(bytes).map(Self)
+        }
+    }
+
+    impl FromStr for Pubkey {
+        type Err = ParseError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            decode_base58(s).map(Self)
+        }
+    }
+
+    impl fmt::Display for Pubkey {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(&bs58::encode(self.0).into_string())
+        }
+    }
+
+    impl fmt::Debug for Pubkey {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            fmt::Display::fmt(self, f)
+        }
+    }
+
+    #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+    pub struct Signature([u8; 64]);
+
+    impl Signature {
+        pub const fn as_array(&self) -> &[u8; 64] {
+            &self.0
+        }
+    }
+
+    impl Default for Signature {
+        fn default() -> Self {
+            Self([0; 64])
+        }
+    }
+
+    impl AsRef<[u8]> for Signature {
+        fn as_ref(&self) -> &[u8] {
+            &self.0
+        }
+    }
+
+    impl From<[u8; 64]> for Signature {
+        fn from(bytes: [u8; 64]) -> Self {
+            Self(bytes)
+        }
+    }
+
+    impl From<Signature> for [u8; 64] {
+        fn from(signature: Signature) -> Self {
+            signature.0
+        }
+    }
+
+    impl FromStr for Signature {
+        type Err = ParseError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            decode_base58(s).map(Self)
+        }
+    }
+
+    impl fmt::Display for Signature {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(&bs58::encode(self.0).into_string())
+        }
+    }
+
+    impl fmt::Debug for Signature {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            fmt::Display::fmt(self, f)
+        }
+    }
+
+    #[derive(
+        Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+    )]
+    pub struct Hash([u8; 32]);
+
+    impl Hash {
+        pub const fn new_from_array(bytes: [u8; 32]) -> Self {
+            Self(bytes)
+        }
+
+        pub const fn to_bytes(self) -> [u8; 32] {
+            self.0
+        }
+    }
+
+    impl AsRef<[u8]> for Hash {
+        fn as_ref(&self) -> &[u8] {
+            &self.0
+        }
+    }
+
+    impl FromStr for Hash {
+        type Err = ParseError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            decode_base58(s).map(Self)
+        }
+    }
+
+    impl fmt::Display for Hash {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(&bs58::encode(self.0).into_string())
+        }
+    }
+
+    impl fmt::Debug for Hash {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            fmt::Display::fmt(self, f)
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct Instruction {
+        pub program_id: Pubkey,
+        pub accounts: Vec<AccountMeta>,
+        pub data: Vec<u8>,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct AccountMeta {
+        pub pubkey: Pubkey,
+        pub is_signer: bool,
+        pub is_writable: bool,
+    }
+
+    impl AccountMeta {
+        pub fn new(pubkey: Pubkey, is_signer: bool) -> Self {
+            Self {
+                pubkey,
+                is_signer,
+                is_writable: true,
+            }
+        }
+
+        pub fn new_readonly(pubkey: Pubkey, is_signer: bool) -> Self {
+            Self {
+                pubkey,
+                is_signer,
+                is_writable: false,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pubkeys_round_trip_through_base58() {
+        for address in [
+            "11111111111111111111111111111111",
+            "So11111111111111111111111111111111111111112",
+            "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4",
+        ] {
+            let pubkey = Pubkey::from_str_const(address);
+            assert_eq!(address.parse::<Pubkey>().unwrap(), pubkey);
+            assert_eq!(pubkey.to_string(), address);
+        }
+        assert!("not base58!".parse::<Pubkey>().is_err());
+        assert!("1111".parse::<Pubkey>().is_err());
+    }
+}
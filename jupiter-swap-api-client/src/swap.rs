@@ -1,12 +1,15 @@
+use std::collections::HashSet;
+
 use crate::{
-    quote::QuoteResponse, serde_helpers::field_as_string, transaction_config::TransactionConfig,
+    quote::QuoteResponse,
+    serde_helpers::field_as_string,
+    solana_types::{AccountMeta, Instruction, Pubkey},
+    transaction_config::TransactionConfig,
+    ClientError,
 };
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use solana_sdk::{
-    instruction::{AccountMeta, Instruction},
-    pubkey::Pubkey,
-};
+use serde_json::Value;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -18,6 +21,21 @@ pub struct SwapRequest {
     pub config: TransactionConfig,
 }
 
+impl SwapRequest {
+    /// JSON body of the request, with [`TransactionConfig::extra_body`] merged over the typed fields
+    /// and the raw quote, if any, in place of the typed one
+    pub fn body(&self) -> Result<Value, ClientError> {
+        let mut body = serde_json::to_value(self)?;
+        if let Value::Object(fields) = &mut body {
+            if let Some(raw_quote_response) = &self.quote_response.raw {
+                fields.insert("quoteResponse".to_string(), raw_quote_response.clone());
+            }
+            fields.extend(self.config.extra_body.clone());
+        }
+        Ok(body)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub enum PrioritizationType {
@@ -43,8 +61,8 @@ pub struct DynamicSlippageReport {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct UiSimulationError {
-    error_code: String,
-    error: String,
+    pub(crate) error_code: String,
+    pub(crate) error: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -81,6 +99,33 @@ pub mod base64_serialize_deserialize {
     }
 }
 
+pub mod option_base64_serialize_deserialize {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use serde::{de, Deserializer, Serializer};
+
+    use super::*;
+    pub fn serialize<S: Serializer>(v: &Option<Vec<u8>>, s: S) -> Result<S::Ok, S::Error> {
+        match v {
+            Some(v) => String::serialize(&STANDARD.encode(v), s),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Vec<u8>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<String>::deserialize(deserializer)?
+            .filter(|field_string| !field_string.is_empty())
+            .map(|field_string| {
+                STANDARD
+                    .decode(field_string)
+                    .map_err(|e| de::Error::custom(format!("base64 decoding error: {:?}", e)))
+            })
+            .transpose()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SwapInstructionsResponse {
     pub token_ledger_instruction: Option<Instruction>,
@@ -100,6 +145,55 @@ pub struct SwapInstructionsResponse {
     pub simulation_error: Option<UiSimulationError>,
 }
 
+impl SwapInstructionsResponse {
+    /// Every instruction in execution order: compute budget, setup, token ledger, swap, cleanup
+    /// and other instructions
+    pub(crate) fn all_instructions(&self) -> impl Iterator<Item = &Instruction> {
+        self.ordered_instructions(true, true)
+    }
+
+    /// Same as [`Self::all_instructions`], without the compute budget and cleanup instructions
+    /// unless included
+    pub(crate) fn ordered_instructions(
+        &self,
+        include_compute_budget: bool,
+        include_cleanup: bool,
+    ) -> impl Iterator<Item = &Instruction> {
+        let compute_budget_instructions = if include_compute_budget {
+            self.compute_budget_instructions.as_slice()
+        } else {
+            &[]
+        };
+        compute_budget_instructions
+            .iter()
+            .chain(&self.setup_instructions)
+            .chain(&self.token_ledger_instruction)
+            .chain(std::iter::once(&self.swap_instruction))
+            .chain(
+                self.cleanup_instruction
+                    .as_ref()
+                    .filter(|_| include_cleanup),
+            )
+            .chain(&self.other_instructions)
+    }
+
+    /// Unique programs invoked by the returned instructions
+    pub fn program_ids(&self) -> HashSet<Pubkey> {
+        self.all_instructions()
+            .map(|instruction| instruction.program_id)
+            .collect()
+    }
+
+    /// Unique accounts write locked by the returned instructions
+    pub fn writable_accounts(&self) -> HashSet<Pubkey> {
+        self.all_instructions()
+            .flat_map(|instruction| &instruction.accounts)
+            .filter(|account_meta| account_meta.is_writable)
+            .map(|account_meta| account_meta.pubkey)
+            .collect()
+    }
+}
+
 // Duplicate for deserialization
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -0,0 +1,87 @@
+//! The Swap API as a trait, so strategies can run against the live client or another backend such
+//! as [`crate::replay::ReplayClient`], and unit tests against a fake.
+
+use std::{collections::HashMap, sync::Arc};
+
+use async_trait::async_trait;
+
+use crate::{
+    quote::{QuoteRequest, QuoteResponse},
+    solana_types::Pubkey,
+    swap::{SwapInstructionsResponse, SwapRequest, SwapResponse},
+    ClientError, JupiterSwapApiClient,
+};
+
+#[async_trait]
+pub trait SwapApi: Send + Sync {
+    async fn quote(&self, quote_request: &QuoteRequest) -> Result<QuoteResponse, ClientError>;
+
+    async fn swap(
+        &self,
+        swap_request: &SwapRequest,
+        extra_args: Option<HashMap<String, String>>,
+    ) -> Result<SwapResponse, ClientError>;
+
+    async fn swap_instructions(
+        &self,
+        swap_request: &SwapRequest,
+    ) -> Result<SwapInstructionsResponse, ClientError>;
+
+    /// Program id to DEX label map, backends without one serve an empty map
+    async fn program_id_to_label(&self) -> Result<HashMap<Pubkey, String>, ClientError> {
+        Ok(HashMap::new())
+    }
+}
+
+/// Lets a backend be shared or injected as `Arc<dyn SwapApi>`
+#[async_trait]
+impl<A: SwapApi + ?Sized> SwapApi for Arc<A> {
+    async fn quote(&self, quote_request: &QuoteRequest) -> Result<QuoteResponse, ClientError> {
+        (**self).quote(quote_request).await
+    }
+
+    async fn swap(
+        &self,
+        swap_request: &SwapRequest,
+        extra_args: Option<HashMap<String, String>>,
+    ) -> Result<SwapResponse, ClientError> {
+        (**self).swap(swap_request, extra_args).await
+    }
+
+    async fn swap_instructions(
+        &self,
+        swap_request: &SwapRequest,
+    ) -> Result<SwapInstructionsResponse, ClientError> {
+        (**self).swap_instructions(swap_request).await
+    }
+
+    async fn program_id_to_label(&self) -> Result<HashMap<Pubkey, String>, ClientError> {
+        (**self).program_id_to_label().await
+    }
+}
+
+#[async_trait]
+impl SwapApi for JupiterSwapApiClient {
+    async fn quote(&self, quote_request: &QuoteRequest) -> Result<QuoteResponse, ClientError> {
+        JupiterSwapApiClient::quote(self, quote_request).await
+    }
+
+    async fn swap(
+        &self,
+        swap_request: &SwapRequest,
+        extra_args: Option<HashMap<String, String>>,
+    ) -> Result<SwapResponse, ClientError> {
+        JupiterSwapApiClient::swap(self, swap_request, extra_args).await
+    }
+
+    async fn swap_instructions(
+        &self,
+        swap_request: &SwapRequest,
+    ) -> Result<SwapInstructionsResponse, ClientError> {
+        JupiterSwapApiClient::swap_instructions(self, swap_request).await
+    }
+
+    async fn program_id_to_label(&self) -> Result<HashMap<Pubkey, String>, ClientError> {
+        JupiterSwapApiClient::program_id_to_label(self).await
+    }
+}
@@ -0,0 +1,171 @@
+//! Reuse of `/swap-instructions` responses for high-frequency flows on a fixed pair: when a new
+//! quote follows the same route, the instructions are rebuilt locally with its amounts instead of
+//! being requested again.
+
+use std::{
+    collections::HashMap,
+    sync::{Mutex, PoisonError},
+};
+
+use crate::{
+    quote::{QuoteResponse, SwapMode},
+    solana_types::{Instruction, Pubkey},
+    swap::{SwapInstructionsResponse, SwapRequest},
+    transaction_config::TransactionConfig,
+    ClientError, JupiterSwapApiClient, SYSTEM_PROGRAM_ID,
+};
+
+/// Size of the trailing arguments of the Jupiter route instructions: the fixed amount, the quoted
+/// amount on the other side, the slippage bps and the platform fee bps
+const ROUTE_ARGS_SIZE: usize = 8 + 8 + 2 + 1;
+
+/// Discriminant of the system program transfer instruction
+const SYSTEM_TRANSFER_DISCRIMINANT: u32 = 2;
+
+/// What the account layout of a swap depends on: the route hops, their split and the config
+#[derive(Clone, Debug, PartialEq)]
+struct RouteShape {
+    user_public_key: Pubkey,
+    swap_mode: SwapMode,
+    /// `(amm key, input mint, output mint, percent)` of every hop
+    hops: Vec<(Pubkey, Pubkey, Pubkey, u8)>,
+    config: TransactionConfig,
+}
+
+impl RouteShape {
+    fn new(swap_request: &SwapRequest) -> Self {
+        Self {
+            user_public_key: swap_request.user_public_key,
+            swap_mode: swap_request.quote_response.swap_mode.clone(),
+            hops: swap_request
+                .quote_response
+                .route_plan
+                .iter()
+                .map(|step| {
+                    (
+                        step.swap_info.amm_key,
+                        step.swap_info.input_mint,
+                        step.swap_info.output_mint,
+                        step.percent,
+                    )
+                })
+                .collect(),
+            config: TransactionConfig {
+                idempotency_key: None,
+                ..swap_request.config.clone()
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct CachedSwapInstructions {
+    route_shape: RouteShape,
+    quote_response: QuoteResponse,
+    swap_instructions: SwapInstructionsResponse,
+}
+
+/// Cached `/swap-instructions` responses, one per user and pair
+///
+/// Instructions are only rebuilt when the new quote follows exactly the same hops with the same
+/// split and the transaction config is unchanged. The compute unit limit and the dynamic slippage
+/// report of the cached response are kept as is.
+#[derive(Debug, Default)]
+pub struct SwapInstructionsCache {
+    entries: Mutex<HashMap<(Pubkey, Pubkey, Pubkey), CachedSwapInstructions>>,
+}
+
+impl SwapInstructionsCache {
+    fn key(swap_request: &SwapRequest) -> (Pubkey, Pubkey, Pubkey) {
+        (
+            swap_request.user_public_key,
+            swap_request.quote_response.input_mint,
+            swap_request.quote_response.output_mint,
+        )
+    }
+
+    /// Instructions of `swap_request` rebuilt from the cached response, `None` when the route
+    /// changed or nothing is cached for the pair
+    pub fn get(&self, swap_request: &SwapRequest) -> Option<SwapInstructionsResponse> {
+        let entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        let cached = entries.get(&Self::key(swap_request))?;
+        if cached.route_shape != RouteShape::new(swap_request) {
+            return None;
+        }
+        rebuild(cached, &swap_request.quote_response)
+    }
+
+    pub fn insert(&self, swap_request: &SwapRequest, swap_instructions: &SwapInstructionsResponse) {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(
+                Self::key(swap_request),
+                CachedSwapInstructions {
+                    route_shape: RouteShape::new(swap_request),
+                    quote_response: swap_request.quote_response.clone(),
+                    swap_instructions: swap_instructions.clone(),
+                },
+            );
+    }
+
+    pub fn clear(&self) {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
+
+/// Patches the amounts of the cached instructions with those of `quote_response`
+fn rebuild(
+    cached: &CachedSwapInstructions,
+    quote_response: &QuoteResponse,
+) -> Option<SwapInstructionsResponse> {
+    let mut swap_instructions = cached.swap_instructions.clone();
+    let data = &mut swap_instructions.swap_instruction.data;
+    let args_start = data.len().checked_sub(ROUTE_ARGS_SIZE)?;
+    let (fixed_amount, quoted_amount) = match quote_response.swap_mode {
+        SwapMode::ExactIn => (quote_response.in_amount, quote_response.out_amount),
+        SwapMode::ExactOut => (quote_response.out_amount, quote_response.in_amount),
+    };
+    data[args_start..args_start + 8].copy_from_slice(&fixed_amount.to_le_bytes());
+    data[args_start + 8..args_start + 16].copy_from_slice(&quoted_amount.to_le_bytes());
+    data[args_start + 16..args_start + 18]
+        .copy_from_slice(&quote_response.slippage_bps.to_le_bytes());
+
+    // SOL is wrapped with a transfer of the input amount
+    for instruction in &mut swap_instructions.setup_instructions {
+        if is_transfer_of(instruction, cached.quote_response.in_amount) {
+            instruction.data[4..12].copy_from_slice(&quote_response.in_amount.to_le_bytes());
+        }
+    }
+    Some(swap_instructions)
+}
+
+fn is_transfer_of(instruction: &Instruction, lamports: u64) -> bool {
+    instruction.program_id == SYSTEM_PROGRAM_ID
+        && instruction.data.len() == 12
+        && instruction.data[..4] == SYSTEM_TRANSFER_DISCRIMINANT.to_le_bytes()
+        && instruction.data[4..12] == lamports.to_le_bytes()
+}
+
+impl JupiterSwapApiClient {
+    /// Same as [`Self::swap_instructions`], rebuilding the instructions from `cache` when the
+    /// route of the quote is unchanged, saving the round trip
+    pub async fn swap_instructions_cached(
+        &self,
+        cache: &SwapInstructionsCache,
+        swap_request: &SwapRequest,
+    ) -> Result<SwapInstructionsResponse, ClientError> {
+        self.in_flight(async {
+            if let Some(swap_instructions) = cache.get(swap_request) {
+                return Ok(swap_instructions);
+            }
+            let swap_instructions = self.swap_instructions(swap_request).await?;
+            cache.insert(swap_request, &swap_instructions);
+            Ok(swap_instructions)
+        })
+        .await
+    }
+}
@@ -0,0 +1,95 @@
+//! Centralized risk checks run by the client before and after building every swap, see
+//! [`crate::JupiterSwapApiClient::with_swap_policy`].
+
+use std::collections::HashSet;
+
+use crate::{
+    dex::Dex,
+    quote::QuoteResponse,
+    quote_policy::{PolicyViolation, QuotePolicy},
+    solana_types::Pubkey,
+    swap::{SwapRequest, SwapResponse},
+};
+
+/// Check of the quotes swaps are built from and of the swap transactions returned by the API
+pub trait SwapPolicy: Send + Sync {
+    /// Called before `/swap` and `/swap-instructions` with the quote of the request
+    fn validate_quote(&self, _quote_response: &QuoteResponse) -> Result<(), PolicyViolation> {
+        Ok(())
+    }
+
+    /// Called with the response of `/swap` before it is returned
+    fn validate_swap_response(
+        &self,
+        _swap_request: &SwapRequest,
+        _swap_response: &SwapResponse,
+    ) -> Result<(), PolicyViolation> {
+        Ok(())
+    }
+}
+
+/// Price impact and slippage limits
+impl SwapPolicy for QuotePolicy {
+    fn validate_quote(&self, quote_response: &QuoteResponse) -> Result<(), PolicyViolation> {
+        match self.violation(quote_response) {
+            Some(violation) => Err(violation),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Rejects routes going through a DEX outside of the allowlist
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DexAllowlist(pub HashSet<Dex>);
+
+impl SwapPolicy for DexAllowlist {
+    fn validate_quote(&self, quote_response: &QuoteResponse) -> Result<(), PolicyViolation> {
+        match quote_response
+            .route_plan
+            .iter()
+            .map(|step| Dex::from(step.swap_info.label.as_str()))
+            .find(|dex| !self.0.contains(dex))
+        {
+            Some(dex) => Err(PolicyViolation::DexNotAllowed { dex }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Rejects swaps whose input or output mint is outside of the allowlist, intermediate mints of the
+/// route are not checked
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MintAllowlist(pub HashSet<Pubkey>);
+
+impl SwapPolicy for MintAllowlist {
+    fn validate_quote(&self, quote_response: &QuoteResponse) -> Result<(), PolicyViolation> {
+        match [quote_response.input_mint, quote_response.output_mint]
+            .into_iter()
+            .find(|mint| !self.0.contains(mint))
+        {
+            Some(mint) => Err(PolicyViolation::MintNotAllowed { mint }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Rejects swap transactions paying more than `0` lamports of prioritization fee, Jito tip
+/// included, the base fee per signature is not counted
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MaxFeeLamports(pub u64);
+
+impl SwapPolicy for MaxFeeLamports {
+    fn validate_swap_response(
+        &self,
+        _swap_request: &SwapRequest,
+        swap_response: &SwapResponse,
+    ) -> Result<(), PolicyViolation> {
+        if swap_response.prioritization_fee_lamports > self.0 {
+            return Err(PolicyViolation::FeeTooHigh {
+                fee_lamports: swap_response.prioritization_fee_lamports,
+                max_fee_lamports: self.0,
+            });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,236 @@
+//! In-process mock of the Jupiter APIs for integration tests that must not depend on the network,
+//! see [`MockJupiterApi`].
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{Method, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    Json, Router,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Value};
+use solana_hash::Hash;
+use solana_instruction::{AccountMeta, Instruction};
+use solana_message::{v0, VersionedMessage};
+use solana_pubkey::Pubkey;
+use solana_signature::Signature;
+use solana_transaction::versioned::VersionedTransaction;
+use tokio::{net::TcpListener, task::JoinHandle};
+
+use crate::{JupiterSwapApiClient, SYSTEM_PROGRAM_ID};
+
+/// A request received by a [`MockJupiterApi`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReceivedRequest {
+    pub method: Method,
+    pub path: String,
+    pub query: HashMap<String, String>,
+    /// `None` when the body is empty or not JSON
+    pub body: Option<Value>,
+}
+
+#[derive(Default)]
+struct MockState {
+    responses: HashMap<String, (StatusCode, Value)>,
+    received_requests: Vec<ReceivedRequest>,
+}
+
+/// HTTP server on a random local port serving canned `/quote`, `/swap` and `/swap-instructions`
+/// responses, the server stops when dropped
+///
+/// The default quote echoes the requested mints and amount at a 1:1 price, the swap transaction
+/// and swap instruction transfer 0 lamports from the user to itself. Any path, including the
+/// defaults, can be answered with another response through [`Self::respond_with`].
+pub struct MockJupiterApi {
+    base_path: String,
+    state: Arc<Mutex<MockState>>,
+    server: JoinHandle<()>,
+}
+
+impl MockJupiterApi {
+    pub async fn start() -> std::io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let base_path = format!("http://{}", listener.local_addr()?);
+        let state = Arc::new(Mutex::new(MockState::default()));
+        let router = Router::new().fallback(handle).with_state(state.clone());
+        let server = tokio::spawn(async move {
+            let _ = axum::serve(listener, router).await;
+        });
+        Ok(Self {
+            base_path,
+            state,
+            server,
+        })
+    }
+
+    pub fn base_path(&self) -> &str {
+        &self.base_path
+    }
+
+    /// Client sending the requests of every API to the mock
+    pub fn client(&self) -> JupiterSwapApiClient {
+        let client = JupiterSwapApiClient::new(self.base_path.clone());
+        client.update_config(|config| {
+            config.ultra_base_path = self.base_path.clone();
+            config.tokens_base_path = self.base_path.clone();
+            config.recurring_base_path = self.base_path.clone();
+        });
+        client
+    }
+
+    /// Answers every later request to `path` with `status` and the JSON `body`
+    pub fn respond_with(&self, path: &str, status: StatusCode, body: Value) {
+        self.lock()
+            .responses
+            .insert(path.to_string(), (status, body));
+    }
+
+    /// Requests received so far, oldest first
+    pub fn received_requests(&self) -> Vec<ReceivedRequest> {
+        self.lock().received_requests.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Drop for MockJupiterApi {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+async fn handle(
+    State(state): State<Arc<Mutex<MockState>>>,
+    method: Method,
+    uri: Uri,
+    body: Bytes,
+) -> Response {
+    let request = ReceivedRequest {
+        method,
+        path: uri.path().to_string(),
+        query: uri
+            .query()
+            .and_then(|query| serde_qs::from_str(query).ok())
+            .unwrap_or_default(),
+        body: serde_json::from_slice(&body).ok(),
+    };
+    let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+    state.received_requests.push(request.clone());
+    let (status, body) = match state.responses.get(&request.path) {
+        Some(response) => response.clone(),
+        None => default_response(&request),
+    };
+    (status, Json(body)).into_response()
+}
+
+fn default_response(request: &ReceivedRequest) -> (StatusCode, Value) {
+    let user = || {
+        request
+            .body
+            .as_ref()
+            .and_then(|body| body["userPublicKey"].as_str()?.parse().ok())
+            .unwrap_or_default()
+    };
+    match request.path.as_str() {
+        "/quote" => (StatusCode::OK, quote_response(&request.query)),
+        "/swap" => (StatusCode::OK, swap_response(&user())),
+        "/swap-instructions" => (StatusCode::OK, swap_instructions_response(&user())),
+        _ => (
+            StatusCode::NOT_FOUND,
+            json!({ "error": format!("No mock response for {}", request.path) }),
+        ),
+    }
+}
+
+fn quote_response(query: &HashMap<String, String>) -> Value {
+    let param = |name: &str| query.get(name).cloned().unwrap_or_default();
+    let amount = param("amount");
+    json!({
+        "inputMint": param("inputMint"),
+        "inAmount": amount,
+        "outputMint": param("outputMint"),
+        "outAmount": amount,
+        "otherAmountThreshold": amount,
+        "swapMode": query.get("swapMode").map_or("ExactIn", String::as_str),
+        "slippageBps": query
+            .get("slippageBps")
+            .and_then(|slippage_bps| slippage_bps.parse::<u16>().ok())
+            .unwrap_or(50),
+        "platformFee": null,
+        "priceImpactPct": "0",
+        "routePlan": [{
+            "swapInfo": {
+                "ammKey": Pubkey::default().to_string(),
+                "label": "Mock",
+                "inputMint": param("inputMint"),
+                "outputMint": param("outputMint"),
+                "inAmount": amount,
+                "outAmount": amount,
+            },
+            "percent": 100,
+        }],
+    })
+}
+
+fn swap_instruction(user: &Pubkey) -> Instruction {
+    // SystemInstruction::Transfer { lamports: 0 }
+    let mut data = 2u32.to_le_bytes().to_vec();
+    data.extend(0u64.to_le_bytes());
+    Instruction::new_with_bytes(
+        SYSTEM_PROGRAM_ID,
+        &data,
+        vec![
+            AccountMeta::new(*user, true),
+            AccountMeta::new(*user, false),
+        ],
+    )
+}
+
+fn swap_response(user: &Pubkey) -> Value {
+    let message = v0::Message::try_compile(user, &[swap_instruction(user)], &[], Hash::default())
+        .expect("a single instruction message compiles");
+    let transaction = VersionedTransaction {
+        signatures: vec![Signature::default()],
+        message: VersionedMessage::V0(message),
+    };
+    let transaction = bincode::serialize(&transaction).expect("transactions serialize");
+    json!({
+        "swapTransaction": STANDARD.encode(transaction),
+        "lastValidBlockHeight": 0,
+        "prioritizationFeeLamports": 0,
+        "computeUnitLimit": 200_000,
+    })
+}
+
+fn swap_instructions_response(user: &Pubkey) -> Value {
+    let instruction = swap_instruction(user);
+    json!({
+        "computeBudgetInstructions": [],
+        "setupInstructions": [],
+        "swapInstruction": {
+            "programId": instruction.program_id.to_string(),
+            "accounts": instruction
+                .accounts
+                .iter()
+                .map(|account| json!({
+                    "pubkey": account.pubkey.to_string(),
+                    "isSigner": account.is_signer,
+                    "isWritable": account.is_writable,
+                }))
+                .collect::<Vec<_>>(),
+            "data": STANDARD.encode(&instruction.data),
+        },
+        "otherInstructions": [],
+        "addressLookupTableAddresses": [],
+        "prioritizationFeeLamports": 0,
+        "computeUnitLimit": 200_000,
+    })
+}
@@ -0,0 +1,407 @@
+//! Detection of Token-2022 mint extensions that commonly make swaps fail or behave unexpectedly.
+
+use std::collections::HashMap;
+
+use crate::{
+    quote::{QuoteResponse, SwapMode},
+    solana_types::Pubkey,
+};
+
+pub const TOKEN_2022_PROGRAM_ID: Pubkey =
+    Pubkey::from_str_const("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+/// Mints are padded to the size of a token account so both can be told apart by their account type
+const BASE_ACCOUNT_LENGTH: usize = 165;
+const MINT_ACCOUNT_TYPE: u8 = 1;
+
+const TRANSFER_FEE_CONFIG: u16 = 1;
+const CONFIDENTIAL_TRANSFER_MINT: u16 = 4;
+const DEFAULT_ACCOUNT_STATE: u16 = 6;
+const NON_TRANSFERABLE: u16 = 9;
+const PERMANENT_DELEGATE: u16 = 12;
+const TRANSFER_HOOK: u16 = 14;
+const PAUSABLE: u16 = 26;
+
+/// Account state value of a frozen token account
+const FROZEN: u8 = 2;
+
+/// Offset of the older transfer fee in the transfer fee config, after both authorities and the
+/// withheld amount
+const OLDER_TRANSFER_FEE_OFFSET: usize = 32 + 32 + 8;
+const TRANSFER_FEE_LENGTH: usize = 8 + 8 + 2;
+
+const MAX_FEE_BASIS_POINTS: u128 = 10_000;
+
+/// Transfer fee of a Token-2022 mint, effective from `epoch`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TransferFee {
+    pub epoch: u64,
+    pub maximum_fee: u64,
+    pub basis_points: u16,
+}
+
+impl TransferFee {
+    fn parse(bytes: &[u8]) -> Option<Self> {
+        Some(Self {
+            epoch: u64::from_le_bytes(bytes.get(..8)?.try_into().ok()?),
+            maximum_fee: u64::from_le_bytes(bytes.get(8..16)?.try_into().ok()?),
+            basis_points: u16::from_le_bytes(bytes.get(16..18)?.try_into().ok()?),
+        })
+    }
+
+    /// Fee withheld when transferring `amount`, rounded up as the token program does
+    pub fn fee(&self, amount: u64) -> u64 {
+        if self.basis_points == 0 || amount == 0 {
+            return 0;
+        }
+        let fee =
+            (u128::from(amount) * u128::from(self.basis_points)).div_ceil(MAX_FEE_BASIS_POINTS);
+        u64::try_from(fee).unwrap_or(u64::MAX).min(self.maximum_fee)
+    }
+}
+
+/// Token-2022 mint extension relevant to swaps
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MintExtension {
+    /// Every transfer withholds a fee, `newer` applies from its epoch on, `older` before
+    TransferFee {
+        older: TransferFee,
+        newer: TransferFee,
+    },
+    /// Every transfer invokes `program_id`, which may reject or fail the swap
+    TransferHook { program_id: Option<Pubkey> },
+    /// Balances can be made confidential, only the public balance can be swapped
+    ConfidentialTransferMint,
+    /// `delegate` can transfer or burn tokens out of any account at any time
+    PermanentDelegate { delegate: Option<Pubkey> },
+    /// Tokens cannot be transferred at all
+    NonTransferable,
+    /// New token accounts are created frozen when `frozen` is set
+    DefaultAccountState { frozen: bool },
+    /// Transfers fail while `paused` is set
+    Pausable { paused: bool },
+    /// Any other extension, by extension type
+    Other(u16),
+}
+
+impl MintExtension {
+    /// Swaps involving the mint are expected to fail
+    pub fn is_incompatible(&self) -> bool {
+        matches!(
+            self,
+            Self::NonTransferable
+                | Self::DefaultAccountState { frozen: true }
+                | Self::Pausable { paused: true }
+        )
+    }
+
+    /// Swaps involving the mint may fail or carry extra risk
+    pub fn is_warning(&self) -> bool {
+        self.is_incompatible()
+            || matches!(
+                self,
+                Self::TransferHook { .. }
+                    | Self::ConfidentialTransferMint
+                    | Self::PermanentDelegate { .. }
+            )
+            || self
+                .transfer_fee(u64::MAX)
+                .is_some_and(|fee| fee.basis_points > 0)
+    }
+
+    /// Transfer fee in effect at `epoch`, `None` for other extensions
+    pub fn transfer_fee(&self, epoch: u64) -> Option<TransferFee> {
+        match self {
+            Self::TransferFee { newer, .. } if epoch >= newer.epoch => Some(*newer),
+            Self::TransferFee { older, .. } => Some(*older),
+            _ => None,
+        }
+    }
+}
+
+fn optional_pubkey(bytes: &[u8]) -> Option<Pubkey> {
+    let pubkey = Pubkey::try_from(bytes.get(..32)?).ok()?;
+    (pubkey != Pubkey::default()).then_some(pubkey)
+}
+
+/// Parses the extensions of a mint account, empty for accounts not owned by Token-2022 or without extensions
+pub fn parse_mint_extensions(owner: &Pubkey, data: &[u8]) -> Vec<MintExtension> {
+    if *owner != TOKEN_2022_PROGRAM_ID || data.get(BASE_ACCOUNT_LENGTH) != Some(&MINT_ACCOUNT_TYPE)
+    {
+        return Vec::new();
+    }
+
+    let mut extensions = Vec::new();
+    let mut tlv_data = &data[BASE_ACCOUNT_LENGTH + 1..];
+    while tlv_data.len() >= 4 {
+        let extension_type = u16::from_le_bytes([tlv_data[0], tlv_data[1]]);
+        let length = usize::from(u16::from_le_bytes([tlv_data[2], tlv_data[3]]));
+        let Some(value) = tlv_data.get(4..4 + length) else {
+            break;
+        };
+        // Uninitialized marks the end of the extensions
+        if extension_type == 0 {
+            break;
+        }
+        extensions.push(match extension_type {
+            TRANSFER_FEE_CONFIG => {
+                let transfer_fee = |offset: usize| {
+                    value
+                        .get(offset..offset + TRANSFER_FEE_LENGTH)
+                        .and_then(TransferFee::parse)
+                        .unwrap_or_default()
+                };
+                MintExtension::TransferFee {
+                    older: transfer_fee(OLDER_TRANSFER_FEE_OFFSET),
+                    newer: transfer_fee(OLDER_TRANSFER_FEE_OFFSET + TRANSFER_FEE_LENGTH),
+                }
+            }
+            TRANSFER_HOOK => MintExtension::TransferHook {
+                program_id: value.get(32..).and_then(optional_pubkey),
+            },
+            CONFIDENTIAL_TRANSFER_MINT => MintExtension::ConfidentialTransferMint,
+            PERMANENT_DELEGATE => MintExtension::PermanentDelegate {
+                delegate: optional_pubkey(value),
+            },
+            NON_TRANSFERABLE => MintExtension::NonTransferable,
+            DEFAULT_ACCOUNT_STATE => MintExtension::DefaultAccountState {
+                frozen: value.first() == Some(&FROZEN),
+            },
+            PAUSABLE => MintExtension::Pausable {
+                paused: value.get(32).is_some_and(|&paused| paused != 0),
+            },
+            other => MintExtension::Other(other),
+        });
+        tlv_data = &tlv_data[4 + length..];
+    }
+    extensions
+}
+
+/// Input, output and intermediate mints traded through by a quote
+pub(crate) fn quote_mints(quote_response: &QuoteResponse) -> Vec<Pubkey> {
+    let mut mints = vec![quote_response.input_mint, quote_response.output_mint];
+    for step in &quote_response.route_plan {
+        for mint in [step.swap_info.input_mint, step.swap_info.output_mint] {
+            if !mints.contains(&mint) {
+                mints.push(mint);
+            }
+        }
+    }
+    mints
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MintWarning {
+    pub mint: Pubkey,
+    pub extension: MintExtension,
+}
+
+/// Warnings about the mints a quote trades through
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TokenExtensionReport {
+    pub warnings: Vec<MintWarning>,
+}
+
+impl TokenExtensionReport {
+    /// Checks the input, output and intermediate mints of `quote_response` against their known extensions
+    pub fn for_quote(
+        quote_response: &QuoteResponse,
+        mint_extensions: &HashMap<Pubkey, Vec<MintExtension>>,
+    ) -> Self {
+        Self::for_mints(&quote_mints(quote_response), mint_extensions)
+    }
+
+    pub fn for_mints(
+        mints: &[Pubkey],
+        mint_extensions: &HashMap<Pubkey, Vec<MintExtension>>,
+    ) -> Self {
+        let warnings = mints
+            .iter()
+            .flat_map(|mint| {
+                mint_extensions
+                    .get(mint)
+                    .into_iter()
+                    .flatten()
+                    .filter(|extension| extension.is_warning())
+                    .map(|extension| MintWarning {
+                        mint: *mint,
+                        extension: extension.clone(),
+                    })
+            })
+            .collect();
+        Self { warnings }
+    }
+
+    pub fn incompatible(&self) -> impl Iterator<Item = &MintWarning> {
+        self.warnings
+            .iter()
+            .filter(|warning| warning.extension.is_incompatible())
+    }
+
+    /// No mint is expected to make the swap fail, warnings may remain
+    pub fn is_compatible(&self) -> bool {
+        self.incompatible().next().is_none()
+    }
+}
+
+/// Transfer fee of `mint` at `epoch`, `None` when it has no transfer fee extension
+fn mint_transfer_fee(
+    mint: &Pubkey,
+    mint_extensions: &HashMap<Pubkey, Vec<MintExtension>>,
+    epoch: u64,
+) -> Option<TransferFee> {
+    mint_extensions
+        .get(mint)?
+        .iter()
+        .find_map(|extension| extension.transfer_fee(epoch))
+}
+
+/// Amounts of a quote once the transfer fees of its input and output mints are withheld
+///
+/// Fees withheld on intermediate mints are not accounted for, they show up in the quote itself.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TransferTaxReport {
+    /// Withheld on the input transferred out of the user account
+    pub input_fee: u64,
+    /// Withheld on the output transferred into the user account
+    pub output_fee: u64,
+    /// Output actually received for the quoted output
+    pub expected_out_amount: u64,
+    /// Output actually received at the slippage threshold of an `ExactIn` quote
+    pub min_out_amount: Option<u64>,
+}
+
+impl TransferTaxReport {
+    /// Applies the transfer fees in effect at `epoch` to the amounts of `quote_response`
+    pub fn for_quote(
+        quote_response: &QuoteResponse,
+        mint_extensions: &HashMap<Pubkey, Vec<MintExtension>>,
+        epoch: u64,
+    ) -> Self {
+        let input_fee = mint_transfer_fee(&quote_response.input_mint, mint_extensions, epoch)
+            .map_or(0, |fee| fee.fee(quote_response.in_amount));
+        let output_transfer_fee =
+            mint_transfer_fee(&quote_response.output_mint, mint_extensions, epoch);
+        // A malformed fee config can withhold more than the transferred amount
+        let received = |amount: u64| {
+            amount.saturating_sub(output_transfer_fee.map_or(0, |fee| fee.fee(amount)))
+        };
+        Self {
+            input_fee,
+            output_fee: quote_response
+                .out_amount
+                .saturating_sub(received(quote_response.out_amount)),
+            expected_out_amount: received(quote_response.out_amount),
+            min_out_amount: (quote_response.swap_mode == SwapMode::ExactIn)
+                .then(|| received(quote_response.other_amount_threshold)),
+        }
+    }
+
+    pub fn is_taxed(&self) -> bool {
+        self.input_fee > 0 || self.output_fee > 0
+    }
+}
+
+#[cfg(feature = "rpc")]
+mod rpc {
+    use solana_client::nonblocking::rpc_client::RpcClient;
+
+    use super::*;
+    use crate::ClientError;
+
+    /// Maximum number of accounts accepted by a single `getMultipleAccounts` call
+    const MAX_ACCOUNTS_PER_REQUEST: usize = 100;
+
+    /// Fetches `mints` and parses their extensions, missing accounts are omitted from the result
+    pub async fn fetch_mint_extensions(
+        rpc_client: &RpcClient,
+        mints: &[Pubkey],
+    ) -> Result<HashMap<Pubkey, Vec<MintExtension>>, ClientError> {
+        let mut mint_extensions = HashMap::with_capacity(mints.len());
+        for chunk in mints.chunks(MAX_ACCOUNTS_PER_REQUEST) {
+            let accounts = rpc_client.get_multiple_accounts(chunk).await?;
+            for (mint, account) in chunk.iter().zip(accounts) {
+                if let Some(account) = account {
+                    mint_extensions
+                        .insert(*mint, parse_mint_extensions(&account.owner, &account.data));
+                }
+            }
+        }
+        Ok(mint_extensions)
+    }
+
+    /// Fetches the extensions of every mint traded through by `quote_response` and reports on them
+    pub async fn check_quote(
+        rpc_client: &RpcClient,
+        quote_response: &QuoteResponse,
+    ) -> Result<TokenExtensionReport, ClientError> {
+        let mints = quote_mints(quote_response);
+        let mint_extensions = fetch_mint_extensions(rpc_client, &mints).await?;
+        Ok(TokenExtensionReport::for_quote(
+            quote_response,
+            &mint_extensions,
+        ))
+    }
+
+    /// Fetches the input and output mints of `quote_response` and applies their transfer fees
+    /// in effect at the current epoch
+    pub async fn check_transfer_tax(
+        rpc_client: &RpcClient,
+        quote_response: &QuoteResponse,
+    ) -> Result<TransferTaxReport, ClientError> {
+        let mint_extensions = fetch_mint_extensions(
+            rpc_client,
+            &[quote_response.input_mint, quote_response.output_mint],
+        )
+        .await?;
+        let epoch = rpc_client.get_epoch_info().await?.epoch;
+        Ok(TransferTaxReport::for_quote(
+            quote_response,
+            &mint_extensions,
+            epoch,
+        ))
+    }
+}
+
+#[cfg(feature = "rpc")]
+pub use rpc::{check_quote, check_transfer_tax, fetch_mint_extensions};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn out_of_range_transfer_fees_withhold_at_most_the_amount() {
+        let quote_response: QuoteResponse = serde_json::from_value(serde_json::json!({
+            "inputMint": "So11111111111111111111111111111111111111112",
+            "inAmount": "1000000",
+            "outputMint": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+            "outAmount": "1000000",
+            "otherAmountThreshold": "995000",
+            "swapMode": "ExactIn",
+            "slippageBps": 50,
+            "platformFee": null,
+            "priceImpactPct": "0",
+            "routePlan": [],
+        }))
+        .unwrap();
+        let transfer_fee = TransferFee {
+            epoch: 0,
+            maximum_fee: u64::MAX,
+            basis_points: 20_000,
+        };
+        let mint_extensions = HashMap::from([(
+            quote_response.output_mint,
+            vec![MintExtension::TransferFee {
+                older: transfer_fee,
+                newer: transfer_fee,
+            }],
+        )]);
+
+        let report = TransferTaxReport::for_quote(&quote_response, &mint_extensions, 0);
+        assert_eq!(report.output_fee, 1_000_000);
+        assert_eq!(report.expected_out_amount, 0);
+        assert_eq!(report.min_out_amount, Some(0));
+    }
+}
@@ -0,0 +1,94 @@
+//! Token API: metadata of the tokens known to Jupiter, for symbol resolution and decimal-aware
+//! amount handling.
+
+use std::collections::HashMap;
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    serde_helpers::{field_as_string, option_field_as_string},
+    solana_types::Pubkey,
+    ClientError, JupiterSwapApiClient,
+};
+
+pub const DEFAULT_TOKENS_BASE_PATH: &str = "https://lite-api.jup.ag/tokens/v1";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TokenInfo {
+    #[serde(with = "field_as_string")]
+    pub address: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    #[serde(rename = "logoURI")]
+    pub logo_uri: Option<String>,
+    /// e.g. `verified`, `strict`, `community`, `lst`
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub daily_volume: Option<f64>,
+    #[serde(with = "option_field_as_string", default)]
+    pub freeze_authority: Option<Pubkey>,
+    #[serde(with = "option_field_as_string", default)]
+    pub mint_authority: Option<Pubkey>,
+    #[serde(with = "option_field_as_string", default)]
+    pub permanent_delegate: Option<Pubkey>,
+    /// e.g. the `coingeckoId`
+    #[serde(default)]
+    pub extensions: HashMap<String, Value>,
+}
+
+impl TokenInfo {
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|token_tag| token_tag == tag)
+    }
+
+    /// `amount` in whole tokens
+    pub fn ui_amount(&self, amount: u64) -> Decimal {
+        Decimal::from_i128_with_scale(i128::from(amount), u32::from(self.decimals))
+    }
+
+    /// `ui_amount` in base units, `None` when negative or too large
+    pub fn amount(&self, ui_amount: Decimal) -> Option<u64> {
+        ui_amount
+            .checked_mul(Decimal::from(10u64.checked_pow(u32::from(self.decimals))?))?
+            .trunc()
+            .try_into()
+            .ok()
+    }
+}
+
+impl JupiterSwapApiClient {
+    /// GET /token/{mint}, `None` for mints unknown to Jupiter
+    pub async fn token(&self, mint: &Pubkey) -> Result<Option<TokenInfo>, ClientError> {
+        self.in_flight(self.send_to(
+            |config| config.tokens_base_path.clone(),
+            &format!("/token/{mint}"),
+            |client, url| client.get(url),
+        ))
+        .await
+    }
+
+    /// GET /tagged/{tags}, tokens carrying any of `tags`, e.g. `["verified"]`
+    pub async fn tokens(&self, tags: &[&str]) -> Result<Vec<TokenInfo>, ClientError> {
+        self.in_flight(self.send_to(
+            |config| config.tokens_base_path.clone(),
+            &format!("/tagged/{}", tags.join(",")),
+            |client, url| client.get(url),
+        ))
+        .await
+    }
+
+    /// GET /mints/tradable
+    pub async fn tradable_mints(&self) -> Result<Vec<Pubkey>, ClientError> {
+        let mints = self
+            .in_flight(self.send_to::<Vec<String>>(
+                |config| config.tokens_base_path.clone(),
+                "/mints/tradable",
+                |client, url| client.get(url),
+            ))
+            .await?;
+        Ok(mints.iter().filter_map(|mint| mint.parse().ok()).collect())
+    }
+}
@@ -0,0 +1,115 @@
+//! Attribution of swaps through the `tracking_account` of [`TransactionConfig`]: a deterministic
+//! account per integrator and campaign, referenced read-only by every swap of the campaign.
+
+use sha2::{Digest, Sha256};
+
+use crate::{solana_types::Pubkey, transaction_config::TransactionConfig};
+
+const TRACKING_ACCOUNT_DOMAIN: &[u8] = b"jupiter-tracking-account";
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TrackingCampaign {
+    pub integrator: Pubkey,
+    pub campaign: String,
+}
+
+impl TrackingCampaign {
+    pub fn new(integrator: Pubkey, campaign: impl Into<String>) -> Self {
+        Self {
+            integrator,
+            campaign: campaign.into(),
+        }
+    }
+
+    /// Tracking account of the campaign, see [`tracking_account`]
+    pub fn account(&self) -> Pubkey {
+        tracking_account(&self.integrator, &self.campaign)
+    }
+
+    /// Sets the tracking account of the campaign on `config`
+    pub fn apply(&self, config: &mut TransactionConfig) {
+        config.tracking_account = Some(self.account());
+    }
+}
+
+/// Derives the tracking account of `campaign` from the hash of `integrator` and `campaign`
+///
+/// The account holds no data and needs no signature, the same inputs always give the same account.
+pub fn tracking_account(integrator: &Pubkey, campaign: &str) -> Pubkey {
+    Pubkey::new_from_array(
+        Sha256::new()
+            .chain_update(TRACKING_ACCOUNT_DOMAIN)
+            .chain_update(integrator)
+            .chain_update(campaign)
+            .finalize()
+            .into(),
+    )
+}
+
+#[cfg(feature = "rpc")]
+mod rpc {
+    use solana_client::{
+        nonblocking::rpc_client::RpcClient, rpc_client::GetConfirmedSignaturesForAddress2Config,
+        rpc_response::RpcConfirmedTransactionStatusWithSignature,
+    };
+    use solana_commitment_config::CommitmentConfig;
+    use solana_signature::Signature;
+
+    use super::*;
+    use crate::ClientError;
+
+    /// Maximum number of signatures returned by a single `getSignaturesForAddress` call
+    const SIGNATURES_PAGE_LIMIT: usize = 1_000;
+
+    impl TrackingCampaign {
+        /// Fetches the confirmed transactions referencing the tracking account, newest first,
+        /// stopping at `until` when set
+        ///
+        /// Failed transactions are included, their `err` is set.
+        pub async fn fetch_signatures(
+            &self,
+            rpc_client: &RpcClient,
+            until: Option<Signature>,
+        ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>, ClientError> {
+            fetch_signatures(rpc_client, &self.account(), until).await
+        }
+    }
+
+    /// Fetches every confirmed transaction referencing `address`, newest first, stopping at
+    /// `until` when set
+    pub async fn fetch_signatures(
+        rpc_client: &RpcClient,
+        address: &Pubkey,
+        until: Option<Signature>,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>, ClientError> {
+        let mut signatures = Vec::new();
+        let mut before = None;
+        loop {
+            let page = rpc_client
+                .get_signatures_for_address_with_config(
+                    address,
+                    GetConfirmedSignaturesForAddress2Config {
+                        before,
+                        until,
+                        limit: Some(SIGNATURES_PAGE_LIMIT),
+                        commitment: Some(CommitmentConfig::confirmed()),
+                    },
+                )
+                .await?;
+            let page_len = page.len();
+            signatures.extend(page);
+            if page_len < SIGNATURES_PAGE_LIMIT {
+                return Ok(signatures);
+            }
+            before = signatures
+                .last()
+                .and_then(|status| status.signature.parse().ok());
+            if before.is_none() {
+                return Ok(signatures);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "rpc")]
+pub use rpc::fetch_signatures;
@@ -0,0 +1,216 @@
+//! Assembles the instructions returned by `/swap-instructions` into a transaction message.
+
+use solana_hash::Hash;
+use solana_instruction::Instruction;
+use solana_message::{v0, AddressLookupTableAccount, VersionedMessage};
+use solana_pubkey::Pubkey;
+use solana_signature::Signature;
+use solana_transaction::versioned::VersionedTransaction;
+
+use crate::{
+    blockhash::{BlockhashInfo, BlockhashProvider},
+    swap::{SwapInstructionsResponse, SwapResponse},
+    ClientError,
+};
+
+/// Instructions left out of [`SwapInstructionsResponse::instructions_with_options`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InstructionsOptions {
+    /// For transactions setting their own compute unit limit and price
+    pub exclude_compute_budget: bool,
+    /// Keeps the wrapped SOL account open, e.g. when more swaps follow
+    pub exclude_cleanup: bool,
+}
+
+impl SwapInstructionsResponse {
+    /// Every instruction in execution order: compute budget, setup, token ledger, swap, cleanup
+    /// and other instructions
+    pub fn instructions(&self) -> Vec<Instruction> {
+        self.all_instructions().cloned().collect()
+    }
+
+    /// Same as [`Self::instructions`] without the instructions excluded by `options`, for callers
+    /// composing their own transactions
+    pub fn instructions_with_options(&self, options: &InstructionsOptions) -> Vec<Instruction> {
+        self.ordered_instructions(!options.exclude_compute_budget, !options.exclude_cleanup)
+            .cloned()
+            .collect()
+    }
+
+    /// Compiles the instructions into a v0 message paid by `payer`
+    ///
+    /// `address_lookup_table_accounts` are the accounts of
+    /// [`address_lookup_table_addresses`](SwapInstructionsResponse::address_lookup_table_addresses).
+    pub async fn build_message(
+        &self,
+        payer: &Pubkey,
+        address_lookup_table_accounts: &[AddressLookupTableAccount],
+        blockhash_provider: &dyn BlockhashProvider,
+    ) -> Result<(VersionedMessage, BlockhashInfo), ClientError> {
+        let blockhash_info = blockhash_provider.blockhash().await?;
+        let message = self.compile_message(
+            payer,
+            blockhash_info.blockhash,
+            address_lookup_table_accounts,
+        )?;
+        Ok((message, blockhash_info))
+    }
+
+    /// Compiles the instructions into an unsigned v0 transaction paid by `payer`, every signature
+    /// is left to the default signature
+    pub fn into_versioned_transaction(
+        self,
+        payer: &Pubkey,
+        recent_blockhash: Hash,
+        address_lookup_table_accounts: &[AddressLookupTableAccount],
+    ) -> Result<VersionedTransaction, ClientError> {
+        let message =
+            self.compile_message(payer, recent_blockhash, address_lookup_table_accounts)?;
+        Ok(VersionedTransaction {
+            signatures: vec![
+                Signature::default();
+                usize::from(message.header().num_required_signatures)
+            ],
+            message,
+        })
+    }
+
+    fn compile_message(
+        &self,
+        payer: &Pubkey,
+        recent_blockhash: Hash,
+        address_lookup_table_accounts: &[AddressLookupTableAccount],
+    ) -> Result<VersionedMessage, ClientError> {
+        Ok(VersionedMessage::V0(v0::Message::try_compile(
+            payer,
+            &self.instructions(),
+            address_lookup_table_accounts,
+            recent_blockhash,
+        )?))
+    }
+}
+
+impl SwapResponse {
+    /// Replaces the expired blockhash of the swap transaction with one from `blockhash_provider`
+    ///
+    /// Lets approval flows sign after the original blockhash expired without quoting again. The
+    /// amounts and slippage of the swap are unchanged, existing signatures are cleared.
+    pub async fn rebuild_with_fresh_blockhash(
+        &mut self,
+        blockhash_provider: &dyn BlockhashProvider,
+    ) -> Result<BlockhashInfo, ClientError> {
+        let mut transaction: VersionedTransaction = bincode::deserialize(&self.swap_transaction)?;
+        let blockhash_info = blockhash_provider.blockhash().await?;
+        refresh_blockhash(&mut transaction, blockhash_info.blockhash);
+        self.swap_transaction = bincode::serialize(&transaction)?;
+        self.last_valid_block_height = blockhash_info.last_valid_block_height;
+        Ok(blockhash_info)
+    }
+}
+
+/// Replaces the recent blockhash of `transaction` and clears its signatures, which the new
+/// blockhash invalidates, for transactions held for a while before being signed and sent
+pub fn refresh_blockhash(transaction: &mut VersionedTransaction, blockhash: Hash) {
+    transaction.message.set_recent_blockhash(blockhash);
+    transaction.signatures.fill(Signature::default());
+}
+
+#[cfg(feature = "rpc")]
+mod rpc {
+    use std::{
+        collections::HashMap,
+        sync::{Mutex, PoisonError},
+    };
+
+    use solana_address_lookup_table_interface::state::AddressLookupTable;
+    use solana_client::nonblocking::rpc_client::RpcClient;
+
+    use super::*;
+
+    /// Maximum number of accounts of a single `getMultipleAccounts` request
+    const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+    /// Fetches the address lookup tables referenced by a swap, tables that do not exist or fail to
+    /// deserialize are skipped
+    pub async fn fetch_address_lookup_tables(
+        rpc_client: &RpcClient,
+        addresses: &[Pubkey],
+    ) -> Result<Vec<AddressLookupTableAccount>, ClientError> {
+        let accounts = rpc_client.get_multiple_accounts(addresses).await?;
+        Ok(addresses
+            .iter()
+            .zip(accounts)
+            .filter_map(|(key, account)| {
+                let account = account?;
+                let table = AddressLookupTable::deserialize(&account.data).ok()?;
+                Some(AddressLookupTableAccount {
+                    key: *key,
+                    addresses: table.addresses.into_owned(),
+                })
+            })
+            .collect())
+    }
+
+    /// Address lookup tables by address, fetched once and reused by every later swap
+    ///
+    /// Tables are append-only, a cached table stays valid for the addresses it holds until it is
+    /// deactivated and closed. Swaps failing on a table should [`invalidate`](Self::invalidate) it.
+    #[derive(Debug, Default)]
+    pub struct AddressLookupTableCache {
+        tables: Mutex<HashMap<Pubkey, AddressLookupTableAccount>>,
+    }
+
+    impl AddressLookupTableCache {
+        /// Tables of `addresses`, the ones missing from the cache are fetched concurrently in
+        /// batches and cached, tables that do not exist are skipped
+        pub async fn get_or_fetch(
+            &self,
+            rpc_client: &RpcClient,
+            addresses: &[Pubkey],
+        ) -> Result<Vec<AddressLookupTableAccount>, ClientError> {
+            let missing = {
+                let tables = self.lock();
+                addresses
+                    .iter()
+                    .filter(|address| !tables.contains_key(address))
+                    .copied()
+                    .collect::<Vec<_>>()
+            };
+            if !missing.is_empty() {
+                let fetched = futures::future::try_join_all(
+                    missing
+                        .chunks(MAX_MULTIPLE_ACCOUNTS)
+                        .map(|chunk| fetch_address_lookup_tables(rpc_client, chunk)),
+                )
+                .await?;
+                self.lock().extend(
+                    fetched
+                        .into_iter()
+                        .flatten()
+                        .map(|table| (table.key, table)),
+                );
+            }
+
+            let tables = self.lock();
+            Ok(addresses
+                .iter()
+                .filter_map(|address| tables.get(address).cloned())
+                .collect())
+        }
+
+        pub fn invalidate(&self, address: &Pubkey) {
+            self.lock().remove(address);
+        }
+
+        pub fn clear(&self) {
+            self.lock().clear();
+        }
+
+        fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<Pubkey, AddressLookupTableAccount>> {
+            self.tables.lock().unwrap_or_else(PoisonError::into_inner)
+        }
+    }
+}
+
+#[cfg(feature = "rpc")]
+pub use rpc::{fetch_address_lookup_tables, AddressLookupTableCache};
@@ -0,0 +1,156 @@
+//! Composition of caller instructions (memos, transfers, program calls) with the swap instructions
+//! into a single transaction, see [`TransactionComposer`].
+
+use solana_hash::Hash;
+use solana_instruction::Instruction;
+use solana_message::{v0, AddressLookupTableAccount, VersionedMessage};
+use solana_packet::PACKET_DATA_SIZE;
+use solana_pubkey::Pubkey;
+use solana_signature::Signature;
+use solana_transaction::versioned::VersionedTransaction;
+
+use crate::{
+    swap::SwapInstructionsResponse, transaction_builder::InstructionsOptions, ClientError,
+};
+
+/// Builds a v0 transaction out of swap instructions surrounded by caller instructions
+///
+/// Instructions are ordered as compute budget, instructions before the swap, setup, token ledger,
+/// swap, cleanup, instructions after the swap, then other instructions such as the Jito tip. The
+/// compute unit limit set by the API does not account for the caller instructions.
+#[derive(Clone, Debug)]
+pub struct TransactionComposer {
+    payer: Pubkey,
+    swap_instructions: SwapInstructionsResponse,
+    instructions_options: InstructionsOptions,
+    before_swap: Vec<Instruction>,
+    after_swap: Vec<Instruction>,
+    /// Lookup tables of the caller instructions
+    extra_address_lookup_table_addresses: Vec<Pubkey>,
+    address_lookup_table_accounts: Vec<AddressLookupTableAccount>,
+}
+
+impl TransactionComposer {
+    pub fn new(payer: Pubkey, swap_instructions: SwapInstructionsResponse) -> Self {
+        Self {
+            payer,
+            swap_instructions,
+            instructions_options: InstructionsOptions::default(),
+            before_swap: Vec::new(),
+            after_swap: Vec::new(),
+            extra_address_lookup_table_addresses: Vec::new(),
+            address_lookup_table_accounts: Vec::new(),
+        }
+    }
+
+    pub fn instructions_options(mut self, instructions_options: InstructionsOptions) -> Self {
+        self.instructions_options = instructions_options;
+        self
+    }
+
+    pub fn before_swap(mut self, instruction: Instruction) -> Self {
+        self.before_swap.push(instruction);
+        self
+    }
+
+    pub fn after_swap(mut self, instruction: Instruction) -> Self {
+        self.after_swap.push(instruction);
+        self
+    }
+
+    /// Lookup table to compile the caller instructions with, on top of the swap ones
+    pub fn extra_address_lookup_table(mut self, address: Pubkey) -> Self {
+        self.extra_address_lookup_table_addresses.push(address);
+        self
+    }
+
+    /// Accounts of the lookup tables, see [`Self::address_lookup_table_addresses`]
+    pub fn address_lookup_table_accounts(
+        mut self,
+        address_lookup_table_accounts: Vec<AddressLookupTableAccount>,
+    ) -> Self {
+        self.address_lookup_table_accounts = address_lookup_table_accounts;
+        self
+    }
+
+    /// Lookup tables the transaction is compiled with: the swap ones then the extra ones
+    pub fn address_lookup_table_addresses(&self) -> Vec<Pubkey> {
+        let mut addresses = self
+            .swap_instructions
+            .address_lookup_table_addresses
+            .clone();
+        for address in &self.extra_address_lookup_table_addresses {
+            if !addresses.contains(address) {
+                addresses.push(*address);
+            }
+        }
+        addresses
+    }
+
+    /// Every instruction in transaction order
+    pub fn instructions(&self) -> Vec<Instruction> {
+        let mut instructions = self
+            .swap_instructions
+            .instructions_with_options(&self.instructions_options);
+        let other_start = instructions.len() - self.swap_instructions.other_instructions.len();
+        instructions.splice(other_start..other_start, self.after_swap.iter().cloned());
+        let compute_budget_end = if self.instructions_options.exclude_compute_budget {
+            0
+        } else {
+            self.swap_instructions.compute_budget_instructions.len()
+        };
+        instructions.splice(
+            compute_budget_end..compute_budget_end,
+            self.before_swap.iter().cloned(),
+        );
+        instructions
+    }
+
+    /// Compiles the unsigned transaction, [`ClientError::TransactionTooLarge`] when it does not fit
+    /// in a packet once signed
+    pub fn compose(&self, recent_blockhash: Hash) -> Result<VersionedTransaction, ClientError> {
+        let message = v0::Message::try_compile(
+            &self.payer,
+            &self.instructions(),
+            &self.address_lookup_table_accounts,
+            recent_blockhash,
+        )?;
+        let transaction = VersionedTransaction {
+            signatures: vec![
+                Signature::default();
+                usize::from(message.header.num_required_signatures)
+            ],
+            message: VersionedMessage::V0(message),
+        };
+        let size = bincode::serialized_size(&transaction)? as usize;
+        if size > PACKET_DATA_SIZE {
+            return Err(ClientError::TransactionTooLarge {
+                size,
+                overflow: size - PACKET_DATA_SIZE,
+            });
+        }
+        Ok(transaction)
+    }
+}
+
+#[cfg(feature = "rpc")]
+mod rpc {
+    use solana_client::nonblocking::rpc_client::RpcClient;
+
+    use super::*;
+    use crate::transaction_builder::AddressLookupTableCache;
+
+    impl TransactionComposer {
+        /// Fetches the accounts of [`Self::address_lookup_table_addresses`] through `cache`
+        pub async fn resolve_address_lookup_tables(
+            self,
+            rpc_client: &RpcClient,
+            cache: &AddressLookupTableCache,
+        ) -> Result<Self, ClientError> {
+            let address_lookup_table_accounts = cache
+                .get_or_fetch(rpc_client, &self.address_lookup_table_addresses())
+                .await?;
+            Ok(self.address_lookup_table_accounts(address_lookup_table_accounts))
+        }
+    }
+}
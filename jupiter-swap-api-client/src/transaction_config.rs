@@ -1,9 +1,11 @@
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use serde_json::Value;
-use solana_account_decoder::UiAccount;
-use solana_sdk::pubkey::Pubkey;
+use serde_json::{Map, Value};
 
-use crate::serde_helpers::option_field_as_string;
+use crate::{
+    priority_fees::FeeProfile,
+    serde_helpers::option_field_as_string,
+    solana_types::{Pubkey, UiAccount},
+};
 
 #[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -189,6 +191,18 @@ pub struct TransactionConfig {
     /// Requests a correct last valid block height,
     /// this is to allow a smooth transition to agave 2.0 for all consumers, see https://github.com/solana-labs/solana/issues/24526
     pub correct_last_valid_block_height: bool,
+    /// Named fee profile resolved into `compute_unit_price_micro_lamports` by the client when no
+    /// prioritization fee is set explicitly, see [`crate::priority_fees::FeeProfiles`]
+    #[serde(skip)]
+    pub fee_profile: Option<FeeProfile>,
+    /// Sent in the [`IDEMPOTENCY_KEY_HEADER`](crate::IDEMPOTENCY_KEY_HEADER) header, generated
+    /// when unset and retries are enabled
+    #[serde(skip)]
+    pub idempotency_key: Option<String>,
+    /// Merged into the `/swap` and `/swap-instructions` request bodies, overriding the typed fields,
+    /// so server parameters not supported by this crate yet can be sent
+    #[serde(skip)]
+    pub extra_body: Map<String, Value>,
 }
 
 impl Default for TransactionConfig {
@@ -211,6 +225,9 @@ impl Default for TransactionConfig {
             dynamic_slippage: None,
             blockhash_slots_to_expiry: None,
             correct_last_valid_block_height: false,
+            fee_profile: None,
+            idempotency_key: None,
+            extra_body: Map::new(),
         }
     }
 }
@@ -0,0 +1,149 @@
+//! Fluent construction of a [`TransactionConfig`] and presets for common setups, see
+//! [`TransactionConfigBuilder`].
+
+use serde_json::Value;
+
+use crate::{
+    priority_fees::FeeProfile,
+    solana_types::Pubkey,
+    transaction_config::{
+        ComputeUnitPriceMicroLamports, DynamicSlippageSettings, PrioritizationFeeLamports,
+        PriorityLevel, TransactionConfig,
+    },
+};
+
+/// Setting one way of paying priority fees clears the other, the API accepts only one
+#[derive(Clone, Debug, Default)]
+pub struct TransactionConfigBuilder {
+    config: TransactionConfig,
+}
+
+impl TransactionConfigBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts from `config` instead of the defaults
+    pub fn from_config(config: TransactionConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn wrap_and_unwrap_sol(mut self, wrap_and_unwrap_sol: bool) -> Self {
+        self.config.wrap_and_unwrap_sol = wrap_and_unwrap_sol;
+        self
+    }
+
+    pub fn fee_account(mut self, fee_account: Pubkey) -> Self {
+        self.config.fee_account = Some(fee_account);
+        self
+    }
+
+    pub fn destination_token_account(mut self, destination_token_account: Pubkey) -> Self {
+        self.config.destination_token_account = Some(destination_token_account);
+        self
+    }
+
+    pub fn tracking_account(mut self, tracking_account: Pubkey) -> Self {
+        self.config.tracking_account = Some(tracking_account);
+        self
+    }
+
+    pub fn compute_unit_price_micro_lamports(mut self, micro_lamports: u64) -> Self {
+        self.config.compute_unit_price_micro_lamports =
+            Some(ComputeUnitPriceMicroLamports::MicroLamports(micro_lamports));
+        self.config.prioritization_fee_lamports = None;
+        self
+    }
+
+    pub fn prioritization_fee_lamports(
+        mut self,
+        prioritization_fee_lamports: PrioritizationFeeLamports,
+    ) -> Self {
+        self.config.prioritization_fee_lamports = Some(prioritization_fee_lamports);
+        self.config.compute_unit_price_micro_lamports = None;
+        self
+    }
+
+    /// Priority fee estimated by the API at `priority_level`, capped at `max_lamports`
+    pub fn priority(self, priority_level: PriorityLevel, max_lamports: u64) -> Self {
+        self.prioritization_fee_lamports(PrioritizationFeeLamports::PriorityLevelWithMaxLamports {
+            priority_level,
+            max_lamports,
+            global: false,
+        })
+    }
+
+    pub fn jito_tip_lamports(self, lamports: u64) -> Self {
+        self.prioritization_fee_lamports(PrioritizationFeeLamports::JitoTipLamports(lamports))
+    }
+
+    pub fn fee_profile(mut self, fee_profile: FeeProfile) -> Self {
+        self.config.fee_profile = Some(fee_profile);
+        self
+    }
+
+    pub fn dynamic_compute_unit_limit(mut self, dynamic_compute_unit_limit: bool) -> Self {
+        self.config.dynamic_compute_unit_limit = dynamic_compute_unit_limit;
+        self
+    }
+
+    pub fn as_legacy_transaction(mut self, as_legacy_transaction: bool) -> Self {
+        self.config.as_legacy_transaction = as_legacy_transaction;
+        self
+    }
+
+    pub fn use_shared_accounts(mut self, use_shared_accounts: bool) -> Self {
+        self.config.use_shared_accounts = Some(use_shared_accounts);
+        self
+    }
+
+    pub fn skip_user_accounts_rpc_calls(mut self, skip_user_accounts_rpc_calls: bool) -> Self {
+        self.config.skip_user_accounts_rpc_calls = skip_user_accounts_rpc_calls;
+        self
+    }
+
+    pub fn dynamic_slippage(mut self, min_bps: u16, max_bps: u16) -> Self {
+        self.config.dynamic_slippage = Some(DynamicSlippageSettings {
+            min_bps: Some(min_bps),
+            max_bps: Some(max_bps),
+        });
+        self
+    }
+
+    pub fn idempotency_key(mut self, idempotency_key: String) -> Self {
+        self.config.idempotency_key = Some(idempotency_key);
+        self
+    }
+
+    /// See [`TransactionConfig::extra_body`]
+    pub fn extra_body_field(mut self, name: impl Into<String>, value: Value) -> Self {
+        self.config.extra_body.insert(name.into(), value);
+        self
+    }
+
+    pub fn build(self) -> TransactionConfig {
+        self.config
+    }
+}
+
+impl TransactionConfig {
+    pub fn builder() -> TransactionConfigBuilder {
+        TransactionConfigBuilder::new()
+    }
+
+    /// For swaps signed by a program derived address through CPI: the user accounts are not
+    /// fetched by the API and shared accounts are not used
+    pub fn for_pda_signer() -> Self {
+        Self::builder()
+            .skip_user_accounts_rpc_calls(true)
+            .use_shared_accounts(false)
+            .build()
+    }
+
+    /// Priority fee estimated by the API at `priority_level`, capped at `max_lamports`
+    pub fn with_priority(priority_level: PriorityLevel, max_lamports: u64) -> Self {
+        Self::builder()
+            .priority(priority_level, max_lamports)
+            .build()
+    }
+}
@@ -0,0 +1,514 @@
+//! Defense in depth for wallets signing transactions produced by the API: checks what a swap
+//! transaction pays for, invokes and requires signatures from before it is signed.
+
+use std::collections::BTreeSet;
+
+use solana_instruction::Instruction;
+use solana_pubkey::{pubkey, Pubkey};
+use solana_transaction::versioned::VersionedTransaction;
+
+use crate::{
+    quote::SwapMode,
+    swap::{SwapInstructionsResponse, SwapRequest, SwapResponse},
+    token_extensions::TOKEN_2022_PROGRAM_ID,
+    ClientError, SYSTEM_PROGRAM_ID,
+};
+
+pub const JUPITER_V6_PROGRAM_ID: Pubkey = pubkey!("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4");
+pub const COMPUTE_BUDGET_PROGRAM_ID: Pubkey =
+    pubkey!("ComputeBudget111111111111111111111111111111");
+pub const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// Programs a swap transaction invokes directly, AMMs are only invoked through the Jupiter program
+pub const DEFAULT_PROGRAM_ALLOWLIST: [Pubkey; 6] = [
+    JUPITER_V6_PROGRAM_ID,
+    COMPUTE_BUDGET_PROGRAM_ID,
+    TOKEN_PROGRAM_ID,
+    TOKEN_2022_PROGRAM_ID,
+    SYSTEM_PROGRAM_ID,
+    ASSOCIATED_TOKEN_PROGRAM_ID,
+];
+
+/// Instructions of the Jupiter program carrying a swap
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RouteKind {
+    Route,
+    RouteWithTokenLedger,
+    ExactOutRoute,
+    SharedAccountsRoute,
+    SharedAccountsRouteWithTokenLedger,
+    SharedAccountsExactOutRoute,
+}
+
+impl RouteKind {
+    fn from_discriminator(discriminator: &[u8]) -> Option<Self> {
+        Some(match discriminator {
+            [229, 23, 203, 151, 122, 227, 173, 42] => Self::Route,
+            [150, 86, 71, 116, 167, 93, 14, 104] => Self::RouteWithTokenLedger,
+            [208, 51, 239, 151, 123, 43, 237, 92] => Self::ExactOutRoute,
+            [193, 32, 155, 51, 65, 214, 156, 129] => Self::SharedAccountsRoute,
+            [230, 121, 143, 80, 119, 159, 106, 170] => Self::SharedAccountsRouteWithTokenLedger,
+            [176, 209, 105, 168, 154, 125, 69, 62] => Self::SharedAccountsExactOutRoute,
+            _ => return None,
+        })
+    }
+
+    fn is_shared_accounts(self) -> bool {
+        matches!(
+            self,
+            Self::SharedAccountsRoute
+                | Self::SharedAccountsRouteWithTokenLedger
+                | Self::SharedAccountsExactOutRoute
+        )
+    }
+
+    fn swap_mode(self) -> SwapMode {
+        match self {
+            Self::ExactOutRoute | Self::SharedAccountsExactOutRoute => SwapMode::ExactOut,
+            _ => SwapMode::ExactIn,
+        }
+    }
+}
+
+/// Accounts and amounts of a Jupiter route instruction
+struct RouteInstruction {
+    kind: RouteKind,
+    /// `None` when loaded from an address lookup table, which cannot be resolved offline
+    destination_token_account: Option<Pubkey>,
+    /// Quoted amount on the non-fixed side: output for `ExactIn`, input for `ExactOut`
+    quoted_amount: u64,
+    slippage_bps: u16,
+}
+
+impl RouteInstruction {
+    fn decode(instruction: &Instruction) -> Option<Self> {
+        let accounts = instruction
+            .accounts
+            .iter()
+            .map(|meta| Some(meta.pubkey))
+            .collect::<Vec<_>>();
+        Self::decode_parts(&instruction.program_id, &accounts, &instruction.data)
+    }
+
+    /// Decodes the first top level route instruction of `transaction`
+    fn find(transaction: &VersionedTransaction) -> Option<Self> {
+        let account_keys = transaction.message.static_account_keys();
+        transaction
+            .message
+            .instructions()
+            .iter()
+            .find_map(|instruction| {
+                let program_id = account_keys.get(usize::from(instruction.program_id_index))?;
+                let accounts = instruction
+                    .accounts
+                    .iter()
+                    .map(|index| account_keys.get(usize::from(*index)).copied())
+                    .collect::<Vec<_>>();
+                Self::decode_parts(program_id, &accounts, &instruction.data)
+            })
+    }
+
+    /// Reads the trailing arguments after the route plan: `[fixed amount,] quoted amount,
+    /// slippage_bps, platform_fee_bps`, token ledger routes have no fixed amount
+    ///
+    /// `accounts` are `None` when loaded from an address lookup table.
+    fn decode_parts(program_id: &Pubkey, accounts: &[Option<Pubkey>], data: &[u8]) -> Option<Self> {
+        if *program_id != JUPITER_V6_PROGRAM_ID {
+            return None;
+        }
+        let kind = RouteKind::from_discriminator(data.get(..8)?)?;
+        let account = |index: usize| accounts.get(index).copied();
+        let destination_token_account = if kind.is_shared_accounts() {
+            account(6)?
+        } else {
+            // The optional destination account is set to the program id when absent
+            match account(4)? {
+                Some(destination) if destination != JUPITER_V6_PROGRAM_ID => Some(destination),
+                Some(_) => account(3)?,
+                None => None,
+            }
+        };
+        let arguments = data.get(8..)?;
+        let tail = arguments.get(arguments.len().checked_sub(11)?..)?;
+        let (quoted_amount, rest) = tail.split_first_chunk::<8>()?;
+        let (slippage_bps, _platform_fee_bps) = rest.split_first_chunk::<2>()?;
+        Some(Self {
+            kind,
+            destination_token_account,
+            quoted_amount: u64::from_le_bytes(*quoted_amount),
+            slippage_bps: u16::from_le_bytes(*slippage_bps),
+        })
+    }
+
+    fn other_amount_threshold(&self) -> u64 {
+        other_amount_threshold(self.kind.swap_mode(), self.quoted_amount, self.slippage_bps)
+    }
+}
+
+/// Minimum output for `ExactIn`, maximum input for `ExactOut`, as enforced by the program for a
+/// route quoting `quoted_amount` on the non-fixed side with `slippage_bps`
+pub(crate) fn other_amount_threshold(
+    swap_mode: SwapMode,
+    quoted_amount: u64,
+    slippage_bps: u16,
+) -> u64 {
+    let quoted_amount = u128::from(quoted_amount);
+    let slippage_bps = u128::from(slippage_bps);
+    let threshold = match swap_mode {
+        SwapMode::ExactIn => quoted_amount * 10_000u128.saturating_sub(slippage_bps) / 10_000,
+        SwapMode::ExactOut => (quoted_amount * (10_000 + slippage_bps)).div_ceil(10_000),
+    };
+    u64::try_from(threshold).unwrap_or(u64::MAX)
+}
+
+/// Minimum output (`ExactIn`) or maximum input (`ExactOut`) enforced by the route instruction of
+/// `transaction`, `None` when it has no route instruction this crate can decode
+pub fn swap_transaction_other_amount_threshold(transaction: &VersionedTransaction) -> Option<u64> {
+    RouteInstruction::find(transaction).map(|route| route.other_amount_threshold())
+}
+
+pub fn associated_token_address(
+    wallet: &Pubkey,
+    mint: &Pubkey,
+    token_program_id: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[wallet.as_ref(), token_program_id.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TransactionViolation {
+    /// The transaction is not paid by the user, `actual` is `None` for a message without accounts
+    FeePayerMismatch {
+        expected: Pubkey,
+        actual: Option<Pubkey>,
+    },
+    /// A top level instruction invokes a program outside of the allowlist
+    UnexpectedProgram { program_id: Pubkey },
+    /// A signature is required from an account other than the user
+    UnexpectedSigner { signer: Pubkey },
+    /// The instruction is not a Jupiter route instruction this crate can decode
+    UnrecognizedSwapInstruction { program_id: Pubkey },
+    /// The swap pays out to an account other than the configured destination token account or
+    /// the user's associated token accounts of the output mint
+    DestinationMismatch {
+        expected: Vec<Pubkey>,
+        actual: Pubkey,
+    },
+    /// The instruction swaps in a different mode than the quote
+    SwapModeMismatch {
+        expected: SwapMode,
+        actual: SwapMode,
+    },
+    /// The quoted amount of the instruction differs from the quote: output amount for `ExactIn`,
+    /// input amount for `ExactOut`
+    QuotedAmountMismatch { expected: u64, actual: u64 },
+    /// The minimum output (`ExactIn`) or maximum input (`ExactOut`) enforced by the instruction is
+    /// looser than the quote's `other_amount_threshold`
+    OtherAmountThresholdMismatch { expected: u64, actual: u64 },
+    /// The transaction has no Jupiter route instruction this crate can decode
+    MissingSwapInstruction,
+    /// The destination token account of the swap is loaded from an address lookup table and
+    /// cannot be checked offline
+    UnresolvedDestination,
+}
+
+/// Checks `transaction` against [`DEFAULT_PROGRAM_ALLOWLIST`], see
+/// [`verify_swap_transaction_with_allowlist`]
+pub fn verify_swap_transaction(
+    transaction: &VersionedTransaction,
+    swap_request: &SwapRequest,
+) -> Vec<TransactionViolation> {
+    verify_swap_transaction_with_allowlist(transaction, swap_request, &DEFAULT_PROGRAM_ALLOWLIST)
+}
+
+/// Checks that `transaction` is paid by the user of `swap_request`, only requires the user's
+/// signature, only invokes programs of `program_allowlist` and that its route instruction passes
+/// the checks of [`verify_swap_instruction`], every violation is returned
+///
+/// A destination token account loaded from an address lookup table is reported as
+/// [`TransactionViolation::UnresolvedDestination`].
+pub fn verify_swap_transaction_with_allowlist(
+    transaction: &VersionedTransaction,
+    swap_request: &SwapRequest,
+    program_allowlist: &[Pubkey],
+) -> Vec<TransactionViolation> {
+    let user = swap_request.user_public_key;
+    let message = &transaction.message;
+    let account_keys = message.static_account_keys();
+    let mut violations = Vec::new();
+
+    let fee_payer = account_keys.first().copied();
+    if fee_payer != Some(user) {
+        violations.push(TransactionViolation::FeePayerMismatch {
+            expected: user,
+            actual: fee_payer,
+        });
+    }
+
+    let signer_count = usize::from(message.header().num_required_signatures);
+    violations.extend(
+        account_keys
+            .iter()
+            .take(signer_count)
+            .filter(|signer| **signer != user)
+            .map(|signer| TransactionViolation::UnexpectedSigner { signer: *signer }),
+    );
+
+    let program_ids = message
+        .instructions()
+        .iter()
+        .filter_map(|instruction| account_keys.get(usize::from(instruction.program_id_index)))
+        .collect::<BTreeSet<_>>();
+    violations.extend(
+        program_ids
+            .into_iter()
+            .filter(|program_id| !program_allowlist.contains(program_id))
+            .map(|program_id| TransactionViolation::UnexpectedProgram {
+                program_id: *program_id,
+            }),
+    );
+
+    match RouteInstruction::find(transaction) {
+        Some(route) => {
+            if route.destination_token_account.is_none() {
+                violations.push(TransactionViolation::UnresolvedDestination);
+            }
+            violations.extend(verify_route(&route, swap_request));
+        }
+        None => violations.push(TransactionViolation::MissingSwapInstruction),
+    }
+
+    violations
+}
+
+/// Checks that the swap instruction pays out to the destination of `swap_request` and enforces at
+/// least the slippage protection of its quote
+///
+/// The destination must be `destination_token_account` when configured, the user's associated
+/// token account of the output mint otherwise. Transactions built with dynamic slippage can be
+/// reported when the API widened the slippage of the quote.
+pub fn verify_swap_instruction(
+    swap_instruction: &Instruction,
+    swap_request: &SwapRequest,
+) -> Vec<TransactionViolation> {
+    match RouteInstruction::decode(swap_instruction) {
+        Some(route) => verify_route(&route, swap_request),
+        None => vec![TransactionViolation::UnrecognizedSwapInstruction {
+            program_id: swap_instruction.program_id,
+        }],
+    }
+}
+
+/// Checks the destination, when resolved, swap mode, quoted amount and threshold of `route`
+/// against `swap_request`
+fn verify_route(route: &RouteInstruction, swap_request: &SwapRequest) -> Vec<TransactionViolation> {
+    let quote_response = &swap_request.quote_response;
+    let mut violations = Vec::new();
+
+    let expected_destinations = match swap_request.config.destination_token_account {
+        Some(destination_token_account) => vec![destination_token_account],
+        None => [TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID]
+            .iter()
+            .map(|token_program_id| {
+                associated_token_address(
+                    &swap_request.user_public_key,
+                    &quote_response.output_mint,
+                    token_program_id,
+                )
+            })
+            .collect(),
+    };
+    if let Some(actual) = route.destination_token_account {
+        if !expected_destinations.contains(&actual) {
+            violations.push(TransactionViolation::DestinationMismatch {
+                expected: expected_destinations,
+                actual,
+            });
+        }
+    }
+
+    let swap_mode = route.kind.swap_mode();
+    if swap_mode != quote_response.swap_mode {
+        violations.push(TransactionViolation::SwapModeMismatch {
+            expected: quote_response.swap_mode.clone(),
+            actual: swap_mode,
+        });
+        return violations;
+    }
+
+    let (quoted_amount, threshold_too_loose) = match swap_mode {
+        SwapMode::ExactIn => (
+            quote_response.out_amount,
+            route.other_amount_threshold() < quote_response.other_amount_threshold,
+        ),
+        SwapMode::ExactOut => (
+            quote_response.in_amount,
+            route.other_amount_threshold() > quote_response.other_amount_threshold,
+        ),
+    };
+    if route.quoted_amount != quoted_amount {
+        violations.push(TransactionViolation::QuotedAmountMismatch {
+            expected: quoted_amount,
+            actual: route.quoted_amount,
+        });
+    }
+    if threshold_too_loose {
+        violations.push(TransactionViolation::OtherAmountThresholdMismatch {
+            expected: quote_response.other_amount_threshold,
+            actual: route.other_amount_threshold(),
+        });
+    }
+
+    violations
+}
+
+impl SwapResponse {
+    /// Verifies the swap transaction built for `swap_request`, see [`verify_swap_transaction`]
+    pub fn verify(
+        &self,
+        swap_request: &SwapRequest,
+    ) -> Result<Vec<TransactionViolation>, ClientError> {
+        let transaction: VersionedTransaction = bincode::deserialize(&self.swap_transaction)?;
+        Ok(verify_swap_transaction(&transaction, swap_request))
+    }
+}
+
+impl SwapInstructionsResponse {
+    /// Verifies the swap instruction built for `swap_request`, see [`verify_swap_instruction`]
+    pub fn verify(&self, swap_request: &SwapRequest) -> Vec<TransactionViolation> {
+        verify_swap_instruction(&self.swap_instruction, swap_request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_hash::Hash;
+    use solana_instruction::AccountMeta;
+    use solana_message::{v0, AddressLookupTableAccount, Message, VersionedMessage};
+    use solana_signature::Signature;
+
+    use super::*;
+    use crate::{quote::QuoteResponse, transaction_config::TransactionConfig};
+
+    const ROUTE_DISCRIMINATOR: [u8; 8] = [229, 23, 203, 151, 122, 227, 173, 42];
+
+    fn swap_request() -> SwapRequest {
+        let quote_response: QuoteResponse = serde_json::from_value(serde_json::json!({
+            "inputMint": "So11111111111111111111111111111111111111112",
+            "inAmount": "1000000",
+            "outputMint": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+            "outAmount": "1000000",
+            "otherAmountThreshold": "995000",
+            "swapMode": "ExactIn",
+            "slippageBps": 50,
+            "platformFee": null,
+            "priceImpactPct": "0",
+            "routePlan": [],
+        }))
+        .unwrap();
+        SwapRequest {
+            user_public_key: Pubkey::new_unique(),
+            quote_response,
+            config: TransactionConfig::default(),
+        }
+    }
+
+    /// `route` instruction of `swap_request`'s quote paying out to `destination`
+    fn route_instruction(swap_request: &SwapRequest, destination: Pubkey) -> Instruction {
+        let quote_response = &swap_request.quote_response;
+        let mut data = ROUTE_DISCRIMINATOR.to_vec();
+        // Empty route plan, then in amount, quoted out amount, slippage and platform fee
+        data.extend(0u32.to_le_bytes());
+        data.extend(quote_response.in_amount.to_le_bytes());
+        data.extend(quote_response.out_amount.to_le_bytes());
+        data.extend(quote_response.slippage_bps.to_le_bytes());
+        data.push(0);
+        let mut accounts = vec![AccountMeta::new_readonly(Pubkey::new_unique(), false); 9];
+        accounts[1] = AccountMeta::new_readonly(swap_request.user_public_key, true);
+        accounts[3] = AccountMeta::new(destination, false);
+        accounts[4] = AccountMeta::new_readonly(JUPITER_V6_PROGRAM_ID, false);
+        Instruction::new_with_bytes(JUPITER_V6_PROGRAM_ID, &data, accounts)
+    }
+
+    fn transaction(message: VersionedMessage) -> VersionedTransaction {
+        VersionedTransaction {
+            signatures: vec![Signature::default()],
+            message,
+        }
+    }
+
+    #[test]
+    fn route_of_the_swap_transaction_is_verified() {
+        let swap_request = swap_request();
+        let user = swap_request.user_public_key;
+        let user_token_account = associated_token_address(
+            &user,
+            &swap_request.quote_response.output_mint,
+            &TOKEN_PROGRAM_ID,
+        );
+        let verify = |destination| {
+            let route = route_instruction(&swap_request, destination);
+            let message = Message::new_with_blockhash(&[route], Some(&user), &Hash::default());
+            verify_swap_transaction(
+                &transaction(VersionedMessage::Legacy(message)),
+                &swap_request,
+            )
+        };
+
+        assert_eq!(verify(user_token_account), vec![]);
+        let other_token_account = Pubkey::new_unique();
+        assert_eq!(
+            verify(other_token_account),
+            vec![TransactionViolation::DestinationMismatch {
+                expected: vec![
+                    user_token_account,
+                    associated_token_address(
+                        &user,
+                        &swap_request.quote_response.output_mint,
+                        &TOKEN_2022_PROGRAM_ID,
+                    ),
+                ],
+                actual: other_token_account,
+            }]
+        );
+    }
+
+    #[test]
+    fn unverifiable_routes_are_reported() {
+        let swap_request = swap_request();
+        let user = swap_request.user_public_key;
+
+        let message = Message::new_with_blockhash(&[], Some(&user), &Hash::default());
+        assert_eq!(
+            verify_swap_transaction(
+                &transaction(VersionedMessage::Legacy(message)),
+                &swap_request
+            ),
+            vec![TransactionViolation::MissingSwapInstruction]
+        );
+
+        let destination = Pubkey::new_unique();
+        let lookup_table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: vec![destination],
+        };
+        let message = v0::Message::try_compile(
+            &user,
+            &[route_instruction(&swap_request, destination)],
+            &[lookup_table],
+            Hash::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            verify_swap_transaction(&transaction(VersionedMessage::V0(message)), &swap_request),
+            vec![TransactionViolation::UnresolvedDestination]
+        );
+    }
+}
@@ -0,0 +1,301 @@
+//! Ultra API: the router builds a ready to sign order transaction, the signed transaction is then
+//! handed back to Jupiter which lands it and reports the outcome.
+
+use std::{collections::HashMap, convert::Infallible, fmt, str::FromStr, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    quote::SwapMode,
+    serde_helpers::{field_as_string, option_comma_separated, option_field_as_string},
+    solana_types::{Pubkey, Signature},
+    swap::{base64_serialize_deserialize, option_base64_serialize_deserialize},
+    ClientError, JupiterSwapApiClient,
+};
+
+pub const DEFAULT_ULTRA_BASE_PATH: &str = "https://lite-api.jup.ag/ultra/v1";
+
+/// Router or aggregator behind an Ultra order
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum UltraRouter {
+    /// Jupiter's own routing engine
+    Metis,
+    /// Jupiter's RFQ system, filled by market makers
+    JupiterZ,
+    Dflow,
+    Okx,
+    Hashflow,
+    /// A router unknown to this version of the client
+    Other(String),
+}
+
+impl fmt::Display for UltraRouter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Metis => "metis",
+            Self::JupiterZ => "jupiterz",
+            Self::Dflow => "dflow",
+            Self::Okx => "okx",
+            Self::Hashflow => "hashflow",
+            Self::Other(router) => router,
+        })
+    }
+}
+
+impl FromStr for UltraRouter {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_ascii_lowercase().as_str() {
+            "metis" => Self::Metis,
+            "jupiterz" => Self::JupiterZ,
+            "dflow" => Self::Dflow,
+            "okx" => Self::Okx,
+            "hashflow" => Self::Hashflow,
+            _ => Self::Other(s.to_string()),
+        })
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UltraOrderRequest {
+    #[serde(with = "field_as_string")]
+    pub input_mint: Pubkey,
+    #[serde(with = "field_as_string")]
+    pub output_mint: Pubkey,
+    #[serde(with = "field_as_string")]
+    pub amount: u64,
+    /// Wallet signing the order transaction, no transaction is returned without it
+    #[serde(
+        with = "option_field_as_string",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub taker: Option<Pubkey>,
+    /// Routers not allowed to fill the order
+    #[serde(
+        with = "option_comma_separated",
+        skip_serializing_if = "option_comma_separated::is_none_or_empty"
+    )]
+    pub exclude_routers: Option<Vec<UltraRouter>>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UltraOrderResponse {
+    /// Identifies the order when executing it
+    pub request_id: String,
+    /// Unsigned transaction, only present when a taker was provided
+    #[serde(with = "option_base64_serialize_deserialize", default)]
+    pub transaction: Option<Vec<u8>>,
+    #[serde(with = "field_as_string")]
+    pub input_mint: Pubkey,
+    #[serde(with = "field_as_string")]
+    pub output_mint: Pubkey,
+    #[serde(with = "field_as_string")]
+    pub in_amount: u64,
+    #[serde(with = "field_as_string")]
+    pub out_amount: u64,
+    pub swap_mode: SwapMode,
+    pub slippage_bps: u16,
+    /// Router that produced the order
+    #[serde(with = "option_field_as_string", default)]
+    pub router: Option<UltraRouter>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UltraExecuteRequest {
+    /// Order transaction signed by the taker
+    #[serde(with = "base64_serialize_deserialize")]
+    pub signed_transaction: Vec<u8>,
+    pub request_id: String,
+    /// Sent in the [`IDEMPOTENCY_KEY_HEADER`](crate::IDEMPOTENCY_KEY_HEADER) header, generated
+    /// for every execution when unset and retries are enabled
+    #[serde(skip)]
+    pub idempotency_key: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UltraExecuteStatus {
+    Success,
+    Failed,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UltraExecuteResponse {
+    pub status: UltraExecuteStatus,
+    /// 0 on success, negative on failure
+    pub code: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(with = "option_field_as_string", default)]
+    pub signature: Option<Signature>,
+    #[serde(with = "option_field_as_string", default)]
+    pub slot: Option<u64>,
+    #[serde(with = "option_field_as_string", default)]
+    pub input_amount_result: Option<u64>,
+    #[serde(with = "option_field_as_string", default)]
+    pub output_amount_result: Option<u64>,
+}
+
+impl UltraExecuteResponse {
+    /// The transaction failed to land (yet) or the execution timed out on Jupiter's side, executing
+    /// the same request again reports its latest status
+    pub const PENDING_CODES: [i32; 2] = [-1000, -1006];
+
+    /// Whether executing the same request again cannot change the outcome
+    pub fn is_terminal(&self) -> bool {
+        self.status == UltraExecuteStatus::Success || !Self::PENDING_CODES.contains(&self.code)
+    }
+}
+
+/// Polling configuration of [`JupiterSwapApiClient::ultra_execute_until_terminal`]
+#[derive(Clone, Debug)]
+pub struct UltraPollConfig {
+    /// Overall time allowed to reach a terminal status
+    pub timeout: Duration,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for UltraPollConfig {
+    fn default() -> Self {
+        Self {
+            // Jupiter keeps the order available for execution for about 2 minutes
+            timeout: Duration::from_secs(120),
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Balance of a token held by a wallet
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UltraBalance {
+    #[serde(with = "field_as_string")]
+    pub amount: u64,
+    pub ui_amount: f64,
+    pub slot: u64,
+    pub is_frozen: bool,
+}
+
+/// Key of the native SOL balance in [`JupiterSwapApiClient::ultra_balances`]
+pub const ULTRA_SOL_BALANCE_KEY: &str = "SOL";
+
+/// Terminal outcome of an Ultra execution
+#[derive(Debug, Clone)]
+pub enum UltraExecutionResult {
+    Success(UltraExecuteResponse),
+    Failed(UltraExecuteResponse),
+    /// No terminal status was observed before the timeout
+    TimedOut {
+        last_response: Option<UltraExecuteResponse>,
+    },
+}
+
+impl JupiterSwapApiClient {
+    /// GET /order
+    pub async fn ultra_order(
+        &self,
+        order_request: &UltraOrderRequest,
+    ) -> Result<UltraOrderResponse, ClientError> {
+        self.in_flight(self.send_to(
+            |config| config.ultra_base_path.clone(),
+            "/order",
+            |client, url| client.get(url).query(order_request),
+        ))
+        .await
+    }
+
+    /// POST /execute
+    pub async fn ultra_execute(
+        &self,
+        execute_request: &UltraExecuteRequest,
+    ) -> Result<UltraExecuteResponse, ClientError> {
+        self.in_flight(self.send_idempotent(
+            |config| config.ultra_base_path.clone(),
+            "/execute",
+            execute_request.idempotency_key.as_deref(),
+            |client, url| client.post(url).json(execute_request),
+        ))
+        .await
+    }
+
+    /// GET /balances/{wallet}, keyed by mint, native SOL under [`ULTRA_SOL_BALANCE_KEY`]
+    pub async fn ultra_balances(
+        &self,
+        wallet: &Pubkey,
+    ) -> Result<HashMap<String, UltraBalance>, ClientError> {
+        self.in_flight(self.send_to(
+            |config| config.ultra_base_path.clone(),
+            &format!("/balances/{wallet}"),
+            |client, url| client.get(url),
+        ))
+        .await
+    }
+
+    /// Executes the signed order then keeps executing it with backoff, which Jupiter treats as a
+    /// status poll, until a terminal status is reached or `poll_config.timeout` elapses
+    ///
+    /// Retryable client errors are polled through, other errors are returned immediately.
+    pub async fn ultra_execute_until_terminal(
+        &self,
+        execute_request: &UltraExecuteRequest,
+        poll_config: &UltraPollConfig,
+    ) -> Result<UltraExecutionResult, ClientError> {
+        self.in_flight(async {
+            let deadline = tokio::time::Instant::now() + poll_config.timeout;
+            let mut backoff = poll_config.initial_backoff;
+            let mut last_response = None;
+            loop {
+                match self.ultra_execute(execute_request).await {
+                    Ok(response) if response.is_terminal() => {
+                        return Ok(match response.status {
+                            UltraExecuteStatus::Success => UltraExecutionResult::Success(response),
+                            UltraExecuteStatus::Failed => UltraExecutionResult::Failed(response),
+                        });
+                    }
+                    Ok(response) => last_response = Some(response),
+                    Err(error) if error.is_retryable() => (),
+                    Err(error) => return Err(error),
+                }
+
+                if tokio::time::Instant::now() + backoff > deadline {
+                    return Ok(UltraExecutionResult::TimedOut { last_response });
+                }
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(poll_config.max_backoff);
+            }
+        })
+        .await
+    }
+
+    /// Creates an order with `taker` as the taker, signs it and executes it until a terminal
+    /// status, the Ultra counterpart of quoting, building and sending a swap
+    #[cfg(feature = "solana")]
+    pub async fn ultra_swap(
+        &self,
+        order_request: &UltraOrderRequest,
+        taker: &dyn crate::signer::AsyncSigner,
+        poll_config: &UltraPollConfig,
+    ) -> Result<UltraExecutionResult, ClientError> {
+        self.in_flight(async {
+            let order_request = UltraOrderRequest {
+                taker: Some(taker.pubkey()),
+                ..order_request.clone()
+            };
+            let order_response = self.ultra_order(&order_request).await?;
+            let execute_request = order_response.sign(taker).await?.ok_or_else(|| {
+                ClientError::UltraOrderWithoutTransaction {
+                    request_id: order_response.request_id.clone(),
+                }
+            })?;
+            self.ultra_execute_until_terminal(&execute_request, poll_config)
+                .await
+        })
+        .await
+    }
+}